@@ -0,0 +1,86 @@
+//! Sums over the neighbors of an agent, one neighbor at a time from an array of
+//! structures against the structure of arrays batches the agents use. Batches pay
+//! for their extra passes once neighborhoods grow past a few dozen agents.
+
+use birds::batch::{self, Batch};
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use nannou::prelude::*;
+use nannou::rand::rngs::SmallRng;
+use nannou::rand::{Rng, SeedableRng};
+
+/// Neighbor as the agents store it, position and velocity side by side.
+struct Neighbor {
+    position: Vec2,
+    velocity: Vec2,
+    distance: f32,
+    weight: f32,
+}
+
+/// Weighted center, velocity and separation, one neighbor at a time.
+fn scalar(position: Vec2, neighbors: &[Neighbor]) -> (Vec2, Vec2, Vec2) {
+    let mut center = Vec2::ZERO;
+    let mut velocity = Vec2::ZERO;
+    let mut separation = Vec2::ZERO;
+    let mut total = 0.0;
+    for neighbor in neighbors {
+        center += neighbor.position * neighbor.weight;
+        velocity += neighbor.velocity * neighbor.weight;
+        separation += (position - neighbor.position) / neighbor.distance * neighbor.weight;
+        total += neighbor.weight;
+    }
+    (center / total, velocity / total, separation)
+}
+
+/// Weighted center, velocity and separation, summed in batches.
+fn batched(position: Vec2, batch: &Batch, weights: &[f32]) -> (Vec2, Vec2, Vec2) {
+    let total = batch::sum(weights);
+    (
+        batch.positions(weights) / total,
+        batch.velocities(weights) / total,
+        batch.away(position, weights),
+    )
+}
+
+fn neighbors(c: &mut Criterion) {
+    let mut rng = SmallRng::seed_from_u64(0);
+    let mut group = c.benchmark_group("neighbors");
+    for count in [8, 64, 512] {
+        let position = Vec2::ZERO;
+        let neighbors: Vec<Neighbor> = (0..count)
+            .map(|_| {
+                let offset = vec2(rng.gen_range(-50.0..50.0), rng.gen_range(-50.0..50.0));
+                Neighbor {
+                    position: position + offset,
+                    velocity: vec2(rng.gen_range(-2.0..2.0), rng.gen_range(-2.0..2.0)),
+                    distance: offset.length().max(1.0),
+                    weight: rng.gen_range(0.0..1.0),
+                }
+            })
+            .collect();
+
+        let mut batch = Batch::with_capacity(count);
+        for neighbor in &neighbors {
+            batch.push(neighbor.position, neighbor.velocity, neighbor.distance);
+        }
+        let weights: Vec<f32> = neighbors.iter().map(|neighbor| neighbor.weight).collect();
+
+        // Both paths agree up to the order the floats are summed in
+        assert!(!batch.is_empty());
+        let (center, velocity, separation) = scalar(position, &neighbors);
+        let (batch_center, batch_velocity, batch_separation) = batched(position, &batch, &weights);
+        assert!(center.distance(batch_center) < 1e-3);
+        assert!(velocity.distance(batch_velocity) < 1e-3);
+        assert!(separation.distance(batch_separation) < 1e-3);
+
+        group.bench_with_input(BenchmarkId::new("scalar", count), &neighbors, |b, n| {
+            b.iter(|| scalar(black_box(position), black_box(n)))
+        });
+        group.bench_with_input(BenchmarkId::new("batched", count), &batch, |b, batch| {
+            b.iter(|| batched(black_box(position), black_box(batch), black_box(&weights)))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, neighbors);
+criterion_main!(benches);
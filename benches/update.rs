@@ -0,0 +1,101 @@
+//! One update of every agent of synthetic flocks of growing size, finding
+//! neighbors by scanning every agent or through the spatial hash grid, with the
+//! agents in random order or sorted by grid cell.
+
+use birds::agent::Agent;
+use birds::agents::Agents;
+use birds::config::Config;
+use birds::flow_field::FlowField;
+use birds::index::Indexing;
+use birds::scratch::Scratch;
+use birds::spatial_hash::CellSort;
+use birds::world::World;
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use nannou::prelude::*;
+use nannou::rand::rngs::SmallRng;
+use nannou::rand::SeedableRng;
+use noise::Perlin;
+
+/// Agents per million square pixels, about the density of the sketch's initial flock
+const DENSITY: f32 = 100.0;
+
+/// Flock of a number of agents spread over a square holding them at the density.
+fn flock(count: usize, config: &Config, rng: &mut SmallRng) -> (Rect, Agents) {
+    let side = (count as f32 / DENSITY * 1e6).sqrt();
+    let bounds = Rect::from_w_h(side, side);
+    let agents = (0..count)
+        .map(|i| Agent::new(bounds, i % config.species.len(), config, rng))
+        .collect();
+    (bounds, agents)
+}
+
+/// Update every agent once, as the sketch does on the plane with every feature off.
+fn update(
+    config: &Config,
+    bounds: Rect,
+    agents: &Agents,
+    indexing: Indexing,
+    flow_field: &FlowField,
+    noise: &Perlin,
+) -> Agents {
+    let index = indexing.build(agents);
+    let world = World::plain(config, bounds, agents, index.as_ref(), flow_field, noise);
+    let mut scratch = Scratch::default();
+    let mut next = agents.clone();
+    next.for_each_mut(|agent| agent.update(&world, &mut scratch));
+    next
+}
+
+fn agents(c: &mut Criterion) {
+    let config = Config::get();
+    let mut rng = SmallRng::seed_from_u64(0);
+    let flow_field = FlowField::new(&mut rng);
+    let noise = Perlin::new(0);
+    let mut group = c.benchmark_group("update");
+    group.sample_size(10);
+    for count in [100, 1_000, 10_000] {
+        let (bounds, agents) = flock(count, &config, &mut rng);
+        for (name, indexing) in [
+            ("brute force", Indexing::BruteForce),
+            ("grid", Indexing::Grid),
+        ] {
+            group.bench_with_input(BenchmarkId::new(name, count), &agents, |b, agents| {
+                b.iter(|| {
+                    update(
+                        &config,
+                        bounds,
+                        black_box(agents),
+                        indexing,
+                        &flow_field,
+                        &noise,
+                    )
+                })
+            });
+        }
+
+        // Sorting by cell first, counted in the time, so neighbors lie close in memory
+        group.bench_with_input(
+            BenchmarkId::new("sorted grid", count),
+            &agents,
+            |b, agents| {
+                let mut cell_sort = CellSort::new();
+                b.iter(|| {
+                    let mut agents = black_box(agents).clone();
+                    cell_sort.sort(&mut agents);
+                    update(
+                        &config,
+                        bounds,
+                        &agents,
+                        Indexing::Grid,
+                        &flow_field,
+                        &noise,
+                    )
+                })
+            },
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, agents);
+criterion_main!(benches);
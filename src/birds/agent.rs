@@ -0,0 +1,1260 @@
+use crate::avoidance::Avoidance;
+use crate::batch;
+use crate::brain::Brain;
+use crate::color_mode::ColorMode;
+use crate::config::Config;
+use crate::flight::Flight;
+use crate::flocks::Flocks;
+use crate::instancing::Instance;
+use crate::integrator::Integrator;
+use crate::lod::Sketch;
+use crate::movement::Movement;
+use crate::parameters::Parameters;
+use crate::perception::Perception;
+use crate::personality::Personality;
+use crate::scratch::Scratch;
+use crate::species::Species;
+use crate::world::World;
+use nannou::prelude::*;
+use nannou::rand::rngs::SmallRng;
+use nannou::rand::{Rng, SeedableRng};
+use noise::NoiseFn;
+
+/// What an agent is currently doing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum State {
+    Flying,
+    /// Sitting at the bottom of the window for the given number of frames
+    Perched(u32),
+    /// Climbing away from the perch for the given number of frames
+    TakingOff(u32),
+}
+
+/// What an agent perceives of its neighbors.
+#[derive(Debug, Default)]
+struct Neighborhood {
+    /// Weighted average position and velocity of the neighbors to flock with
+    center: Option<Vec2>,
+    velocity: Option<Vec2>,
+    /// Pushes away from neighbors that are too close or avoided
+    separation: Vec2,
+    avoidance: Vec2,
+    count: usize,
+    /// Whether any neighbor is spreading panic
+    alarmed: bool,
+}
+
+impl Neighborhood {
+    /// How crowded the agent is, from 0 when alone to 1 when surrounded.
+    fn crowding(&self) -> f32 {
+        (self.count as f32 / Agent::CROWD_SIZE).min(1.0)
+    }
+}
+
+/// Steering forces of the flocking rules in the last step of an agent.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RuleForces {
+    pub separation: Vec2,
+    pub alignment: Vec2,
+    pub cohesion: Vec2,
+}
+
+/// Seeds of the first few neighbors an agent perceived in its last step, which
+/// still name them once the flock was sorted or agents replaced.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Perceived {
+    seeds: [u64; Self::CAPACITY],
+    len: usize,
+}
+
+impl Perceived {
+    pub const CAPACITY: usize = 6;
+
+    fn record(&mut self, neighbors: &[(usize, f32)], seeds: &[u64]) {
+        self.len = neighbors.len().min(Self::CAPACITY);
+        for (seed, &(i, _)) in self.seeds.iter_mut().zip(neighbors) {
+            *seed = seeds[i];
+        }
+    }
+
+    pub fn seeds(&self) -> &[u64] {
+        &self.seeds[..self.len]
+    }
+
+    pub fn contains(&self, seed: u64) -> bool {
+        self.seeds().contains(&seed)
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Agent {
+    pub position: Vec2,
+    pub velocity: Vec2,
+    /// Change in velocity over the last step, carried over by Verlet integration
+    pub acceleration: Vec2,
+    pub species: usize,
+    pub state: State,
+    pub parameters: Parameters,
+    /// Offset of the agent in the wander noise, so every agent meanders differently
+    pub wander_seed: f64,
+    /// Seed of the random draws of the agent, mixed with the frame so they don't
+    /// depend on which thread updates the agent. Also identifies the agent, as its
+    /// place in the flock changes when agents are sorted or replaced
+    pub seed: u64,
+    pub energy: f32,
+    pub leader: bool,
+    /// Frames of panic left
+    pub panic: u32,
+    /// Frames before the agent panics after noticing a panicked neighbor
+    pub alarm: Option<u32>,
+    /// Frames lived and frames the agent will live for
+    pub age: u32,
+    pub lifespan: u32,
+    /// How hungry the agent is, from 0 when fed to 1 when starving
+    pub hunger: f32,
+    /// Frames the agent stays rallied by a call it heard
+    pub rallied: u32,
+    pub personality: Personality,
+    /// Flap and glide phases of the agent, when its flight costs energy
+    pub flight: Option<Flight>,
+    /// Connected flock the agent was last found in
+    pub flock: Option<u32>,
+    /// Neighbors the agent perceived in its last step
+    pub neighbors: usize,
+    /// First of those neighbors
+    pub perceived: Perceived,
+    /// Forces of the flocking rules in its last step, zero unless it followed them
+    pub rule_forces: RuleForces,
+}
+
+impl Agent {
+    const AVOIDANCE_WEIGHT: f32 = 1.0;
+    const FLEE_WEIGHT: f32 = 4.0;
+    const OBSTACLE_WEIGHT: f32 = 4.0;
+    const WAYPOINT_WEIGHT: f32 = 0.3;
+    const BOUNDARY_WEIGHT: f32 = 4.0;
+    const WANDER_WEIGHT: f32 = 0.3;
+    const LEADER_WEIGHT: f32 = 1.5;
+    const MOUSE_WEIGHT: f32 = 3.0;
+    const PHEROMONE_WEIGHT: f32 = 1.0;
+    const FORMATION_WEIGHT: f32 = 2.0;
+    const MIGRATION_WEIGHT: f32 = 0.15;
+    const TERRAIN_WEIGHT: f32 = 0.5;
+    const FOOD_WEIGHT: f32 = 2.0;
+    const PATH_WEIGHT: f32 = 1.0;
+    const ATTRACTOR_WEIGHT: f32 = 1.0;
+    const ZONES_WEIGHT: f32 = 2.0;
+
+    // Startled agents speed up and alarm their neighbors for a short while,
+    // who panic in turn after a delay
+    const PANIC_COLOR: (f32, f32, f32, f32) = (1.0, 0.3, 0.1, 1.0);
+    const PANIC_DURATION: u32 = 90;
+    const PANIC_SPREAD_WINDOW: u32 = 10;
+    const PANIC_DELAY: u32 = 6;
+    const PANIC_SPEED_BOOST: f32 = 0.8;
+
+    // Agents fade out at the end of their life
+    const LIFESPAN: (u32, u32) = (1800, 5400);
+    const FADE_DURATION: u32 = 120;
+
+    // At midnight agents fly this much slower and flock this much more tightly
+    const NIGHT_SLOWDOWN: f32 = 0.4;
+    const NIGHT_COHESION: f32 = 1.0;
+
+    // Agents that heard a call flock this much more tightly for this many frames
+    const RALLY_COHESION: f32 = 1.5;
+
+    // Weight of the pull of the distant flock relative to the cohesion with neighbors
+    const LONG_RANGE_COHESION: f32 = 0.3;
+    const RALLY_DURATION: u32 = 90;
+
+    // Hunger gained every frame while foraging and the hunger at which agents seek food
+    const HUNGER_RATE: f32 = 1.0 / 1200.0;
+    const HUNGRY: f32 = 0.5;
+
+    // Banking agents turn at most this many radians and change speed at most this much every frame
+    const BANK_TURN_RATE: f32 = 0.04;
+    const BANK_ACCELERATION: f32 = 0.02;
+
+    // Whiskers probe this far ahead and this far to the sides
+    const WHISKER_LENGTH: f32 = 80.0;
+    const WHISKER_ANGLE: f32 = 0.4;
+
+    // Number of nearest neighbors agents interact with in topological mode, as in starlings
+    const TOPOLOGICAL_NEIGHBORS: usize = 7;
+
+    // Number of neighbors at which an agent is considered crowded
+    const CROWD_SIZE: f32 = 10.0;
+
+    // Physical movement: fraction of the velocity lost to drag every frame, growing
+    // with crowding, and the range of speeds relative to the cruising speed
+    const DRAG: f32 = 0.02;
+    const CROWD_DRAG: f32 = 1.0;
+    const OPEN_SPEED: f32 = 1.3;
+    const MIN_SPEED: f32 = 0.3;
+
+    // Followers aim for a spot this far behind their leader
+    const FOLLOW_DISTANCE: f32 = 30.0;
+
+    // Drafting agents aim for a spot this far behind and beside their wing-neighbor
+    const DRAFT_OFFSET: (f32, f32) = (20.0, 20.0);
+
+    // Largest angle between the heading and the wander direction
+    const WANDER_ANGLE: f32 = 0.5 * PI;
+    const WANDER_RATE: f64 = 0.3;
+    const WANDER_SEED_RANGE: f64 = 1e4;
+
+    /// Odd constant spreading successive steps across the seeds of the random draws
+    const STEP_MIX: u64 = 0x9e37_79b9_7f4a_7c15;
+
+    // Agents moving further than this in a step are drawn without interpolation
+    const MAX_INTERPOLATED_JUMP: f32 = 50.0;
+
+    // Agents flying this close to the bottom of the window may land
+    const PERCH_HEIGHT: f32 = 40.0;
+    const PERCH_CHANCE: f32 = 0.005;
+    const PERCH_DURATION: (u32, u32) = (120, 600);
+    const TAKEOFF_DURATION: u32 = 45;
+
+    pub fn new(win_rect: Rect, species: usize, config: &Config, rng: &mut impl Rng) -> Self {
+        // Random position
+        let position = vec2(
+            rng.gen_range(win_rect.left()..win_rect.right()),
+            rng.gen_range(win_rect.bottom()..win_rect.top()),
+        );
+        Self::spawn(position, species, config, rng)
+    }
+
+    /// Create an agent of the given species at a position, flying in a random direction.
+    pub fn spawn(position: Vec2, species: usize, config: &Config, rng: &mut impl Rng) -> Self {
+        // Random velocity
+        let parameters = Parameters::random(config, species, rng);
+        let velocity = Vec2::new(rng.gen_range(-1.0..1.0), rng.gen_range(-1.0..1.0)).normalize()
+            * parameters.speed;
+
+        // Return new agent
+        Agent {
+            position,
+            velocity,
+            acceleration: Vec2::ZERO,
+            species,
+            state: State::Flying,
+            parameters,
+            wander_seed: rng.gen_range(0.0..Self::WANDER_SEED_RANGE),
+            seed: rng.gen(),
+            energy: rng.gen(),
+            leader: false,
+            panic: 0,
+            alarm: None,
+            age: 0,
+            lifespan: rng.gen_range(Self::LIFESPAN.0..Self::LIFESPAN.1),
+            hunger: rng.gen::<f32>() * Self::HUNGRY,
+            rallied: 0,
+            personality: Personality::random(rng),
+            flight: None,
+            flock: None,
+            neighbors: 0,
+            perceived: Perceived::default(),
+            rule_forces: RuleForces::default(),
+        }
+    }
+
+    /// Whether the agent is hungry enough to leave the flock in search of food.
+    pub fn is_hungry(&self) -> bool {
+        self.hunger >= Self::HUNGRY
+    }
+
+    pub fn is_perched(&self) -> bool {
+        matches!(self.state, State::Perched(_))
+    }
+
+    /// Radius of the circle enclosing the body of the agent.
+    pub fn radius(&self, config: &Config) -> f32 {
+        self.species(config).radius()
+    }
+
+    /// Move the agent by a displacement, reversing its velocity along the edges it bounces off.
+    fn step(&mut self, world: &World, displacement: Vec2) {
+        let moved;
+        (self.position, moved) = world
+            .boundary
+            .step(self.position, displacement, &world.bounds);
+        let flip = |before: f32, after: f32| if before == after { 1.0 } else { -1.0 };
+        self.velocity *= vec2(flip(displacement.x, moved.x), flip(displacement.y, moved.y));
+    }
+
+    /// Create an agent of the given species on the edge of the window, flying inwards.
+    pub fn new_at_edge(
+        win_rect: Rect,
+        species: usize,
+        config: &Config,
+        rng: &mut impl Rng,
+    ) -> Self {
+        // Random point along the perimeter of the window
+        let perimeter = 2.0 * (win_rect.w() + win_rect.h());
+        let mut distance = rng.gen_range(0.0..perimeter);
+        let position = if distance < win_rect.w() {
+            vec2(win_rect.left() + distance, win_rect.bottom())
+        } else if distance < win_rect.w() + win_rect.h() {
+            distance -= win_rect.w();
+            vec2(win_rect.right(), win_rect.bottom() + distance)
+        } else if distance < 2.0 * win_rect.w() + win_rect.h() {
+            distance -= win_rect.w() + win_rect.h();
+            vec2(win_rect.right() - distance, win_rect.top())
+        } else {
+            distance -= 2.0 * win_rect.w() + win_rect.h();
+            vec2(win_rect.left(), win_rect.top() - distance)
+        };
+
+        let mut agent = Self::spawn(position, species, config, rng);
+        agent.velocity = (win_rect.xy() - position).normalize_or_zero() * agent.parameters.speed;
+        agent
+    }
+
+    /// Grow older by a frame, returning whether the agent is still alive.
+    pub fn grow_older(&mut self) -> bool {
+        self.age += 1;
+        self.age < self.lifespan
+    }
+
+    /// Opacity of the agent, fading out as it reaches the end of its life.
+    fn fade(&self) -> f32 {
+        let remaining = self.lifespan.saturating_sub(self.age);
+        (remaining as f32 / Self::FADE_DURATION as f32).min(1.0)
+    }
+
+    /// Make the agent panic immediately.
+    pub fn startle(&mut self) {
+        self.panic = Self::PANIC_DURATION;
+        self.alarm = None;
+    }
+
+    /// Rally the agent around its neighbors for a while.
+    pub fn hear_call(&mut self) {
+        self.rallied = Self::RALLY_DURATION;
+    }
+
+    /// Freshly panicked agents alarm their neighbors.
+    pub fn spreads_panic(&self) -> bool {
+        Self::spreads(self.panic)
+    }
+
+    /// Whether an agent with this many frames of panic left alarms its neighbors.
+    pub fn spreads(panic: u32) -> bool {
+        panic > Self::PANIC_DURATION - Self::PANIC_SPREAD_WINDOW
+    }
+
+    fn panic_level(&self) -> f32 {
+        self.panic as f32 / Self::PANIC_DURATION as f32
+    }
+
+    /// Personality of the agent if traits are enabled.
+    fn traits(&self, world: &World) -> Personality {
+        if world.personalities {
+            self.personality
+        } else {
+            Personality::NEUTRAL
+        }
+    }
+
+    /// Generator of the random draws of the agent during a step.
+    fn rng(&self, step: u64) -> SmallRng {
+        SmallRng::seed_from_u64(self.seed ^ step.wrapping_mul(Self::STEP_MIX))
+    }
+
+    pub fn update(&mut self, world: &World, scratch: &mut Scratch) {
+        let mut rng = self.rng(world.step);
+        let config = &world.config.agent;
+        let panic_radius = config.panic_radius * self.traits(world).fearfulness();
+
+        // Panic wears off over time while pending alarms turn into panic
+        self.panic = self.panic.saturating_sub(1);
+        self.rallied = self.rallied.saturating_sub(1);
+        match self.alarm {
+            Some(0) => self.startle(),
+            Some(frames) => self.alarm = Some(frames - 1),
+            None => {}
+        }
+
+        match self.state {
+            State::Flying => {}
+            State::Perched(frames) => {
+                // Agent takes off when rested or when a predator comes too close
+                let threatened = world.predators.iter().any(|predator| {
+                    predator.position.distance_squared(self.position) < panic_radius * panic_radius
+                });
+                if threatened {
+                    self.startle();
+                }
+                self.state = if frames == 0 || threatened {
+                    self.velocity = vec2(self.velocity.x.signum() * 0.5, 1.0).normalize()
+                        * self.parameters.speed;
+                    State::TakingOff(Self::TAKEOFF_DURATION)
+                } else {
+                    State::Perched(frames - 1)
+                };
+                return;
+            }
+            State::TakingOff(frames) => {
+                // Agent climbs straight up before rejoining the flock
+                self.step(world, self.velocity);
+                self.state = if frames == 0 {
+                    State::Flying
+                } else {
+                    State::TakingOff(frames - 1)
+                };
+                return;
+            }
+        }
+
+        // Move agent, after the forces for semi-implicit Euler
+        // Constant speed movement sets the velocity instead of integrating forces
+        let integrator = match world.movement {
+            Movement::Classic => Integrator::Euler,
+            Movement::Physical => world.integrator,
+        };
+        match integrator {
+            Integrator::Euler => self.step(world, self.velocity),
+            Integrator::SemiImplicit => {}
+            Integrator::Verlet => self.step(world, self.velocity + self.acceleration * 0.5),
+        }
+
+        // Neighbors are only perceived inside the vision cone
+        let heading = self.velocity.normalize_or_zero();
+
+        // Accumulate the steering forces of every rule
+        // Leaders ignore the flocking rules while the others follow the nearest leader
+        let mut force = Vec2::default();
+        let mut crowding = 0.0;
+        self.neighbors = 0;
+        self.perceived = Perceived::default();
+        self.rule_forces = RuleForces::default();
+        if !self.leader {
+            let neighborhood = self.perceive(world, heading, scratch);
+            crowding = neighborhood.crowding();
+            self.neighbors = neighborhood.count;
+            self.perceived
+                .record(&scratch.neighbors, &world.agents.seeds);
+            force += match world.brain {
+                Brain::Rules => self.rules_force(world, &neighborhood),
+                Brain::Neural => self.neural_force(world, &neighborhood, heading),
+                Brain::Zones => {
+                    self.zones_force(world, heading, &mut scratch.candidates) * Self::ZONES_WEIGHT
+                }
+            };
+            force += self.follow_force(world) * Self::LEADER_WEIGHT;
+
+            // Agent should draft diagonally behind a wing-neighbor
+            if world.formation {
+                force += self.formation_force(world, heading) * Self::FORMATION_WEIGHT;
+            }
+
+            // Agent panics shortly after noticing a panicked neighbor
+            if neighborhood.alarmed && self.panic == 0 && self.alarm.is_none() {
+                self.alarm = Some(Self::PANIC_DELAY);
+            }
+        }
+
+        // Agent should flee from predators inside its panic radius
+        // The closer the predator, the stronger the force, shy agents fleeing earlier and harder
+        let mut flee = Vec2::default();
+        for predator in world.predators {
+            let away = self.position - predator.position;
+            let distance_squared = away.length_squared();
+            if distance_squared < panic_radius * panic_radius && distance_squared > 0.0 {
+                let distance = distance_squared.sqrt();
+                flee += away / distance * (1.0 - distance / panic_radius);
+            }
+        }
+        if flee != Vec2::ZERO {
+            self.startle();
+        }
+        force += self.steer(world, flee) * Self::FLEE_WEIGHT * self.traits(world).fearfulness();
+
+        // Agent should steer around obstacles and walls
+        force += match world.avoidance {
+            Avoidance::Radial => self.radial_avoidance(world),
+            Avoidance::Whiskers => self.whisker_avoidance(world, heading),
+        } * Self::OBSTACLE_WEIGHT;
+
+        // Agent should drift with the wind
+        force += world.flow_field.sample(self.position, world.time);
+
+        // Agent should be pulled in or pushed away by the placed force points
+        for point in world.force_points {
+            force += point.force(self.position);
+        }
+
+        // Agent should be swirled around by the placed vortices
+        for vortex in world.vortices {
+            force += vortex.force(self.position);
+        }
+
+        // Agent should be gently attracted to the current waypoint
+        if let Some(waypoint) = world.waypoint {
+            force += self.steer(world, waypoint - self.position) * Self::WAYPOINT_WEIGHT;
+        }
+
+        // Agent should follow the slope of the terrain
+        if let Some(terrain) = world.terrain {
+            let slope = terrain.force(self.position);
+            force += self.steer(world, slope) * slope.length() * Self::TERRAIN_WEIGHT;
+        }
+
+        // Agent should break from the flock to fly to the nearest food when hungry
+        if let Some(food) = world.food {
+            self.hunger = (self.hunger + Self::HUNGER_RATE).min(1.0);
+            let nearest = food.iter().min_by(|a, b| {
+                let distance_a = a.distance_squared(self.position);
+                let distance_b = b.distance_squared(self.position);
+                distance_a.total_cmp(&distance_b)
+            });
+            if let Some(item) = nearest.filter(|_| self.is_hungry()) {
+                force += self.steer(world, *item - self.position) * self.hunger * Self::FOOD_WEIGHT;
+            }
+        }
+
+        // Agent should flow along the path
+        if let Some(path) = world.path {
+            force += self.steer(world, path.flow(self.position)) * Self::PATH_WEIGHT;
+        }
+
+        // Agent should chase the moving attractor
+        if let Some(attractor) = world.attractor {
+            force += self.steer(world, attractor - self.position) * Self::ATTRACTOR_WEIGHT;
+        }
+
+        // Agent should be weakly pulled towards the distant migration target
+        if let Some(migration) = world.migration {
+            force += self.steer(world, migration - self.position) * Self::MIGRATION_WEIGHT;
+        }
+
+        // Agent should be pulled towards or pushed away from the cursor
+        let mouse = world.mouse_force.force(self.position, world.mouse);
+        force += self.steer(world, mouse) * mouse.length() * Self::MOUSE_WEIGHT;
+
+        // Agent should follow the pheromone trails left by others
+        if let Some(pheromones) = world.pheromones {
+            let trail = pheromones.gradient(self.position);
+            force += self.steer(world, trail) * trail.length().min(1.0) * Self::PHEROMONE_WEIGHT;
+        }
+
+        // Agent should meander instead of flying in a straight line
+        let wander = world
+            .noise
+            .get([self.wander_seed, world.time as f64 * Self::WANDER_RATE])
+            as f32;
+        force +=
+            self.steer(world, heading.rotate(wander * Self::WANDER_ANGLE)) * Self::WANDER_WEIGHT;
+
+        // Agent should turn back before reaching the edges of the window
+        let back = world
+            .boundary
+            .steer(self.position, &world.bounds, world.margin);
+        force += self.steer(world, back) * back.length().min(1.0) * Self::BOUNDARY_WEIGHT;
+
+        // Agent speeds up while flapping and slows down while gliding to recover
+        self.flight = world
+            .flapping
+            .then(|| self.flight.unwrap_or_else(|| Flight::random(&mut rng)));
+        let stroke = self
+            .flight
+            .as_mut()
+            .map_or(1.0, |flight| flight.update(&mut rng));
+
+        // Panicked agents fly faster and all agents slow down at night
+        let speed = self.parameters.speed
+            * stroke
+            * (1.0 + self.panic_level() * Self::PANIC_SPEED_BOOST)
+            * (1.0 - world.darkness * Self::NIGHT_SLOWDOWN);
+        let force = force.clamp_length_max(config.max_force);
+        let previous_velocity = self.velocity;
+
+        match world.movement {
+            Movement::Classic => {
+                // Apply the steering force, limiting how fast the agent can turn
+                let desired = self.velocity + force;
+                let turn = self
+                    .velocity
+                    .perp_dot(desired)
+                    .atan2(self.velocity.dot(desired))
+                    .clamp(-config.max_turn_rate, config.max_turn_rate);
+
+                // Normalize velocity and set speed
+                self.velocity = self.velocity.rotate(turn).normalize() * speed;
+            }
+            Movement::Physical => {
+                // Agent thrusts forward against a drag that grows as it gets crowded,
+                // speeding up in open space and slowing down in dense flocks
+                let thrust = heading * speed * Self::OPEN_SPEED * Self::DRAG;
+                let drag = Self::DRAG * (1.0 + crowding * Self::CROWD_DRAG);
+                self.velocity += force + thrust - self.velocity * drag;
+                self.velocity = self
+                    .velocity
+                    .clamp_length(speed * Self::MIN_SPEED, speed * Self::OPEN_SPEED);
+            }
+        }
+
+        // Agent banks into turns and eases into speed changes
+        if world.banking {
+            self.velocity = Self::bank(previous_velocity, self.velocity);
+        }
+
+        // Agent's heading is perturbed by random turns
+        if world.vicsek.noise > 0.0 {
+            self.velocity = self.velocity.rotate(world.vicsek.turn(&mut rng));
+        }
+
+        // Velocity Verlet averages the accelerations at the start and the end of the step
+        let acceleration = self.velocity - previous_velocity;
+        match integrator {
+            Integrator::Euler => {}
+            Integrator::SemiImplicit => self.step(world, self.velocity),
+            Integrator::Verlet => {
+                self.velocity = previous_velocity + (self.acceleration + acceleration) * 0.5;
+            }
+        }
+        self.acceleration = acceleration;
+
+        // Agent may land when flying close to the ground
+        if self.position.y < world.bounds.bottom() + Self::PERCH_HEIGHT
+            && rng.gen::<f32>() < Self::PERCH_CHANCE
+        {
+            self.perch(&world.bounds, world.config, &mut rng);
+        }
+    }
+
+    /// Fill `neighbors` with the indices of the neighbors the agent perceives inside
+    /// its vision cone and in plain sight, with their distance, either those inside
+    /// its detection radius or only the nearest ones.
+    fn neighbors(
+        &self,
+        world: &World,
+        heading: Vec2,
+        candidates: &mut Vec<usize>,
+        neighbors: &mut Vec<(usize, f32)>,
+    ) {
+        let min_alignment = (world.config.agent.field_of_view * 0.5).cos();
+        let positions = &world.agents.positions;
+
+        // Neighbors hidden behind obstacles or walls can't be seen
+        let visible = |i: usize, distance: f32| {
+            distance > 0.0
+                && !world.agents.is_perched(i)
+                && self.sees(positions[i], heading, distance, min_alignment)
+                && world.line_of_sight(self.position, positions[i])
+        };
+
+        match world.perception {
+            Perception::Metric => {
+                // Compare squared distances, only taking the root of those in range
+                let range = self.parameters.detection_radius;
+                world.around(self, range, candidates);
+                neighbors.clear();
+                neighbors.extend(
+                    candidates
+                        .iter()
+                        .filter(|&&i| self.position.distance_squared(positions[i]) < range * range)
+                        .map(|&i| (i, self.position.distance(positions[i])))
+                        .filter(|&(i, distance)| visible(i, distance)),
+                );
+            }
+            Perception::Topological => world.nearest(
+                self.position,
+                Self::TOPOLOGICAL_NEIGHBORS,
+                visible,
+                neighbors,
+            ),
+        }
+    }
+
+    /// Gather what the agent perceives of its neighbors.
+    fn perceive(&self, world: &World, heading: Vec2, scratch: &mut Scratch) -> Neighborhood {
+        let Scratch {
+            candidates,
+            neighbors,
+            batch,
+            alignment,
+            cohesion,
+            separation,
+            avoidance,
+            ..
+        } = scratch;
+        self.neighbors(world, heading, candidates, neighbors);
+        let mut neighborhood = Neighborhood {
+            count: neighbors.len(),
+            ..Default::default()
+        };
+
+        // Weigh every neighbor, then sum them up in batches
+        batch.clear();
+        alignment.clear();
+        cohesion.clear();
+        separation.clear();
+        avoidance.clear();
+        let agents = world.agents;
+        let config = world.config;
+        let min_distance = config.agent.min_distance;
+        for &(i, distance) in neighbors.iter() {
+            neighborhood.alarmed |= agents.spreads_panic(i);
+            batch.push(agents.positions[i], agents.velocities[i], distance);
+
+            // The rules applied depend on the species of both agents
+            let weights = config.interactions.between(self.species, agents.species[i]);
+
+            // Closer neighbors may count more in the averages
+            let closeness = world
+                .kernel
+                .weight(distance, self.parameters.detection_radius);
+            alignment.push(weights.alignment * closeness);
+            cohesion.push(weights.cohesion * closeness);
+
+            // Make sure to keep a minimum distance to other agents
+            // The closer the agent, the stronger the force
+            separation.push(if distance < min_distance {
+                weights.separation * (1.0 - distance / min_distance)
+            } else {
+                0.0
+            });
+
+            // Steer away from agents this species avoids
+            let proximity = 1.0 - distance / self.parameters.detection_radius;
+            avoidance.push(weights.avoidance * proximity.max(0.0));
+        }
+        if batch.is_empty() {
+            return neighborhood;
+        }
+
+        neighborhood.separation = batch.away(self.position, separation);
+        neighborhood.avoidance = batch.away(self.position, avoidance);
+
+        // Calculate average position and velocity
+        let cohesion_weight = batch::sum(cohesion);
+        if cohesion_weight > 0.0 {
+            neighborhood.center = Some(batch.positions(cohesion) / cohesion_weight);
+        }
+        let alignment_weight = batch::sum(alignment);
+        if alignment_weight > 0.0 {
+            neighborhood.velocity = Some(batch.velocities(alignment) / alignment_weight);
+        }
+
+        neighborhood
+    }
+
+    /// Limit the change from the previous to the new velocity, the heading
+    /// turning at a limited rate and the speed changing gradually.
+    fn bank(previous: Vec2, velocity: Vec2) -> Vec2 {
+        if previous == Vec2::ZERO {
+            return velocity;
+        }
+        let turn = previous
+            .perp_dot(velocity)
+            .atan2(previous.dot(velocity))
+            .clamp(-Self::BANK_TURN_RATE, Self::BANK_TURN_RATE);
+        let speed = previous.length()
+            + (velocity.length() - previous.length())
+                .clamp(-Self::BANK_ACCELERATION, Self::BANK_ACCELERATION);
+        previous.normalize().rotate(turn) * speed
+    }
+
+    /// Steering force pushing the agent away from the obstacles and walls inside
+    /// its detection range. The closer the obstacle, the stronger the force.
+    fn radial_avoidance(&self, world: &World) -> Vec2 {
+        let mut avoid_obstacles = Vec2::default();
+        for obstacle in world.obstacles {
+            let distance = obstacle.distance(self.position);
+            if distance < self.parameters.detection_radius {
+                let away = (self.position - obstacle.position).normalize_or_zero();
+                let closeness = 1.0 - distance.max(0.0) / self.parameters.detection_radius;
+                avoid_obstacles += away * closeness * closeness;
+            }
+        }
+
+        // Walls push the agent away from their closest point
+        let mut avoid_walls = Vec2::default();
+        for wall in world.walls {
+            let away = self.position - wall.closest_point(self.position);
+            let distance = away.length();
+            if distance < self.parameters.detection_radius {
+                let closeness = 1.0 - distance / self.parameters.detection_radius;
+                avoid_walls += away.normalize_or_zero() * closeness * closeness;
+            }
+        }
+
+        self.steer(world, avoid_obstacles) + self.steer(world, avoid_walls)
+    }
+
+    /// Steering force turning the agent away from the obstacles and walls its
+    /// whiskers run into, towards the side with the most room.
+    fn whisker_avoidance(&self, world: &World, heading: Vec2) -> Vec2 {
+        // Distance each whisker reaches before hitting something
+        let [ahead, left, right] = [0.0, Self::WHISKER_ANGLE, -Self::WHISKER_ANGLE]
+            .map(|angle| self.probe(world, heading.rotate(angle)));
+        let urgency = |reach: f32| 1.0 - reach / Self::WHISKER_LENGTH;
+
+        // Turn away from a hit on either side, and around a hit straight ahead
+        let around = if left >= right { 1.0 } else { -1.0 };
+        let turn = urgency(right) - urgency(left) + urgency(ahead) * around;
+        if turn == 0.0 {
+            return Vec2::ZERO;
+        }
+        self.steer(world, heading.perp() * turn.signum()) * turn.abs().min(1.0)
+    }
+
+    /// Distance a whisker pointing in a direction reaches before hitting an
+    /// obstacle or a wall, its full length when nothing is in the way.
+    fn probe(&self, world: &World, direction: Vec2) -> f32 {
+        let obstacles = world
+            .obstacles
+            .iter()
+            .filter_map(|obstacle| obstacle.raycast(self.position, direction));
+        let walls = world
+            .walls
+            .iter()
+            .filter_map(|wall| wall.raycast(self.position, direction));
+        obstacles.chain(walls).fold(Self::WHISKER_LENGTH, f32::min)
+    }
+
+    /// Steering force of the weighted flocking rules.
+    fn rules_force(&mut self, world: &World, neighborhood: &Neighborhood) -> Vec2 {
+        let mut force = Vec2::default();
+
+        // Agent should keep a minimum distance to its neighbors
+        self.rule_forces.separation =
+            self.steer(world, neighborhood.separation) * self.parameters.separation_weight;
+        force += self.rule_forces.separation;
+
+        // Social agents stick with their neighbors more than loners
+        let gregariousness = self.traits(world).gregariousness();
+
+        // Agent should move towards the same direction as its neighbors
+        if let Some(velocity) = neighborhood.velocity {
+            self.rule_forces.alignment =
+                self.steer(world, velocity) * self.parameters.alignment_weight * gregariousness;
+            force += self.rule_forces.alignment;
+        }
+
+        // Agent should move towards the average position of its neighbors to stay with them
+        // Flocks tighten up at night or when called, and loosen up when hungry
+        if let Some(center) = neighborhood.center {
+            let mut cohesion = self.parameters.cohesion_weight
+                * gregariousness
+                * (1.0 + world.darkness * Self::NIGHT_COHESION);
+
+            // Agents that heard a call stick closer together
+            if self.rallied > 0 {
+                cohesion *= 1.0 + Self::RALLY_COHESION;
+            }
+
+            // Hungry agents care less about staying with the flock
+            if world.food.is_some() {
+                cohesion *= 1.0 - self.hunger;
+            }
+            self.rule_forces.cohesion = self.steer(world, center - self.position) * cohesion;
+            force += self.rule_forces.cohesion;
+        }
+
+        // Agent should drift towards the distant parts of the flock it can't see
+        if let Some(tree) = world.cohesion_tree {
+            let pull = tree.attraction(
+                self.position,
+                self.parameters.detection_radius,
+                world.long_range.theta,
+            );
+            force += self.steer(world, pull)
+                * self.parameters.cohesion_weight
+                * gregariousness
+                * Self::LONG_RANGE_COHESION;
+        }
+
+        // Agent should keep away from the species it avoids
+        force += self.steer(world, neighborhood.avoidance) * Self::AVOIDANCE_WEIGHT;
+
+        force
+    }
+
+    /// Steering force of Couzin's zone model. Agents only move away from the
+    /// neighbors in the zone of repulsion if there are any, and otherwise align
+    /// with those in the zone of orientation and approach those in the zone of attraction.
+    fn zones_force(&self, world: &World, heading: Vec2, candidates: &mut Vec<usize>) -> Vec2 {
+        let zones = &world.zones;
+        let min_alignment = (world.config.agent.field_of_view * 0.5).cos();
+
+        let mut repulsion = Vec2::default();
+        let mut orientation = Vec2::default();
+        let mut attraction = Vec2::default();
+        let agents = world.agents;
+        world.around(self, zones.attraction, candidates);
+        for &i in candidates.iter() {
+            let offset = agents.positions[i] - self.position;
+            let distance_squared = offset.length_squared();
+            if distance_squared == 0.0
+                || distance_squared >= zones.attraction * zones.attraction
+                || agents.is_perched(i)
+            {
+                continue;
+            }
+            let distance = distance_squared.sqrt();
+
+            // Agents always react to neighbors too close, even behind them
+            if distance < zones.repulsion {
+                repulsion -= offset / distance;
+            } else if self.sees(agents.positions[i], heading, distance, min_alignment) {
+                if distance < zones.orientation {
+                    orientation += agents.velocities[i].normalize_or_zero();
+                } else {
+                    attraction += offset / distance;
+                }
+            }
+        }
+
+        let desired = if repulsion != Vec2::ZERO {
+            repulsion
+        } else {
+            orientation.normalize_or_zero() + attraction.normalize_or_zero()
+        };
+        self.steer(world, desired)
+    }
+
+    /// Steering force computed by the agent's network from its neighborhood,
+    /// expressed relative to its heading.
+    fn neural_force(&self, world: &World, neighborhood: &Neighborhood, heading: Vec2) -> Vec2 {
+        let side = heading.perp();
+        let local = |v: Vec2| [v.dot(heading), v.dot(side)];
+
+        let radius = self.parameters.detection_radius.max(1.0);
+        let center = neighborhood
+            .center
+            .map_or(Vec2::ZERO, |center| (center - self.position) / radius);
+        let velocity = neighborhood
+            .velocity
+            .map_or(Vec2::ZERO, |velocity| velocity / self.parameters.speed);
+        let [center_x, center_y] = local(center);
+        let [velocity_x, velocity_y] = local(velocity);
+        let [separation_x, separation_y] = local(neighborhood.separation + neighborhood.avoidance);
+        let crowding = neighborhood.crowding();
+        let [forward, sideways] = self.parameters.network.forward([
+            center_x,
+            center_y,
+            velocity_x,
+            velocity_y,
+            separation_x,
+            separation_y,
+            crowding,
+        ]);
+        (heading * forward + side * sideways) * world.config.agent.max_force
+    }
+
+    /// Steering force towards a spot trailing behind the nearest leader.
+    fn follow_force(&self, world: &World) -> Vec2 {
+        let leader = world.leaders.iter().min_by(|a, b| {
+            let distance_a = a.position.distance_squared(self.position);
+            let distance_b = b.position.distance_squared(self.position);
+            distance_a.total_cmp(&distance_b)
+        });
+
+        match leader {
+            Some(leader) => {
+                let spot =
+                    leader.position - leader.velocity.normalize_or_zero() * Self::FOLLOW_DISTANCE;
+                self.steer(world, spot - self.position)
+            }
+            None => Vec2::ZERO,
+        }
+    }
+
+    /// Steering force towards the wake of the nearest neighbor ahead, staying
+    /// on the side of it the agent is already on so the flock forms a V or an echelon.
+    fn formation_force(&self, world: &World, heading: Vec2) -> Vec2 {
+        let agents = world.agents;
+        let wing_neighbor = (0..agents.len())
+            .filter(|&i| !agents.is_perched(i))
+            .map(|i| (i, agents.positions[i] - self.position))
+            .filter(|(_, offset)| {
+                let range = self.parameters.detection_radius;
+                offset.dot(heading) > 0.0 && offset.length_squared() < range * range
+            })
+            .min_by(|(_, a), (_, b)| a.length_squared().total_cmp(&b.length_squared()));
+
+        match wing_neighbor {
+            Some((other, offset)) => {
+                let other_heading = agents.velocities[other].normalize_or_zero();
+                let side = if other_heading.perp_dot(-offset) >= 0.0 {
+                    other_heading.perp()
+                } else {
+                    -other_heading.perp()
+                };
+                let spot = agents.positions[other] - other_heading * Self::DRAFT_OFFSET.0
+                    + side * Self::DRAFT_OFFSET.1;
+                self.steer(world, spot - self.position)
+            }
+            None => Vec2::ZERO,
+        }
+    }
+
+    /// Steering force turning the agent towards the desired direction at full speed.
+    fn steer(&self, world: &World, direction: Vec2) -> Vec2 {
+        if direction == Vec2::ZERO {
+            return Vec2::ZERO;
+        }
+        let desired = direction.normalize() * self.parameters.speed;
+        (desired - self.velocity).clamp_length_max(world.config.agent.max_force)
+    }
+
+    fn perch(&mut self, win_rect: &Rect, config: &Config, rng: &mut SmallRng) {
+        // Sit on the ground facing the direction the agent was flying in
+        self.position.y = win_rect.bottom() + self.species(config).size.1 * 0.5;
+        self.velocity = vec2(self.velocity.x.signum(), 0.0) * self.parameters.speed;
+        self.state = State::Perched(rng.gen_range(Self::PERCH_DURATION.0..Self::PERCH_DURATION.1));
+    }
+
+    pub fn species<'a>(&self, config: &'a Config) -> &'a Species {
+        &config.species[self.species]
+    }
+
+    /// Color of the species, flock, personality, heading, speed, acceleration or
+    /// number of neighbors, flushed by panic and faded by age.
+    pub fn color(&self, mode: ColorMode, config: &Config) -> Rgba {
+        let calm = match (mode, self.flock) {
+            (ColorMode::Flock, Some(flock)) => Vec4::from(Flocks::color(flock)),
+            (ColorMode::Personality, _) => self
+                .personality
+                .tint(Vec4::from(self.species(config).color)),
+            (ColorMode::Heading, _) => ColorMode::heading(self.velocity),
+            (ColorMode::Speed, _) => config.gradient.at(ColorMode::speed(
+                self.velocity.length(),
+                self.parameters.speed,
+            )),
+            (ColorMode::Density, _) => config.gradient.at(ColorMode::density(self.neighbors)),
+            (ColorMode::Acceleration, _) => {
+                let share =
+                    ColorMode::acceleration(self.acceleration.length(), config.agent.max_force);
+                config.gradient.at(share)
+            }
+            _ => Vec4::from(self.species(config).color),
+        };
+        let panic = Vec4::from(Self::PANIC_COLOR);
+        let color = calm.lerp(panic, self.panic_level());
+        rgba(color.x, color.y, color.z, color.w * self.fade())
+    }
+
+    /// Check if a point at the given distance lies inside the agent's vision cone.
+    fn sees(&self, point: Vec2, heading: Vec2, distance: f32, min_alignment: f32) -> bool {
+        (point - self.position).dot(heading) >= distance * min_alignment
+    }
+
+    /// Agent partway from its previous step to this one, to be drawn between steps.
+    /// Agents that jumped, wrapping around the window or just born, stay where they are.
+    pub fn interpolate(&self, previous: &Agent, alpha: f32) -> Agent {
+        let jumped = self.seed != previous.seed
+            || self.position.distance_squared(previous.position)
+                > Self::MAX_INTERPOLATED_JUMP * Self::MAX_INTERPOLATED_JUMP;
+        if jumped {
+            return *self;
+        }
+        Agent {
+            position: previous.position.lerp(self.position, alpha),
+            velocity: previous.velocity.lerp(self.velocity, alpha),
+            ..*self
+        }
+    }
+
+    pub fn display(&self, draw: &Draw, color: Rgba, sketch: &mut Sketch, config: &Config) {
+        self.display_leader(draw, color, config);
+
+        let (length, span) = self.size(config);
+        sketch.bird(
+            draw,
+            self.position,
+            self.velocity.angle(),
+            vec2(length, span),
+            self.flight.map_or(1.0, |flight| flight.spread()),
+            color,
+        );
+    }
+
+    /// Circle leaders so they can be told apart.
+    pub fn display_leader(&self, draw: &Draw, color: Rgba, config: &Config) {
+        if self.leader {
+            draw.ellipse()
+                .xy(self.position)
+                .radius(self.species(config).size.0)
+                .no_fill()
+                .stroke_weight(1.5)
+                .stroke(color);
+        }
+    }
+
+    /// Triangle of the agent for instanced rendering.
+    pub fn instance(&self, color: Rgba, config: &Config) -> Instance {
+        let (length, span) = self.size(config);
+        Instance {
+            position: self.position.to_array(),
+            rotation: self.velocity.angle(),
+            size: [length, span],
+            color: [color.red, color.green, color.blue, color.alpha],
+        }
+    }
+
+    /// Length and span of the body, as wings beat while flapping and spread while gliding.
+    fn size(&self, config: &Config) -> (f32, f32) {
+        let (length, span) = self.species(config).size;
+        (
+            length,
+            span * self.flight.map_or(1.0, |flight| flight.span()),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agents::Agents;
+    use crate::config::AgentConfig;
+    use crate::flow_field::FlowField;
+    use crate::index::Indexing;
+    use noise::Perlin;
+
+    /// Agent at the origin heading up, with neighbors around it at the edges of
+    /// its separation distance and detection radius and well inside and outside them.
+    fn layout() -> Agents {
+        let config = Config::get();
+        let mut rng = SmallRng::seed_from_u64(0);
+        let mut agent = Agent::spawn(Vec2::ZERO, 0, &config, &mut rng);
+        agent.velocity = vec2(0.0, agent.parameters.speed);
+        let range = agent.parameters.detection_radius;
+        let min_distance = config.agent.min_distance;
+        assert!(min_distance < range);
+
+        let offsets = [
+            vec2(min_distance * 0.5, 0.0),
+            vec2(-min_distance, 0.0),
+            vec2(0.0, min_distance),
+            vec2(3.0, 4.0) * (min_distance + range) * 0.1,
+            vec2(range, 0.0),
+            vec2(0.0, range),
+            vec2(-range * 0.999, 0.0),
+            vec2(range * 1.5, range),
+        ];
+        std::iter::once(agent)
+            .chain(offsets.iter().map(|&offset| {
+                let mut neighbor = Agent::spawn(offset, 0, &config, &mut rng);
+                neighbor.velocity = vec2(neighbor.parameters.speed, 0.0);
+                neighbor
+            }))
+            .collect()
+    }
+
+    /// Neighbors and separation of the first agent, comparing distances rather
+    /// than their squares as the neighbor checks used to.
+    fn reference(agents: &Agents, config: &Config) -> (Vec<usize>, Vec2) {
+        let agent = agents.get(0);
+        let min_distance = config.agent.min_distance;
+        let mut neighbors = Vec::new();
+        let mut separation = Vec2::ZERO;
+        for (i, other) in agents.iter().enumerate() {
+            let distance = agent.position.distance(other.position);
+            if distance > 0.0 && distance < agent.parameters.detection_radius {
+                neighbors.push(i);
+                if distance < min_distance {
+                    let weights = config.interactions.between(agent.species, other.species);
+                    separation += (agent.position - other.position) / distance
+                        * weights.separation
+                        * (1.0 - distance / min_distance);
+                }
+            }
+        }
+        (neighbors, separation)
+    }
+
+    #[test]
+    fn squared_distances_find_the_same_neighbors() {
+        let config = Config::get();
+        let agents = layout();
+        let (expected, separation) = reference(&agents, &config);
+        // Neighbors exactly at the detection radius are out of range
+        assert_eq!(expected, [1, 2, 3, 4, 7]);
+        assert_ne!(separation, Vec2::ZERO);
+
+        let mut rng = SmallRng::seed_from_u64(0);
+        let bounds = Rect::from_w_h(2000.0, 2000.0);
+        let flow_field = FlowField::new(&mut rng);
+        let noise = Perlin::new(0);
+        for indexing in [Indexing::BruteForce, Indexing::Grid] {
+            let index = indexing.build(&agents);
+            let world = World::plain(
+                &config,
+                bounds,
+                &agents,
+                index.as_ref(),
+                &flow_field,
+                &noise,
+            );
+            let agent = agents.get(0);
+            let heading = agent.velocity.normalize();
+            let mut scratch = Scratch::default();
+
+            let (mut candidates, mut neighbors) = (Vec::new(), Vec::new());
+            agent.neighbors(&world, heading, &mut candidates, &mut neighbors);
+            let mut found: Vec<usize> = neighbors.iter().map(|&(i, _)| i).collect();
+            found.sort_unstable();
+            assert_eq!(found, expected, "{indexing:?}");
+
+            // Neighbors exactly at the separation distance don't push the agent
+            let neighborhood = agent.perceive(&world, heading, &mut scratch);
+            assert_eq!(neighborhood.count, expected.len());
+            assert!(
+                neighborhood.separation.distance(separation) < 1e-5,
+                "{indexing:?}: {} != {separation}",
+                neighborhood.separation
+            );
+        }
+    }
+
+    #[test]
+    fn neighbors_are_seen_up_to_the_edges_of_the_vision_cone() {
+        let config = Config {
+            agent: AgentConfig {
+                field_of_view: 0.5 * PI,
+                ..Config::get().agent
+            },
+            ..(*Config::get()).clone()
+        };
+        let mut rng = SmallRng::seed_from_u64(0);
+        let mut agent = Agent::spawn(Vec2::ZERO, 0, &config, &mut rng);
+        agent.velocity = vec2(0.0, agent.parameters.speed);
+        let distance = agent.parameters.detection_radius * 0.5;
+
+        // Neighbors on either side of the heading, just inside and just outside
+        // half the field of view
+        let half = config.agent.field_of_view * 0.5;
+        let agents: Agents = std::iter::once(agent)
+            .chain(
+                [half - 0.01, half + 0.01, -(half - 0.01), -(half + 0.01)]
+                    .into_iter()
+                    .map(|angle| {
+                        let offset = vec2(-angle.sin(), angle.cos()) * distance;
+                        Agent::spawn(offset, 0, &config, &mut rng)
+                    }),
+            )
+            .collect();
+
+        let index = Indexing::BruteForce.build(&agents);
+        let flow_field = FlowField::new(&mut rng);
+        let noise = Perlin::new(0);
+        let bounds = Rect::from_w_h(2000.0, 2000.0);
+        let world = World::plain(
+            &config,
+            bounds,
+            &agents,
+            index.as_ref(),
+            &flow_field,
+            &noise,
+        );
+        let (mut candidates, mut neighbors) = (Vec::new(), Vec::new());
+        agent.neighbors(&world, Vec2::Y, &mut candidates, &mut neighbors);
+        let mut found: Vec<usize> = neighbors.iter().map(|&(i, _)| i).collect();
+        found.sort_unstable();
+        assert_eq!(found, [1, 3]);
+    }
+}
@@ -0,0 +1,148 @@
+use crate::camera::Camera;
+use crate::config::Config;
+use crate::lod::Sketch;
+use nannou::prelude::*;
+use nannou::rand::Rng;
+
+#[derive(Debug, Clone, Copy)]
+pub struct Agent3D {
+    pub position: Vec3,
+    pub velocity: Vec3,
+    pub species: usize,
+}
+
+impl Agent3D {
+    const DETECTION_RADIUS: f32 = 60.0;
+    const MIN_DISTANCE: f32 = 30.0;
+
+    const SEPARATION_FACTOR: f32 = 0.05;
+    const AVERAGE_VELOCITY_FACTOR: f32 = 0.01;
+    const AVERAGE_POSITION_FACTOR: f32 = 1e-3;
+
+    pub fn new(bounds: &Cuboid, species: usize, config: &Config, rng: &mut impl Rng) -> Self {
+        // Random position and velocity
+        let position = vec3(
+            rng.gen_range(bounds.left()..bounds.right()),
+            rng.gen_range(bounds.bottom()..bounds.top()),
+            rng.gen_range(bounds.front()..bounds.back()),
+        );
+        let velocity = vec3(
+            rng.gen_range(-1.0..1.0),
+            rng.gen_range(-1.0..1.0),
+            rng.gen_range(-1.0..1.0),
+        )
+        .normalize()
+            * config.species[species].speed;
+
+        // Return new agent
+        Agent3D {
+            position,
+            velocity,
+            species,
+        }
+    }
+
+    fn step(&mut self, bounds: &Cuboid) {
+        self.position += self.velocity;
+
+        // Wrap around each face of the volume
+        self.position.x = wrap_axis(self.position.x, bounds.left(), bounds.right());
+        self.position.y = wrap_axis(self.position.y, bounds.bottom(), bounds.top());
+        self.position.z = wrap_axis(self.position.z, bounds.front(), bounds.back());
+    }
+
+    pub fn update(&mut self, bounds: &Cuboid, agents: &[Agent3D], config: &Config) {
+        // Move agent
+        self.step(bounds);
+
+        // Calculate average position, velocity and separation of neighbors
+        let mut average_position = Vec3::ZERO;
+        let mut average_velocity = Vec3::ZERO;
+        let mut separation = Vec3::ZERO;
+        let mut cohesion_weight = 0.0;
+        let mut alignment_weight = 0.0;
+
+        // Iterate over all agents
+        let interactions = &config.interactions;
+        for other in agents {
+            let distance_squared = self.position.distance_squared(other.position);
+
+            // Check if other agent is in the detection range and not the agent itself
+            if distance_squared < Self::DETECTION_RADIUS * Self::DETECTION_RADIUS
+                && distance_squared > 0.0
+            {
+                let weights = interactions.between(self.species, other.species);
+
+                average_velocity += other.velocity * weights.alignment;
+                average_position += other.position * weights.cohesion;
+                alignment_weight += weights.alignment;
+                cohesion_weight += weights.cohesion;
+
+                // Move away from agents that are too close
+                // The closer the agent, the stronger the force
+                if distance_squared < Self::MIN_DISTANCE * Self::MIN_DISTANCE {
+                    let distance = distance_squared.sqrt();
+                    separation += (self.position - other.position) / distance
+                        * weights.separation
+                        * (1.0 - distance / Self::MIN_DISTANCE);
+                }
+            }
+        }
+
+        // Agent should move towards the same direction as its neighbors
+        if alignment_weight > 0.0 {
+            average_velocity /= alignment_weight;
+            self.velocity = self
+                .velocity
+                .lerp(average_velocity, Self::AVERAGE_VELOCITY_FACTOR);
+        }
+
+        // Agent should move towards the average position of its neighbors to stay with them
+        if cohesion_weight > 0.0 {
+            average_position /= cohesion_weight;
+            self.velocity += (average_position - self.position) * Self::AVERAGE_POSITION_FACTOR;
+        }
+
+        // Agent should keep a minimum distance to its neighbors
+        self.velocity += separation * Self::SEPARATION_FACTOR;
+
+        // Normalize velocity and set speed
+        self.velocity = self.velocity.normalize() * config.species[self.species].speed;
+    }
+
+    pub fn display(&self, draw: &Draw, camera: &Camera, sketch: &mut Sketch, config: &Config) {
+        let species = &config.species[self.species];
+
+        // Skip agents behind the camera
+        let (Some((position, scale)), Some((ahead, _))) = (
+            camera.project(self.position),
+            camera.project(self.position + self.velocity),
+        ) else {
+            return;
+        };
+
+        // Farther agents are smaller and dimmer
+        let (r, g, b, a) = species.color;
+        let brightness = scale.min(1.0);
+        let color = rgba(r * brightness, g * brightness, b * brightness, a);
+
+        sketch.tri(
+            draw,
+            position,
+            (ahead - position).angle(),
+            Vec2::from(species.size) * scale,
+            color,
+        );
+    }
+}
+
+/// Wrap a coordinate around the given range.
+fn wrap_axis(value: f32, min: f32, max: f32) -> f32 {
+    if value < min {
+        max
+    } else if value > max {
+        min
+    } else {
+        value
+    }
+}
@@ -0,0 +1,335 @@
+use crate::agent::{Agent, Perceived, RuleForces, State};
+use crate::flight::Flight;
+use crate::parameters::Parameters;
+use crate::personality::Personality;
+use nannou::prelude::*;
+use rayon::prelude::*;
+
+/// Agents of the flock stored field by field, one array per field, so loops over
+/// the flock like the neighbor loop only touch the fields they read. Agents are
+/// read and written whole as [`Agent`] values assembled from the arrays.
+#[derive(Debug, Default)]
+pub struct Agents {
+    pub positions: Vec<Vec2>,
+    pub velocities: Vec<Vec2>,
+    pub species: Vec<usize>,
+    pub states: Vec<State>,
+    /// Frames of panic left of every agent
+    pub panics: Vec<u32>,
+    /// Seeds of the agents, which identify them
+    pub seeds: Vec<u64>,
+    /// Fields of the agents only read along with the whole agent
+    rest: Vec<Rest>,
+}
+
+/// Fields of an agent besides those with an array of their own.
+#[derive(Debug, Clone, Copy)]
+struct Rest {
+    acceleration: Vec2,
+    parameters: Parameters,
+    wander_seed: f64,
+    energy: f32,
+    leader: bool,
+    alarm: Option<u32>,
+    age: u32,
+    lifespan: u32,
+    hunger: f32,
+    rallied: u32,
+    personality: Personality,
+    flight: Option<Flight>,
+    flock: Option<u32>,
+    neighbors: usize,
+    perceived: Perceived,
+    rule_forces: RuleForces,
+}
+
+impl Rest {
+    fn of(agent: &Agent) -> Self {
+        Rest {
+            acceleration: agent.acceleration,
+            parameters: agent.parameters,
+            wander_seed: agent.wander_seed,
+            energy: agent.energy,
+            leader: agent.leader,
+            alarm: agent.alarm,
+            age: agent.age,
+            lifespan: agent.lifespan,
+            hunger: agent.hunger,
+            rallied: agent.rallied,
+            personality: agent.personality,
+            flight: agent.flight,
+            flock: agent.flock,
+            neighbors: agent.neighbors,
+            perceived: agent.perceived,
+            rule_forces: agent.rule_forces,
+        }
+    }
+
+    /// Agent with these fields and the ones stored in arrays.
+    fn with(
+        self,
+        position: Vec2,
+        velocity: Vec2,
+        species: usize,
+        state: State,
+        panic: u32,
+        seed: u64,
+    ) -> Agent {
+        Agent {
+            position,
+            velocity,
+            acceleration: self.acceleration,
+            species,
+            state,
+            parameters: self.parameters,
+            wander_seed: self.wander_seed,
+            seed,
+            energy: self.energy,
+            leader: self.leader,
+            panic,
+            alarm: self.alarm,
+            age: self.age,
+            lifespan: self.lifespan,
+            hunger: self.hunger,
+            rallied: self.rallied,
+            personality: self.personality,
+            flight: self.flight,
+            flock: self.flock,
+            neighbors: self.neighbors,
+            perceived: self.perceived,
+            rule_forces: self.rule_forces,
+        }
+    }
+}
+
+impl Agents {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.positions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.positions.is_empty()
+    }
+
+    pub fn get(&self, i: usize) -> Agent {
+        self.rest[i].with(
+            self.positions[i],
+            self.velocities[i],
+            self.species[i],
+            self.states[i],
+            self.panics[i],
+            self.seeds[i],
+        )
+    }
+
+    pub fn set(&mut self, i: usize, agent: Agent) {
+        self.positions[i] = agent.position;
+        self.velocities[i] = agent.velocity;
+        self.species[i] = agent.species;
+        self.states[i] = agent.state;
+        self.panics[i] = agent.panic;
+        self.seeds[i] = agent.seed;
+        self.rest[i] = Rest::of(&agent);
+    }
+
+    pub fn push(&mut self, agent: Agent) {
+        self.positions.push(agent.position);
+        self.velocities.push(agent.velocity);
+        self.species.push(agent.species);
+        self.states.push(agent.state);
+        self.panics.push(agent.panic);
+        self.seeds.push(agent.seed);
+        self.rest.push(Rest::of(&agent));
+    }
+
+    pub fn swap_remove(&mut self, i: usize) -> Agent {
+        let agent = self.get(i);
+        self.positions.swap_remove(i);
+        self.velocities.swap_remove(i);
+        self.species.swap_remove(i);
+        self.states.swap_remove(i);
+        self.panics.swap_remove(i);
+        self.seeds.swap_remove(i);
+        self.rest.swap_remove(i);
+        agent
+    }
+
+    pub fn truncate(&mut self, len: usize) {
+        self.positions.truncate(len);
+        self.velocities.truncate(len);
+        self.species.truncate(len);
+        self.states.truncate(len);
+        self.panics.truncate(len);
+        self.seeds.truncate(len);
+        self.rest.truncate(len);
+    }
+
+    pub fn clear(&mut self) {
+        self.truncate(0);
+    }
+
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = Agent> + ExactSizeIterator + '_ {
+        (0..self.len()).map(|i| self.get(i))
+    }
+
+    pub fn par_iter(&self) -> impl IndexedParallelIterator<Item = Agent> + '_ {
+        (0..self.len()).into_par_iter().map(|i| self.get(i))
+    }
+
+    /// Change an agent.
+    pub fn change(&mut self, i: usize, change: impl FnOnce(&mut Agent)) {
+        let mut agent = self.get(i);
+        change(&mut agent);
+        self.set(i, agent);
+    }
+
+    /// Change every agent in turn.
+    pub fn for_each_mut(&mut self, mut change: impl FnMut(&mut Agent)) {
+        for i in 0..self.len() {
+            self.change(i, &mut change);
+        }
+    }
+
+    /// Change every agent in parallel, each thread working with a value made by
+    /// `init` for the agents it changes.
+    pub fn par_for_each_mut<T>(
+        &mut self,
+        init: impl Fn() -> T + Sync + Send,
+        change: impl Fn(&mut T, &mut Agent) + Sync + Send,
+    ) {
+        (
+            self.positions.par_iter_mut(),
+            self.velocities.par_iter_mut(),
+            self.species.par_iter_mut(),
+            self.states.par_iter_mut(),
+            self.panics.par_iter_mut(),
+            self.seeds.par_iter_mut(),
+            self.rest.par_iter_mut(),
+        )
+            .into_par_iter()
+            .for_each_init(
+                init,
+                |value, (position, velocity, species, state, panic, seed, rest)| {
+                    let mut agent =
+                        rest.with(*position, *velocity, *species, *state, *panic, *seed);
+                    change(value, &mut agent);
+                    (*position, *velocity, *species, *state, *panic, *seed) = (
+                        agent.position,
+                        agent.velocity,
+                        agent.species,
+                        agent.state,
+                        agent.panic,
+                        agent.seed,
+                    );
+                    *rest = Rest::of(&agent);
+                },
+            );
+    }
+
+    /// Change every agent in turn, keeping those for which `keep` returns true in order.
+    pub fn retain_mut(&mut self, mut keep: impl FnMut(&mut Agent) -> bool) {
+        let mut kept = 0;
+        for i in 0..self.len() {
+            let mut agent = self.get(i);
+            if keep(&mut agent) {
+                self.set(kept, agent);
+                kept += 1;
+            }
+        }
+        self.truncate(kept);
+    }
+
+    pub fn is_perched(&self, i: usize) -> bool {
+        matches!(self.states[i], State::Perched(_))
+    }
+
+    /// Whether an agent alarms the agents seeing it.
+    pub fn spreads_panic(&self, i: usize) -> bool {
+        Agent::spreads(self.panics[i])
+    }
+
+    pub fn parameters(&self, i: usize) -> &Parameters {
+        &self.rest[i].parameters
+    }
+}
+
+impl Clone for Agents {
+    fn clone(&self) -> Self {
+        let mut agents = Agents::new();
+        agents.clone_from(self);
+        agents
+    }
+
+    /// Copy the agents column by column, reusing the memory of every array.
+    fn clone_from(&mut self, source: &Self) {
+        self.positions.clone_from(&source.positions);
+        self.velocities.clone_from(&source.velocities);
+        self.species.clone_from(&source.species);
+        self.states.clone_from(&source.states);
+        self.panics.clone_from(&source.panics);
+        self.seeds.clone_from(&source.seeds);
+        self.rest.clone_from(&source.rest);
+    }
+}
+
+impl Extend<Agent> for Agents {
+    fn extend<I: IntoIterator<Item = Agent>>(&mut self, agents: I) {
+        agents.into_iter().for_each(|agent| self.push(agent));
+    }
+}
+
+impl FromIterator<Agent> for Agents {
+    fn from_iter<I: IntoIterator<Item = Agent>>(agents: I) -> Self {
+        let mut all = Agents::new();
+        all.extend(agents);
+        all
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use nannou::rand::rngs::SmallRng;
+    use nannou::rand::SeedableRng;
+
+    fn flock(count: usize) -> Vec<Agent> {
+        let config = Config::get();
+        let mut rng = SmallRng::seed_from_u64(0);
+        let bounds = Rect::from_w_h(500.0, 500.0);
+        (0..count)
+            .map(|i| Agent::new(bounds, i % config.species.len(), &config, &mut rng))
+            .collect()
+    }
+
+    #[test]
+    fn agents_are_read_back_as_they_were_stored() {
+        let flock = flock(50);
+        let mut agents: Agents = flock.iter().copied().collect();
+        for (agent, expected) in agents.iter().zip(&flock) {
+            assert_eq!(agent.seed, expected.seed);
+            assert_eq!(agent.position, expected.position);
+            assert_eq!(agent.lifespan, expected.lifespan);
+        }
+
+        // Changes made in parallel land on the agent they were made to
+        agents.par_for_each_mut(|| (), |_, agent| agent.age = agent.seed as u32);
+        for agent in agents.iter() {
+            assert_eq!(agent.age, agent.seed as u32);
+        }
+
+        // Agents left keep their order
+        agents.retain_mut(|agent| agent.seed % 2 == 0);
+        let kept: Vec<u64> = flock
+            .iter()
+            .map(|agent| agent.seed)
+            .filter(|seed| seed % 2 == 0)
+            .collect();
+        assert_eq!(agents.seeds, kept);
+        assert_eq!(agents.len(), kept.len());
+    }
+}
@@ -0,0 +1,137 @@
+use crate::brain::Brain;
+use crate::integrator::Integrator;
+use crate::profile::Profile;
+use crate::sweep::Span;
+use clap::{Parser, ValueEnum};
+use std::sync::OnceLock;
+
+/// Where the flock is simulated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Backend {
+    /// On the CPU with every feature
+    Cpu,
+    /// Only the flocking rules, on the GPU with compute shaders
+    Gpu,
+    /// The plane with its predators, on a thread of its own decoupled from rendering
+    Thread,
+}
+
+/// Floating point precision of the precise flock.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum FloatType {
+    F32,
+    F64,
+}
+
+/// Options of the sketch, parsed from the command line before the app starts.
+#[derive(Debug, Parser)]
+#[command(name = "birds", about = "Simulate the flocking behaviour of birds")]
+pub struct Args {
+    /// TOML file of the species and agent constants
+    #[arg(long, value_name = "PATH", default_value = "birds.toml")]
+    pub config: String,
+    /// Number of agents the flock starts with, split among the species in their proportions
+    #[arg(long, value_name = "COUNT")]
+    pub agents: Option<usize>,
+    /// Factor every species' speed is multiplied by
+    #[arg(long, value_name = "FACTOR")]
+    pub speed: Option<f32>,
+    /// Seed of every random draw, so a run can be reproduced exactly
+    #[arg(long)]
+    pub seed: Option<u64>,
+    /// Open the window fullscreen, as by default
+    #[arg(long, conflicts_with = "windowed")]
+    pub fullscreen: bool,
+    /// Open a window instead of going fullscreen
+    #[arg(long)]
+    pub windowed: bool,
+    /// Save every frame as a numbered png in the frames directory
+    #[arg(long)]
+    pub record: bool,
+    /// How agents turn what they perceive of their neighbors into steering
+    #[arg(long, value_enum, default_value = "rules")]
+    pub brain: Brain,
+    /// Seconds a day and night cycle lasts
+    #[arg(long, value_name = "SECONDS")]
+    pub day_length: Option<f32>,
+    /// Grayscale image of the terrain below the flock
+    #[arg(long, value_name = "PATH")]
+    pub heightmap: Option<String>,
+    /// Food items growing every second while foraging
+    #[arg(long, value_name = "ITEMS")]
+    pub food_rate: Option<f32>,
+    /// Closed polyline to flow along, one `x y` point per line
+    #[arg(long, value_name = "FILE")]
+    pub path: Option<String>,
+    /// Width in pixels of the margin along the edges agents steer back from
+    #[arg(long, value_name = "PIXELS")]
+    pub margin: Option<f32>,
+    /// Host and port to send flock split and merge events to as OSC messages
+    #[arg(long, value_name = "HOST:PORT")]
+    pub osc: Option<String>,
+    /// Print flock split and merge events
+    #[arg(long)]
+    pub log_events: bool,
+    /// Agents per million square pixels kept as the window changes size
+    #[arg(long, value_name = "AGENTS")]
+    pub density: Option<f32>,
+    /// Steps cached neighbor lists are reused for before being gathered again
+    #[arg(long, value_name = "STEPS")]
+    pub neighbor_refresh: Option<u32>,
+    /// Frame rate to hold by adding and removing agents
+    #[arg(long, value_name = "FPS")]
+    pub target_fps: Option<f32>,
+    /// Shapes to flock around, filled ones as solid regions and others as walls
+    #[arg(long, value_name = "FILE")]
+    pub svg: Option<String>,
+    /// Image drawn for every agent instead of a triangle, tinted by its color
+    #[arg(long, value_name = "PNG")]
+    pub sprite: Option<String>,
+    /// Number of agents, trail quality, antialiasing and neighbor search suiting
+    /// the hardware, unless set otherwise
+    #[arg(long, value_enum)]
+    pub profile: Option<Profile>,
+    /// Where the flock is simulated
+    #[arg(long, value_enum, default_value = "cpu")]
+    pub backend: Backend,
+    /// Number of agents simulated on the GPU
+    #[arg(long, value_name = "COUNT")]
+    pub gpu_agents: Option<u32>,
+    /// How physical movement advances agents over a step
+    #[arg(long, value_enum, default_value = "euler")]
+    pub integrator: Integrator,
+    /// Simulate only the flocking rules, deterministically in this precision
+    #[arg(long, value_enum)]
+    pub precision: Option<FloatType>,
+    /// Sweep the flocking rules over heading noise and cohesion without a window,
+    /// writing the statistics of every run to this CSV file
+    #[arg(long, value_name = "FILE")]
+    pub sweep: Option<String>,
+    /// Heading noise values swept, in radians
+    #[arg(long, value_name = "FROM:TO:COUNT", default_value = "0:6.28:9")]
+    pub sweep_noise: Span,
+    /// Cohesion weights swept
+    #[arg(long, value_name = "FROM:TO:COUNT", default_value = "0:3:7")]
+    pub sweep_cohesion: Span,
+    /// Steps every run of the sweep lasts
+    #[arg(long, value_name = "STEPS", default_value_t = 2000)]
+    pub sweep_steps: u32,
+    /// Runs of every combination of swept values, each from the next seed
+    #[arg(long, value_name = "COUNT", default_value_t = 1)]
+    pub sweep_runs: u32,
+}
+
+static ARGS: OnceLock<Args> = OnceLock::new();
+
+impl Args {
+    /// Parse the command line, exiting with a usage message when it is invalid.
+    pub fn init() {
+        ARGS.get_or_init(Args::parse);
+    }
+
+    /// Options of the sketch, the defaults if the command line wasn't parsed, as
+    /// in the benchmarks and tests.
+    pub fn get() -> &'static Args {
+        ARGS.get_or_init(|| Args::parse_from(["birds"]))
+    }
+}
@@ -0,0 +1,128 @@
+use nannou::prelude::*;
+
+/// Route followed by the moving attractor.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Route {
+    #[default]
+    Off,
+    Lissajous,
+    Circle,
+    /// Loop of the path recorded from the cursor
+    Recorded,
+}
+
+/// Attractor moving along a route, leading the flock in a repeatable choreography.
+#[derive(Debug, Default)]
+pub struct MovingAttractor {
+    pub route: Route,
+    /// Cursor positions and the time they were recorded at, since the start of the recording
+    recording: Vec<(f32, Vec2)>,
+    /// Time the recording in progress started at
+    recording_start: Option<f32>,
+}
+
+impl MovingAttractor {
+    pub const COLOR: (f32, f32, f32, f32) = (1.0, 0.9, 0.3, 0.9);
+    pub const RECORDING_COLOR: (f32, f32, f32, f32) = (1.0, 0.2, 0.2, 0.9);
+    const SIZE: f32 = 8.0;
+
+    /// Seconds for the circle and for the slower axis of the Lissajous curve to loop
+    const PERIOD: f32 = 20.0;
+
+    /// Frequencies of the Lissajous curve along both axes
+    const LISSAJOUS: (f32, f32) = (3.0, 2.0);
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn next(&mut self) {
+        self.route = match self.route {
+            Route::Off => Route::Lissajous,
+            Route::Lissajous => Route::Circle,
+            Route::Circle => Route::Recorded,
+            Route::Recorded => Route::Off,
+        };
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.recording_start.is_some()
+    }
+
+    /// Start recording a new path from the cursor, or stop the recording in progress.
+    pub fn toggle_recording(&mut self, time: f32) {
+        if self.is_recording() {
+            self.recording_start = None;
+        } else {
+            self.recording.clear();
+            self.recording_start = Some(time);
+        }
+    }
+
+    /// Record the position of the cursor while recording.
+    pub fn record(&mut self, time: f32, mouse: Vec2) {
+        if let Some(start) = self.recording_start {
+            self.recording.push((time - start, mouse));
+        }
+    }
+
+    /// Position of the attractor at a given time, if it is moving along a route.
+    pub fn position(&self, win_rect: Rect, time: f32) -> Option<Vec2> {
+        let phase = time / Self::PERIOD * TAU;
+        let half = win_rect.wh() * 0.4;
+        match self.route {
+            Route::Off => None,
+            Route::Lissajous => Some(
+                win_rect.xy()
+                    + vec2(
+                        (phase * Self::LISSAJOUS.0).sin(),
+                        (phase * Self::LISSAJOUS.1).sin(),
+                    ) * half,
+            ),
+            Route::Circle => {
+                Some(win_rect.xy() + vec2(phase.cos(), phase.sin()) * half.min_element())
+            }
+            Route::Recorded => self.replay(time),
+        }
+    }
+
+    /// Position along the recorded path, looping over it.
+    fn replay(&self, time: f32) -> Option<Vec2> {
+        if self.is_recording() {
+            return None;
+        }
+        let &(duration, last) = self.recording.last()?;
+        if duration <= 0.0 {
+            return Some(last);
+        }
+
+        // Interpolate between the samples surrounding the time in the loop
+        let t = time % duration;
+        let next = self
+            .recording
+            .partition_point(|(sample, _)| *sample <= t)
+            .min(self.recording.len() - 1);
+        let (t1, p1) = self.recording[next];
+        let (t0, p0) = self.recording[next.saturating_sub(1)];
+        let fraction = if t1 > t0 { (t - t0) / (t1 - t0) } else { 1.0 };
+        Some(p0.lerp(p1, fraction.clamp(0.0, 1.0)))
+    }
+
+    pub fn display(&self, draw: &Draw, win_rect: Rect, time: f32) {
+        // Draw the path being recorded
+        if self.is_recording() {
+            let points = self.recording.iter().map(|(_, point)| *point);
+            draw.polyline()
+                .weight(1.0)
+                .points(points)
+                .color(Rgba::from(Self::RECORDING_COLOR));
+        }
+
+        if let Some(position) = self.position(win_rect, time) {
+            draw.ellipse()
+                .xy(position)
+                .radius(Self::SIZE)
+                .color(Rgba::from(Self::COLOR));
+        }
+    }
+}
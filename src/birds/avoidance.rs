@@ -0,0 +1,17 @@
+/// How agents detect and steer around obstacles and walls.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Avoidance {
+    /// Pushed away from everything inside the detection radius
+    Radial,
+    /// Probe ahead with rays and turn before running into anything
+    Whiskers,
+}
+
+impl Avoidance {
+    pub fn next(self) -> Self {
+        match self {
+            Avoidance::Radial => Avoidance::Whiskers,
+            Avoidance::Whiskers => Avoidance::Radial,
+        }
+    }
+}
@@ -0,0 +1,167 @@
+use crate::color_mode::Gradient;
+use nannou::geom::Tri;
+use nannou::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// What is drawn behind the agents instead of flat black, set in the
+/// `[background]` table of the configuration as in
+///
+/// ```toml
+/// [background]
+/// kind = "radial"
+/// colors = [[0.1, 0.1, 0.3, 1.0], [0.0, 0.0, 0.0, 1.0]]
+/// ```
+///
+/// or `kind = "image"` with the `path` of the image and a `fit` of `letterbox`
+/// or `tile`.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum Background {
+    #[default]
+    Flat,
+    /// Colors from the top of the window to its bottom
+    Vertical { colors: Gradient },
+    /// Colors from the center of the window to its corners
+    Radial { colors: Gradient },
+    Image {
+        path: String,
+        #[serde(default)]
+        fit: Fit,
+    },
+}
+
+/// How an image covers the window.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Fit {
+    /// Scaled to fit within the window, keeping its proportions, with bars of
+    /// the flat color along the sides left over
+    #[default]
+    Letterbox,
+    /// Repeated across the window at its own size
+    Tile,
+}
+
+/// Background drawn every frame, keeping the image of the configuration loaded
+/// until the configuration names another one.
+#[derive(Debug, Default)]
+pub struct Backdrop {
+    /// Path of the image last named, and the image unless it couldn't be loaded
+    image: Option<(String, Option<wgpu::Texture>)>,
+}
+
+impl Backdrop {
+    // Bands of the vertical gradient, and rings and slices of the radial one
+    const BANDS: usize = 32;
+    const RINGS: usize = 16;
+    const SLICES: usize = 64;
+
+    pub fn new() -> Self {
+        Backdrop { image: None }
+    }
+
+    /// Draw the background over the window, as faint as an opacity. Images are
+    /// only drawn opaque, as they can't be faded.
+    pub fn display(&mut self, draw: &Draw, window: &Window, background: &Background, opacity: f32) {
+        let win_rect = window.rect();
+        match background {
+            Background::Flat => {}
+            Background::Vertical { colors } => {
+                let color = |share: f32| {
+                    let color = colors.at(share);
+                    rgba(color.x, color.y, color.z, color.w * opacity)
+                };
+                let tris = (0..Self::BANDS).flat_map(|band| {
+                    let (top, bottom) = (
+                        band as f32 / Self::BANDS as f32,
+                        (band + 1) as f32 / Self::BANDS as f32,
+                    );
+                    let y = |share: f32| win_rect.top() - share * win_rect.h();
+                    let corners = [
+                        (vec3(win_rect.left(), y(top), 0.0), color(top)),
+                        (vec3(win_rect.right(), y(top), 0.0), color(top)),
+                        (vec3(win_rect.right(), y(bottom), 0.0), color(bottom)),
+                        (vec3(win_rect.left(), y(bottom), 0.0), color(bottom)),
+                    ];
+                    [
+                        Tri([corners[0], corners[1], corners[2]]),
+                        Tri([corners[0], corners[2], corners[3]]),
+                    ]
+                });
+                draw.mesh().tris_colored(tris);
+            }
+            Background::Radial { colors } => {
+                let color = |share: f32| {
+                    let color = colors.at(share);
+                    rgba(color.x, color.y, color.z, color.w * opacity)
+                };
+                let radius = win_rect.wh().length() * 0.5;
+                let point = |ring: usize, slice: usize| {
+                    let angle = slice as f32 / Self::SLICES as f32 * TAU;
+                    let share = ring as f32 / Self::RINGS as f32;
+                    let position = win_rect.xy() + vec2(angle.cos(), angle.sin()) * share * radius;
+                    (position.extend(0.0), color(share))
+                };
+                let tris = (0..Self::RINGS).flat_map(|ring| {
+                    (0..Self::SLICES).flat_map(move |slice| {
+                        let corners = [
+                            point(ring, slice),
+                            point(ring, slice + 1),
+                            point(ring + 1, slice + 1),
+                            point(ring + 1, slice),
+                        ];
+                        [
+                            Tri([corners[0], corners[1], corners[2]]),
+                            Tri([corners[0], corners[2], corners[3]]),
+                        ]
+                    })
+                });
+                draw.mesh().tris_colored(tris);
+            }
+            Background::Image { path, fit } => {
+                if opacity < 1.0 {
+                    return;
+                }
+                let Some(texture) = self.load(window, path) else {
+                    return;
+                };
+                let [width, height] = texture.size();
+                let size = vec2(width as f32, height as f32);
+                match fit {
+                    Fit::Letterbox => {
+                        let scale = (win_rect.w() / size.x).min(win_rect.h() / size.y);
+                        draw.texture(texture).xy(win_rect.xy()).wh(size * scale);
+                    }
+                    Fit::Tile => {
+                        let (columns, rows) = (
+                            (win_rect.w() / size.x).ceil() as usize,
+                            (win_rect.h() / size.y).ceil() as usize,
+                        );
+                        for (column, row) in
+                            (0..columns).flat_map(|c| (0..rows).map(move |r| (c, r)))
+                        {
+                            let center = vec2(
+                                win_rect.left() + (column as f32 + 0.5) * size.x,
+                                win_rect.top() - (row as f32 + 0.5) * size.y,
+                            );
+                            draw.texture(texture).xy(center).wh(size);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Image at a path, loaded the first time it is named.
+    fn load(&mut self, window: &Window, path: &str) -> Option<&wgpu::Texture> {
+        if self.image.as_ref().is_none_or(|(loaded, _)| loaded != path) {
+            let texture = wgpu::Texture::from_path(window, path)
+                .map_err(|error| eprintln!("Could not load background {path}: {error}"))
+                .ok();
+            self.image = Some((path.to_string(), texture));
+        }
+        self.image
+            .as_ref()
+            .and_then(|(_, texture)| texture.as_ref())
+    }
+}
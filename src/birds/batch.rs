@@ -0,0 +1,123 @@
+use nannou::prelude::*;
+
+/// Neighbors laid out as a structure of arrays, one array per coordinate, so the
+/// sums over all of them run in fixed-size chunks the compiler vectorizes.
+#[derive(Debug, Default)]
+pub struct Batch {
+    xs: Vec<f32>,
+    ys: Vec<f32>,
+    velocity_xs: Vec<f32>,
+    velocity_ys: Vec<f32>,
+    distances: Vec<f32>,
+}
+
+impl Batch {
+    /// Neighbors summed side by side, as independent lanes filling vector registers.
+    const LANES: usize = 8;
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        Batch {
+            xs: Vec::with_capacity(capacity),
+            ys: Vec::with_capacity(capacity),
+            velocity_xs: Vec::with_capacity(capacity),
+            velocity_ys: Vec::with_capacity(capacity),
+            distances: Vec::with_capacity(capacity),
+        }
+    }
+
+    pub fn push(&mut self, position: Vec2, velocity: Vec2, distance: f32) {
+        self.xs.push(position.x);
+        self.ys.push(position.y);
+        self.velocity_xs.push(velocity.x);
+        self.velocity_ys.push(velocity.y);
+        self.distances.push(distance);
+    }
+
+    /// Forget the neighbors, keeping the arrays for the next ones.
+    pub fn clear(&mut self) {
+        self.xs.clear();
+        self.ys.clear();
+        self.velocity_xs.clear();
+        self.velocity_ys.clear();
+        self.distances.clear();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.xs.is_empty()
+    }
+
+    /// Sum of the positions of the neighbors, each scaled by its weight.
+    pub fn positions(&self, weights: &[f32]) -> Vec2 {
+        vec2(dot(&self.xs, weights), dot(&self.ys, weights))
+    }
+
+    /// Sum of the velocities of the neighbors, each scaled by its weight.
+    pub fn velocities(&self, weights: &[f32]) -> Vec2 {
+        vec2(
+            dot(&self.velocity_xs, weights),
+            dot(&self.velocity_ys, weights),
+        )
+    }
+
+    /// Sum of the directions from the neighbors to a position, each scaled by its weight.
+    pub fn away(&self, position: Vec2, weights: &[f32]) -> Vec2 {
+        // (position - neighbor) / distance * weight splits into sums over the
+        // neighbors of the per-neighbor factor weight / distance
+        let x = scaled_offset(position.x, &self.xs, &self.distances, weights);
+        let y = scaled_offset(position.y, &self.ys, &self.distances, weights);
+        vec2(x, y)
+    }
+}
+
+/// Full chunks of lanes of an array, and the values left over.
+fn lanes(values: &[f32]) -> (&[[f32; Batch::LANES]], &[f32]) {
+    values.as_chunks::<{ Batch::LANES }>()
+}
+
+/// Sum of an array, accumulated lane by lane.
+pub fn sum(values: &[f32]) -> f32 {
+    let (chunks, rest) = lanes(values);
+    let mut sums = [0.0; Batch::LANES];
+    for chunk in chunks {
+        for lane in 0..Batch::LANES {
+            sums[lane] += chunk[lane];
+        }
+    }
+    sums.iter().sum::<f32>() + rest.iter().sum::<f32>()
+}
+
+/// Sum of the products of two arrays, accumulated lane by lane.
+fn dot(values: &[f32], weights: &[f32]) -> f32 {
+    let (chunks, rest) = lanes(values);
+    let (weight_chunks, weight_rest) = lanes(weights);
+    let mut sums = [0.0; Batch::LANES];
+    for (chunk, weight) in chunks.iter().zip(weight_chunks) {
+        for lane in 0..Batch::LANES {
+            sums[lane] += chunk[lane] * weight[lane];
+        }
+    }
+    let rest: f32 = rest.iter().zip(weight_rest).map(|(v, w)| v * w).sum();
+    sums.iter().sum::<f32>() + rest
+}
+
+/// Sum of the offsets from values to an origin divided by their distance and
+/// scaled by their weight, accumulated lane by lane.
+fn scaled_offset(origin: f32, values: &[f32], distances: &[f32], weights: &[f32]) -> f32 {
+    let (chunks, rest) = lanes(values);
+    let (distance_chunks, distance_rest) = lanes(distances);
+    let (weight_chunks, weight_rest) = lanes(weights);
+    let mut sums = [0.0; Batch::LANES];
+    let all = chunks.iter().zip(distance_chunks).zip(weight_chunks);
+    for ((chunk, distance), weight) in all {
+        for lane in 0..Batch::LANES {
+            sums[lane] += (origin - chunk[lane]) / distance[lane] * weight[lane];
+        }
+    }
+    let rest: f32 = rest
+        .iter()
+        .zip(distance_rest)
+        .zip(weight_rest)
+        .map(|((value, distance), weight)| (origin - value) / distance * weight)
+        .sum();
+    sums.iter().sum::<f32>() + rest
+}
@@ -0,0 +1,242 @@
+use nannou::draw::{Renderer, RendererBuilder};
+use nannou::prelude::*;
+use nannou::wgpu::util::DeviceExt;
+
+/// Post-processing stage making bright agents glow.
+///
+/// The scene is drawn into a texture of its own instead of the frame. Its parts
+/// brighter than the threshold are blurred across and then along a texture of
+/// half its size, and the glow is added back over the scene as it is written to
+/// the frame.
+pub struct Bloom {
+    pub enabled: bool,
+    /// Luminance below which nothing glows
+    pub threshold: f32,
+    /// Factor of the brightness of the glow
+    pub intensity: f32,
+    renderer: Renderer,
+    /// Multisampled target the scene is drawn into, when the window is multisampled
+    multisampled: Option<wgpu::Texture>,
+    scene: wgpu::Texture,
+    across: Pass,
+    along: Pass,
+    composite: Pass,
+    blur_pipeline: wgpu::RenderPipeline,
+    composite_pipeline: wgpu::RenderPipeline,
+}
+
+/// Uniforms and textures of one of the passes.
+struct Pass {
+    uniforms: wgpu::Buffer,
+    bind_group: wgpu::BindGroup,
+    /// Texture written by the pass, the frame when compositing
+    target: Option<wgpu::Texture>,
+}
+
+impl Bloom {
+    pub const THRESHOLD: f32 = 0.5;
+    pub const INTENSITY: f32 = 1.5;
+    /// Format of the scene and the glow, keeping colors brighter than white
+    const FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba16Float;
+    /// Texels of the half-sized glow between the taps of the blur
+    const SPREAD: f32 = 1.5;
+
+    pub fn new(window: &Window) -> Self {
+        let device = window.device();
+        let size = window.inner_size_pixels();
+        let size = [size.0.max(1), size.1.max(1)];
+        let half = [(size[0] / 2).max(1), (size[1] / 2).max(1)];
+        let msaa_samples = window.msaa_samples();
+        let texture = |size: [u32; 2]| {
+            wgpu::TextureBuilder::new()
+                .size(size)
+                .format(Self::FORMAT)
+                .usage(
+                    wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+                )
+                .build(device)
+        };
+        let multisampled = (msaa_samples > 1).then(|| {
+            wgpu::TextureBuilder::new()
+                .size(size)
+                .format(Self::FORMAT)
+                .sample_count(msaa_samples)
+                .usage(wgpu::TextureUsages::RENDER_ATTACHMENT)
+                .build(device)
+        });
+        let (scene, across, along) = (texture(size), texture(half), texture(half));
+        let renderer = RendererBuilder::new().build(
+            device,
+            size,
+            window.scale_factor(),
+            msaa_samples,
+            Self::FORMAT,
+        );
+
+        let sampler_descriptor = wgpu::SamplerBuilder::new().into_descriptor();
+        let sampler = device.create_sampler(&sampler_descriptor);
+        let filtering = wgpu::sampler_filtering(&sampler_descriptor);
+        let sample_type = scene.sample_type();
+        let blur_layout = wgpu::BindGroupLayoutBuilder::new()
+            .uniform_buffer(wgpu::ShaderStages::FRAGMENT, false)
+            .texture(
+                wgpu::ShaderStages::FRAGMENT,
+                false,
+                wgpu::TextureViewDimension::D2,
+                sample_type,
+            )
+            .sampler(wgpu::ShaderStages::FRAGMENT, filtering)
+            .build(device);
+        let composite_layout = wgpu::BindGroupLayoutBuilder::new()
+            .uniform_buffer(wgpu::ShaderStages::FRAGMENT, false)
+            .texture(
+                wgpu::ShaderStages::FRAGMENT,
+                false,
+                wgpu::TextureViewDimension::D2,
+                sample_type,
+            )
+            .sampler(wgpu::ShaderStages::FRAGMENT, filtering)
+            .texture(
+                wgpu::ShaderStages::FRAGMENT,
+                false,
+                wgpu::TextureViewDimension::D2,
+                sample_type,
+            )
+            .build(device);
+
+        let pass = |layout: &wgpu::BindGroupLayout,
+                    sources: &[&wgpu::Texture],
+                    target: Option<wgpu::Texture>| {
+            let uniforms = device.create_buffer_init(&wgpu::BufferInitDescriptor {
+                label: Some("bloom uniforms"),
+                contents: bytemuck::bytes_of(&[0.0_f32; 4]),
+                usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            });
+            let views: Vec<_> = sources.iter().map(|source| source.view().build()).collect();
+            let mut builder = wgpu::BindGroupBuilder::new()
+                .binding(uniforms.as_entire_binding())
+                .texture_view(&views[0])
+                .sampler(&sampler);
+            for view in &views[1..] {
+                builder = builder.texture_view(view);
+            }
+            let bind_group = builder.build(device, layout);
+            Pass {
+                uniforms,
+                bind_group,
+                target,
+            }
+        };
+        let composite = pass(&composite_layout, &[&scene, &along], None);
+        let across = pass(&blur_layout, &[&scene], Some(across));
+        let along = pass(
+            &blur_layout,
+            &[across.target.as_ref().unwrap()],
+            Some(along),
+        );
+
+        let module = device.create_shader_module(wgpu::include_wgsl!("shaders/bloom.wgsl"));
+        let pipeline = |layout: &wgpu::BindGroupLayout, entry_point, format, samples| {
+            let pipeline_layout =
+                wgpu::create_pipeline_layout(device, Some("bloom"), &[layout], &[]);
+            wgpu::RenderPipelineBuilder::from_layout(&pipeline_layout, &module)
+                .vertex_entry_point("vs_main")
+                .fragment_shader(&module)
+                .fragment_entry_point(entry_point)
+                .color_format(format)
+                .sample_count(samples)
+                .build(device)
+        };
+        let blur_pipeline = pipeline(&blur_layout, "fs_blur", Self::FORMAT, 1);
+        let composite_pipeline = pipeline(
+            &composite_layout,
+            "fs_composite",
+            Frame::TEXTURE_FORMAT,
+            msaa_samples,
+        );
+
+        Bloom {
+            enabled: false,
+            threshold: Self::THRESHOLD,
+            intensity: Self::INTENSITY,
+            renderer,
+            multisampled,
+            scene,
+            across,
+            along,
+            composite,
+            blur_pipeline,
+            composite_pipeline,
+        }
+    }
+
+    /// Draw the scene with its glow to the frame, rather than drawing it directly.
+    pub fn display(&mut self, window: &Window, frame: &Frame, draw: &Draw) {
+        // Start over at the new size when the window is resized
+        let size = window.inner_size_pixels();
+        if [size.0, size.1] != self.scene.size() {
+            *self = Bloom {
+                enabled: self.enabled,
+                threshold: self.threshold,
+                intensity: self.intensity,
+                ..Self::new(window)
+            };
+        }
+
+        let device = window.device();
+        let mut encoder = frame.command_encoder();
+        let scene = self.scene.view().build();
+        match &self.multisampled {
+            Some(multisampled) => self.renderer.encode_render_pass(
+                device,
+                &mut encoder,
+                draw,
+                window.scale_factor(),
+                self.scene.size(),
+                &multisampled.view().build(),
+                Some(&scene),
+            ),
+            None => self.renderer.encode_render_pass(
+                device,
+                &mut encoder,
+                draw,
+                window.scale_factor(),
+                self.scene.size(),
+                &scene,
+                None,
+            ),
+        }
+
+        // Only the first blur keeps the bright parts, the second one blurs them all
+        let [width, height] = self.along.target.as_ref().unwrap().size();
+        let queue = window.queue();
+        let uniforms = [
+            (
+                &self.across,
+                [Self::SPREAD / width as f32, 0.0, self.threshold, 0.0],
+            ),
+            (&self.along, [0.0, Self::SPREAD / height as f32, 0.0, 0.0]),
+            (&self.composite, [0.0, 0.0, 0.0, self.intensity]),
+        ];
+        for (pass, uniforms) in uniforms {
+            queue.write_buffer(&pass.uniforms, 0, bytemuck::bytes_of(&uniforms));
+        }
+
+        for pass in [&self.across, &self.along] {
+            let target = pass.target.as_ref().unwrap().view().build();
+            let mut render_pass = wgpu::RenderPassBuilder::new()
+                .color_attachment(&target, |color| color)
+                .begin(&mut encoder);
+            render_pass.set_pipeline(&self.blur_pipeline);
+            render_pass.set_bind_group(0, &pass.bind_group, &[]);
+            render_pass.draw(0..3, 0..1);
+        }
+
+        let mut render_pass = wgpu::RenderPassBuilder::new()
+            .color_attachment(frame.texture_view(), |color| color)
+            .begin(&mut encoder);
+        render_pass.set_pipeline(&self.composite_pipeline);
+        render_pass.set_bind_group(0, &self.composite.bind_group, &[]);
+        render_pass.draw(0..3, 0..1);
+    }
+}
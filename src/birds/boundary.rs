@@ -0,0 +1,125 @@
+use crate::args::Args;
+use nannou::prelude::*;
+
+/// How agents behave at the edges of the window.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Boundary {
+    /// Leave through an edge and come back through the opposite one
+    Wrap,
+    /// Bounce off the edges
+    Bounce,
+    /// Turn back before reaching the edges
+    Steer,
+}
+
+impl Boundary {
+    pub const MARGIN_COLOR: (f32, f32, f32, f32) = (0.3, 0.3, 0.3, 0.5);
+
+    /// Default distance from the edges at which agents start turning back, and
+    /// the range and step it can be adjusted within
+    const MARGIN: f32 = 100.0;
+    const MARGIN_RANGE: (f32, f32) = (20.0, 400.0);
+    const MARGIN_STEP: f32 = 20.0;
+
+    pub fn next(self) -> Self {
+        match self {
+            Boundary::Wrap => Boundary::Bounce,
+            Boundary::Bounce => Boundary::Steer,
+            Boundary::Steer => Boundary::Wrap,
+        }
+    }
+
+    /// Move a body by its velocity, keeping it inside the window.
+    /// Returns the new position and velocity of the body.
+    pub fn step(self, position: Vec2, velocity: Vec2, win_rect: &Rect) -> (Vec2, Vec2) {
+        let position = position + velocity;
+        match self {
+            Boundary::Wrap => (wrap(position, win_rect), velocity),
+            Boundary::Bounce => bounce(position, velocity, win_rect),
+            Boundary::Steer => (position, velocity),
+        }
+    }
+
+    /// Margin given by `--margin <pixels>` on the command line, or the default margin.
+    pub fn margin_from_args() -> f32 {
+        Args::get().margin.map_or(Self::MARGIN, |margin| {
+            margin.clamp(Self::MARGIN_RANGE.0, Self::MARGIN_RANGE.1)
+        })
+    }
+
+    /// Widen the margin by a step, or narrow it when the step is negative.
+    pub fn adjust_margin(margin: f32, steps: f32) -> f32 {
+        (margin + steps * Self::MARGIN_STEP).clamp(Self::MARGIN_RANGE.0, Self::MARGIN_RANGE.1)
+    }
+
+    /// Direction in which a body should steer to stay inside the window when
+    /// within the margin along the edges.
+    /// The closer the edge, the stronger the force.
+    pub fn steer(self, position: Vec2, win_rect: &Rect, margin: f32) -> Vec2 {
+        if self != Boundary::Steer {
+            return Vec2::ZERO;
+        }
+
+        let closeness = |distance: f32| (1.0 - distance / margin).max(0.0);
+        vec2(
+            closeness(position.x - win_rect.left()) - closeness(win_rect.right() - position.x),
+            closeness(position.y - win_rect.bottom()) - closeness(win_rect.top() - position.y),
+        )
+    }
+
+    /// Draw the inner edge of the margin when steering.
+    pub fn display(self, draw: &Draw, win_rect: &Rect, margin: f32, color: Rgba) {
+        if self != Boundary::Steer {
+            return;
+        }
+        let inner = win_rect.pad(margin);
+        draw.rect()
+            .xy(inner.xy())
+            .wh(inner.wh())
+            .no_fill()
+            .stroke_weight(1.0)
+            .stroke(color);
+    }
+}
+
+/// Wrap a position around the edges of the window.
+pub fn wrap(mut position: Vec2, win_rect: &Rect) -> Vec2 {
+    // Wrap around screen width
+    if position.x < win_rect.left() {
+        position.x = win_rect.right();
+    } else if position.x > win_rect.right() {
+        position.x = win_rect.left();
+    }
+
+    // Wrap around screen height
+    if position.y > win_rect.top() {
+        position.y = win_rect.bottom();
+    } else if position.y < win_rect.bottom() {
+        position.y = win_rect.top();
+    }
+
+    position
+}
+
+/// Reflect a body off the edges of the window.
+fn bounce(mut position: Vec2, mut velocity: Vec2, win_rect: &Rect) -> (Vec2, Vec2) {
+    // Bounce off screen width
+    if position.x < win_rect.left() {
+        position.x = 2.0 * win_rect.left() - position.x;
+        velocity.x = velocity.x.abs();
+    } else if position.x > win_rect.right() {
+        position.x = 2.0 * win_rect.right() - position.x;
+        velocity.x = -velocity.x.abs();
+    }
+
+    // Bounce off screen height
+    if position.y < win_rect.bottom() {
+        position.y = 2.0 * win_rect.bottom() - position.y;
+        velocity.y = velocity.y.abs();
+    } else if position.y > win_rect.top() {
+        position.y = 2.0 * win_rect.top() - position.y;
+        velocity.y = -velocity.y.abs();
+    }
+
+    (position, velocity)
+}
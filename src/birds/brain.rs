@@ -0,0 +1,100 @@
+use crate::args::Args;
+use crate::parameters::Distribution;
+use clap::ValueEnum;
+use nannou::rand::Rng;
+
+/// How agents turn what they perceive of their neighbors into steering.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Brain {
+    /// Weighted separation, alignment and cohesion rules
+    Rules,
+    /// Per-agent feed-forward network, trained by evolution
+    Neural,
+    /// Couzin's zones of repulsion, orientation and attraction
+    Zones,
+}
+
+impl Brain {
+    /// Brain selected on the command line with `--brain <rules|neural|zones>`, the rules by default.
+    pub fn from_args() -> Self {
+        Args::get().brain
+    }
+
+    pub fn next(self) -> Self {
+        match self {
+            Brain::Rules => Brain::Neural,
+            Brain::Neural => Brain::Zones,
+            Brain::Zones => Brain::Rules,
+        }
+    }
+}
+
+/// Small feed-forward network with a single hidden layer.
+#[derive(Debug, Clone, Copy)]
+pub struct Network {
+    weights: [f32; Network::NUM_WEIGHTS],
+}
+
+impl Network {
+    pub const INPUTS: usize = 7;
+    pub const OUTPUTS: usize = 2;
+    const HIDDEN: usize = 6;
+
+    // Every layer has a weight per input and a bias per neuron
+    const NUM_HIDDEN_WEIGHTS: usize = (Self::INPUTS + 1) * Self::HIDDEN;
+    const NUM_WEIGHTS: usize = Self::NUM_HIDDEN_WEIGHTS + (Self::HIDDEN + 1) * Self::OUTPUTS;
+
+    // Chance of each weight mutating and the deviation of the mutation
+    const MUTATION_RATE: f32 = 0.1;
+    const MUTATION_DEVIATION: f32 = 0.3;
+
+    pub fn random(rng: &mut impl Rng) -> Self {
+        let distribution = Distribution::new(0.0, 1.0 / (Self::INPUTS as f32).sqrt());
+        Network {
+            weights: std::array::from_fn(|_| distribution.sample_signed(rng)),
+        }
+    }
+
+    /// Evaluate the network, every output lying in `[-1, 1]`.
+    pub fn forward(&self, inputs: [f32; Self::INPUTS]) -> [f32; Self::OUTPUTS] {
+        let (hidden_weights, output_weights) = self.weights.split_at(Self::NUM_HIDDEN_WEIGHTS);
+        let hidden: [f32; Self::HIDDEN] = std::array::from_fn(|i| {
+            let neuron = &hidden_weights[i * (Self::INPUTS + 1)..(i + 1) * (Self::INPUTS + 1)];
+            activate(neuron, &inputs)
+        });
+        std::array::from_fn(|i| {
+            let neuron = &output_weights[i * (Self::HIDDEN + 1)..(i + 1) * (Self::HIDDEN + 1)];
+            activate(neuron, &hidden)
+        })
+    }
+
+    /// Child network taking each weight from either parent.
+    pub fn crossover(&self, other: &Network, rng: &mut impl Rng) -> Self {
+        Network {
+            weights: std::array::from_fn(|i| {
+                if rng.gen() {
+                    self.weights[i]
+                } else {
+                    other.weights[i]
+                }
+            }),
+        }
+    }
+
+    /// Randomly perturb some of the weights.
+    pub fn mutate(&mut self, rng: &mut impl Rng) {
+        let distribution = Distribution::new(0.0, Self::MUTATION_DEVIATION);
+        for weight in &mut self.weights {
+            if rng.gen::<f32>() < Self::MUTATION_RATE {
+                *weight += distribution.sample_signed(rng);
+            }
+        }
+    }
+}
+
+/// Output of a neuron, its last weight being the bias.
+fn activate(neuron: &[f32], inputs: &[f32]) -> f32 {
+    let (bias, weights) = neuron.split_last().unwrap();
+    let sum: f32 = weights.iter().zip(inputs).map(|(w, x)| w * x).sum();
+    (sum + bias).tanh()
+}
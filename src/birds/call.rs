@@ -0,0 +1,63 @@
+use crate::agent::Agent;
+use crate::agents::Agents;
+use nannou::prelude::*;
+use nannou::rand::Rng;
+
+/// Call an agent emits, spreading as a ring that rallies the agents it passes over.
+#[derive(Debug, Clone, Copy)]
+pub struct Call {
+    pub origin: Vec2,
+    pub radius: f32,
+}
+
+impl Call {
+    pub const COLOR: (f32, f32, f32, f32) = (0.9, 0.9, 1.0, 0.5);
+
+    /// Distance travelled by the ring every frame and distance it fades out at
+    const SPEED: f32 = 4.0;
+    const MAX_RADIUS: f32 = 250.0;
+
+    /// Chance of every agent calling in a frame
+    const CHANCE: f32 = 1.0 / 3000.0;
+
+    pub fn new(origin: Vec2) -> Self {
+        Call {
+            origin,
+            radius: 0.0,
+        }
+    }
+
+    /// Add the calls emitted by the agents this frame.
+    pub fn emit(agents: &Agents, rng: &mut impl Rng, calls: &mut Vec<Call>) {
+        calls.extend(
+            (0..agents.len())
+                .filter(|&i| !agents.is_perched(i) && rng.gen::<f32>() < Self::CHANCE)
+                .map(|i| Call::new(agents.positions[i])),
+        );
+    }
+
+    /// Spread the ring, rallying the agents it passes over.
+    /// Returns whether the call can still be heard.
+    pub fn update(&mut self, agents: &mut Agents) -> bool {
+        let inner = self.radius;
+        self.radius += Self::SPEED;
+        for i in 0..agents.len() {
+            let distance = agents.positions[i].distance(self.origin);
+            if distance >= inner && distance < self.radius {
+                agents.change(i, Agent::hear_call);
+            }
+        }
+        self.radius < Self::MAX_RADIUS
+    }
+
+    pub fn display(&self, draw: &Draw, color: Rgba) {
+        // The ring fades as it spreads
+        let fade = 1.0 - self.radius / Self::MAX_RADIUS;
+        draw.ellipse()
+            .xy(self.origin)
+            .radius(self.radius)
+            .no_fill()
+            .stroke_weight(1.0)
+            .stroke(rgba(color.red, color.green, color.blue, color.alpha * fade));
+    }
+}
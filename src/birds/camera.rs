@@ -0,0 +1,71 @@
+use nannou::prelude::*;
+
+/// Perspective camera orbiting the origin.
+#[derive(Debug, Clone, Copy)]
+pub struct Camera {
+    pub yaw: f32,
+    pub pitch: f32,
+    pub distance: f32,
+    pub focal_length: f32,
+}
+
+impl Camera {
+    const NEAR: f32 = 1.0;
+
+    pub fn new(distance: f32) -> Self {
+        Camera {
+            yaw: 0.0,
+            pitch: 0.3,
+            distance,
+            focal_length: distance,
+        }
+    }
+
+    /// Rotation from world space to camera space.
+    fn rotation(&self) -> Quat {
+        Quat::from_rotation_x(self.pitch) * Quat::from_rotation_y(self.yaw)
+    }
+
+    /// Distance from the camera to a point along the viewing direction.
+    pub fn depth(&self, point: Vec3) -> f32 {
+        self.distance - (self.rotation() * point).z
+    }
+
+    /// Fill `order` with the indices of points from the furthest to the closest,
+    /// so closer points are drawn over further ones.
+    pub fn back_to_front(&self, points: impl Iterator<Item = Vec3>, order: &mut Vec<(usize, f32)>) {
+        order.clear();
+        order.extend(points.map(|point| self.depth(point)).enumerate());
+        order.sort_unstable_by(|(_, a), (_, b)| b.total_cmp(a));
+    }
+
+    /// Project a point on the screen, returning its screen position and its
+    /// depth-based scale, or `None` when the point is behind the camera.
+    pub fn project(&self, point: Vec3) -> Option<(Vec2, f32)> {
+        let view = self.rotation() * point;
+        let depth = self.distance - view.z;
+        if depth < Self::NEAR {
+            return None;
+        }
+
+        let scale = self.focal_length / depth;
+        Some((view.truncate() * scale, scale))
+    }
+
+    /// Draw the edges of a cuboid as seen from the camera.
+    pub fn display_cuboid(&self, draw: &Draw, cuboid: &Cuboid, color: Rgba) {
+        let corners = cuboid.corners().map(Vec3::from);
+        for (i, &a) in corners.iter().enumerate() {
+            for &b in &corners[i + 1..] {
+                // Corners sharing an edge differ along a single axis
+                let shared_axes = (a - b).to_array().iter().filter(|d| **d == 0.0).count();
+                if shared_axes != 2 {
+                    continue;
+                }
+                if let (Some((start, _)), Some((end, _))) = (self.project(a), self.project(b)) {
+                    draw.line().start(start).end(end).color(color);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,127 @@
+use crate::agents::Agents;
+use crate::config::Config;
+use crate::index::NeighborIndex;
+use crate::spatial_hash::SpatialHash;
+
+/// Passes over all pairs of agents, as pushing two agents apart may push them into others
+const ITERATIONS: usize = 2;
+
+/// Distance within which two agents may overlap, twice the radius of the largest body
+const REACH: f32 = 20.0;
+
+/// Keeps the bodies of agents from overlapping, holding on to the grid of the
+/// agents and the buffer of those that may touch from one step to the next.
+pub struct Collider {
+    grid: SpatialHash,
+    others: Vec<usize>,
+}
+
+impl Collider {
+    pub fn new() -> Self {
+        Collider {
+            grid: SpatialHash::new(&Agents::new()),
+            others: Vec::new(),
+        }
+    }
+
+    /// Push overlapping agents apart so their bodies never overlap, each moving
+    /// half of the overlap away from the other.
+    pub fn resolve(&mut self, agents: &mut Agents, config: &Config) {
+        let Collider { grid, others } = self;
+        let radius = |species: usize| config.species[species].radius();
+        for _ in 0..ITERATIONS {
+            // Only agents in the cells around an agent may touch it
+            grid.update(agents);
+            for i in 0..agents.len() {
+                if agents.is_perched(i) {
+                    continue;
+                }
+                others.clear();
+                grid.within(agents.positions[i], REACH, others);
+                others.retain(|&j| j > i && !agents.is_perched(j));
+                let positions = &mut agents.positions;
+                for &j in others.iter() {
+                    let offset = positions[i] - positions[j];
+                    let reach = radius(agents.species[i]) + radius(agents.species[j]);
+                    let distance_squared = offset.length_squared();
+                    if distance_squared >= reach * reach || distance_squared == 0.0 {
+                        continue;
+                    }
+                    let distance = distance_squared.sqrt();
+                    let correction = offset / distance * (reach - distance) * 0.5;
+                    positions[i] += correction;
+                    positions[j] -= correction;
+                }
+            }
+        }
+    }
+}
+
+impl Default for Collider {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::Agent;
+    use nannou::prelude::*;
+    use nannou::rand::rngs::SmallRng;
+    use nannou::rand::SeedableRng;
+
+    /// Pairs of agents far enough from the others to be pushed apart on their
+    /// own, from overlapping to exactly touching and slightly apart.
+    fn layout() -> Agents {
+        let config = Config::get();
+        let mut rng = SmallRng::seed_from_u64(0);
+        let reach = 2.0 * Agent::spawn(Vec2::ZERO, 0, &config, &mut rng).radius(&config);
+        [0.0, 0.25, 0.5, 0.99, 1.0, 1.01]
+            .into_iter()
+            .enumerate()
+            .flat_map(|(pair, share)| {
+                let center = vec2(pair as f32 * 100.0, 0.0);
+                [center, center + vec2(0.0, reach * share)]
+            })
+            .map(|position| Agent::spawn(position, 0, &config, &mut rng))
+            .collect()
+    }
+
+    /// Positions the agents of isolated pairs end up at, comparing distances
+    /// rather than their squares as the collisions used to.
+    fn reference(agents: &Agents) -> Vec<Vec2> {
+        let mut positions = agents.positions.clone();
+        for _ in 0..ITERATIONS {
+            for pair in positions.chunks_mut(2) {
+                let offset = pair[0] - pair[1];
+                let distance = offset.length();
+                let overlap = agents.get(0).radius(&Config::get()) * 2.0 - distance;
+                if overlap > 0.0 && distance > 0.0 {
+                    let correction = offset / distance * overlap * 0.5;
+                    pair[0] += correction;
+                    pair[1] -= correction;
+                }
+            }
+        }
+        positions
+    }
+
+    #[test]
+    fn squared_distances_push_the_same_agents_apart() {
+        let mut agents = layout();
+        let expected = reference(&agents);
+        Collider::new().resolve(&mut agents, &Config::get());
+        for (position, expected) in agents.positions.iter().zip(&expected) {
+            assert!(
+                position.distance(*expected) < 1e-4,
+                "{position} != {expected}"
+            );
+        }
+
+        // Agents exactly touching or apart stay where they were
+        for (position, before) in agents.positions.iter().zip(layout().positions).skip(8) {
+            assert_eq!(*position, before);
+        }
+    }
+}
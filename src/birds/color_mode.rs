@@ -0,0 +1,119 @@
+use crate::config;
+use nannou::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// What the color of an agent shows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorMode {
+    /// Color of the agent's species
+    Species,
+    /// Color of the connected flock the agent belongs to
+    Flock,
+    /// Color of the species tinted by the agent's personality
+    Personality,
+    /// Hue of the direction the agent flies in, so aligned agents share a color
+    Heading,
+    /// Speed of the agent relative to its cruising speed, along the configured gradient
+    Speed,
+    /// Change in velocity over the last step, along the configured gradient
+    Acceleration,
+    /// Number of neighbors perceived in the last step, along the configured gradient
+    Density,
+}
+
+impl ColorMode {
+    /// Speeds relative to the cruising speed at either end of the gradient
+    const SPEED_RANGE: (f32, f32) = (0.5, 2.0);
+    /// Accelerations relative to the steering force at the fast end of the gradient
+    const ACCELERATION_RANGE: f32 = 2.0;
+    /// Number of neighbors at the crowded end of the gradient
+    const CROWDED: usize = 20;
+
+    pub fn next(self) -> Self {
+        match self {
+            ColorMode::Species => ColorMode::Flock,
+            ColorMode::Flock => ColorMode::Personality,
+            ColorMode::Personality => ColorMode::Heading,
+            ColorMode::Heading => ColorMode::Speed,
+            ColorMode::Speed => ColorMode::Acceleration,
+            ColorMode::Acceleration => ColorMode::Density,
+            ColorMode::Density => ColorMode::Species,
+        }
+    }
+
+    /// Color around the hue wheel of the angle of a velocity, red heading right.
+    pub fn heading(velocity: Vec2) -> Vec4 {
+        let hue = (velocity.y.atan2(velocity.x) / TAU).rem_euclid(1.0);
+        let color: Rgba = hsva(hue, 0.8, 1.0, 1.0).into();
+        vec4(color.red, color.green, color.blue, color.alpha)
+    }
+
+    /// How far along the gradient a speed lies, given the cruising speed.
+    pub fn speed(speed: f32, cruise: f32) -> f32 {
+        let (slow, fast) = Self::SPEED_RANGE;
+        ((speed / cruise.max(f32::EPSILON) - slow) / (fast - slow)).clamp(0.0, 1.0)
+    }
+
+    /// How far along the gradient an acceleration lies, given the steering force.
+    pub fn acceleration(acceleration: f32, max_force: f32) -> f32 {
+        (acceleration / (max_force * Self::ACCELERATION_RANGE).max(f32::EPSILON)).min(1.0)
+    }
+
+    /// How far along the gradient a number of neighbors lies.
+    pub fn density(neighbors: usize) -> f32 {
+        (neighbors as f32 / Self::CROWDED as f32).min(1.0)
+    }
+}
+
+/// Colors from slow to fast, or sparse to crowded, evenly spaced along the
+/// gradient. Configured as
+///
+/// ```toml
+/// gradient = [[0.2, 0.3, 1.0, 1.0], [1.0, 0.2, 0.1, 1.0]]
+/// ```
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(try_from = "Vec<Stop>", into = "Vec<(f32, f32, f32, f32)>")]
+pub struct Gradient(Vec<(f32, f32, f32, f32)>);
+
+/// Color of a gradient, read as every other color of the configuration.
+#[derive(Deserialize)]
+struct Stop(#[serde(deserialize_with = "config::color")] (f32, f32, f32, f32));
+
+impl Gradient {
+    /// Color a share of the way along the gradient.
+    pub fn at(&self, share: f32) -> Vec4 {
+        let position = share.clamp(0.0, 1.0) * (self.0.len() - 1) as f32;
+        let i = (position as usize).min(self.0.len() - 2);
+        Vec4::from(self.0[i]).lerp(Vec4::from(self.0[i + 1]), position - i as f32)
+    }
+}
+
+impl Default for Gradient {
+    /// Calm blue through yellow to alarmed red
+    fn default() -> Self {
+        Gradient(vec![
+            (0.2, 0.4, 1.0, 1.0),
+            (1.0, 0.9, 0.3, 1.0),
+            (1.0, 0.2, 0.1, 1.0),
+        ])
+    }
+}
+
+impl TryFrom<Vec<Stop>> for Gradient {
+    type Error = String;
+
+    fn try_from(colors: Vec<Stop>) -> Result<Self, Self::Error> {
+        if colors.len() < 2 {
+            return Err(format!("expected at least 2 colors, got {}", colors.len()));
+        }
+        Ok(Gradient(
+            colors.into_iter().map(|Stop(color)| color).collect(),
+        ))
+    }
+}
+
+impl From<Gradient> for Vec<(f32, f32, f32, f32)> {
+    fn from(gradient: Gradient) -> Self {
+        gradient.0
+    }
+}
@@ -0,0 +1,54 @@
+use nannou::prelude::*;
+
+/// Perceptually uniform color scale mapping a value from 0 to 1 to a color, dark
+/// for low values and bright for high ones.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Colormap {
+    /// Black through purple and orange to pale yellow
+    Inferno,
+    /// Purple through teal to yellow
+    Viridis,
+}
+
+impl Colormap {
+    // Colors evenly spaced along the scales
+    const INFERNO: [(f32, f32, f32); 5] = [
+        (0.0, 0.0, 0.016),
+        (0.341, 0.063, 0.431),
+        (0.737, 0.216, 0.329),
+        (0.976, 0.557, 0.035),
+        (0.988, 1.0, 0.643),
+    ];
+    const VIRIDIS: [(f32, f32, f32); 5] = [
+        (0.267, 0.005, 0.329),
+        (0.231, 0.322, 0.545),
+        (0.129, 0.569, 0.549),
+        (0.369, 0.788, 0.384),
+        (0.992, 0.906, 0.144),
+    ];
+
+    pub fn next(self) -> Self {
+        match self {
+            Colormap::Inferno => Colormap::Viridis,
+            Colormap::Viridis => Colormap::Inferno,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Colormap::Inferno => "inferno",
+            Colormap::Viridis => "viridis",
+        }
+    }
+
+    /// Color a share of the way along the scale.
+    pub fn at(self, share: f32) -> Vec3 {
+        let colors = match self {
+            Colormap::Inferno => &Self::INFERNO,
+            Colormap::Viridis => &Self::VIRIDIS,
+        };
+        let position = share.clamp(0.0, 1.0) * (colors.len() - 1) as f32;
+        let i = (position as usize).min(colors.len() - 2);
+        Vec3::from(colors[i]).lerp(Vec3::from(colors[i + 1]), position - i as f32)
+    }
+}
@@ -0,0 +1,459 @@
+use crate::args::Args;
+use crate::background::Background;
+use crate::color_mode::Gradient;
+use crate::profile::Profile;
+use crate::randomize::Bounds;
+use crate::species::{Interactions, Species};
+use crate::timeline::Timeline;
+use serde::de::Error;
+use serde::{Deserialize, Deserializer, Serialize};
+use std::f32::consts::{PI, TAU};
+use std::io::ErrorKind;
+use std::ops::Range;
+use std::sync::{Arc, OnceLock, RwLock};
+
+/// Constants of the flock read from a TOML file at startup and again whenever it
+/// changes, so it can be tuned without recompiling or restarting. Settings the
+/// file leaves out keep their default, but every species needs its color, count,
+/// speed and size, as in
+///
+/// ```toml
+/// seed = 42
+///
+/// [agent]
+/// detection_radius = 80.0
+///
+/// [[species]]
+/// color = [1.0, 1.0, 1.0, 1.0]
+/// count = 200
+/// speed = 1.5
+/// size = [15.0, 15.0]
+/// cohesion_weight = 1.2
+///
+/// [[interaction]]
+/// species = 0
+/// other = 1
+/// alignment = 0.5
+/// ```
+///
+/// with a `[[species]]` table for every species, as many as wanted, and an
+/// `[[interaction]]` table for each pair of species flocking differently than by
+/// default, as described in [`Interactions`]. Parameters may also change over
+/// time following `[[keyframe]]` tables, as described in [`Timeline`], and a
+/// gradient or an image may be drawn behind the agents, as described in
+/// [`Background`]. Settings out of their range are reported with the line they
+/// are on, and the file is then ignored.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Config {
+    /// Seed of every random draw, unless given on the command line
+    pub seed: Option<u64>,
+    /// Colors of agents by speed, acceleration or density, from slow to fast
+    pub gradient: Gradient,
+    /// Drawn behind the agents, unless the day and night cycle colors the sky
+    pub background: Background,
+    #[serde(deserialize_with = "populated")]
+    pub species: Vec<Species>,
+    pub agent: AgentConfig,
+    #[serde(rename = "interaction")]
+    pub interactions: Interactions,
+    #[serde(rename = "keyframe")]
+    pub timeline: Timeline,
+    /// Ranges of the parameters drawn at random on request
+    pub randomize: Bounds,
+}
+
+/// Radii and steering factors of every agent.
+#[derive(Debug, Clone, Copy, Deserialize, Serialize)]
+#[serde(default)]
+pub struct AgentConfig {
+    /// Distance below which agents separate from their neighbors
+    #[serde(deserialize_with = "positive")]
+    pub min_distance: f32,
+    /// Distance within which agents flee from predators
+    #[serde(deserialize_with = "non_negative")]
+    pub panic_radius: f32,
+    // Steering limits per step
+    #[serde(deserialize_with = "positive")]
+    pub max_force: f32,
+    #[serde(deserialize_with = "positive")]
+    pub max_turn_rate: f32,
+    /// Angle of the vision cone centered on the heading, in radians, leaving a
+    /// blind spot behind agents below a full turn
+    #[serde(deserialize_with = "angle")]
+    pub field_of_view: f32,
+    // Averages of the flocking parameters agents are born with
+    #[serde(deserialize_with = "positive")]
+    pub detection_radius: f32,
+    #[serde(deserialize_with = "non_negative")]
+    pub separation_weight: f32,
+    #[serde(deserialize_with = "non_negative")]
+    pub alignment_weight: f32,
+    #[serde(deserialize_with = "non_negative")]
+    pub cohesion_weight: f32,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            seed: None,
+            gradient: Gradient::default(),
+            background: Background::default(),
+            species: Species::DEFAULTS.to_vec(),
+            agent: AgentConfig::default(),
+            interactions: Interactions::new(Species::DEFAULTS.len()),
+            timeline: Timeline::default(),
+            randomize: Bounds::default(),
+        }
+    }
+}
+
+impl Default for AgentConfig {
+    fn default() -> Self {
+        AgentConfig {
+            min_distance: 30.0,
+            panic_radius: 120.0,
+            max_force: 0.05,
+            max_turn_rate: 0.08,
+            field_of_view: 1.5 * PI,
+            detection_radius: 60.0,
+            separation_weight: 1.5,
+            alignment_weight: 1.0,
+            cohesion_weight: 0.8,
+        }
+    }
+}
+
+/// Configuration in use, replaced whole on every change. Replaced configurations
+/// are freed once the last of their readers lets go of them.
+static CONFIG: OnceLock<RwLock<Arc<Config>>> = OnceLock::new();
+
+impl Config {
+    /// Configuration of the running sketch, read on first use.
+    pub fn get() -> Arc<Config> {
+        Self::current().read().unwrap().clone()
+    }
+
+    fn current() -> &'static RwLock<Arc<Config>> {
+        CONFIG.get_or_init(|| {
+            let config = Config::read().unwrap_or_else(|error| {
+                eprintln!("{error}");
+                Config::default()
+            });
+            RwLock::new(Arc::new(config))
+        })
+    }
+
+    /// Read the file again and use it from now on, returning the configuration it
+    /// replaces and the new one, or why the file couldn't be read while keeping the
+    /// current configuration.
+    pub fn reload() -> Result<(Arc<Config>, Arc<Config>), String> {
+        Ok(Self::set(Config::read()?))
+    }
+
+    /// Use another configuration from now on, returning the configuration it
+    /// replaces and the new one.
+    pub fn set(config: Config) -> (Arc<Config>, Arc<Config>) {
+        let config = Arc::new(config);
+        let previous = std::mem::replace(&mut *Self::current().write().unwrap(), config.clone());
+        (previous, config)
+    }
+
+    /// Change the number of agents, keeping the proportions of the species.
+    pub fn scale_agents(&mut self, agents: usize) {
+        let total: usize = self.species.iter().map(|species| species.count).sum();
+        for species in self.species.iter_mut() {
+            species.count = agents * species.count / total.max(1);
+        }
+
+        // The first species takes the agents lost to rounding down
+        let counted: usize = self.species.iter().map(|species| species.count).sum();
+        self.species[0].count += agents - counted;
+    }
+
+    /// Write the configuration to a TOML file, in the format it is read in.
+    pub fn save(&self, path: &str) -> Result<(), String> {
+        let text = toml::to_string(self).map_err(|error| error.to_string())?;
+        std::fs::write(path, text)
+            .map_err(|error| format!("Could not save configuration {path}: {error}"))
+    }
+
+    /// Configuration written in the TOML text of a file, pointing at the line of
+    /// the first setting out of its range. The interactions are only checked once
+    /// the species they name are read.
+    fn parse(path: &str, text: &str) -> Result<Config, String> {
+        let mut config: Config = toml::from_str(text)
+            .map_err(|error| invalid(path, text, error.span(), error.message()))?;
+        let count = config.species.len();
+        config.interactions.resolve(count).map_err(|unknown| {
+            invalid(path, text, Some(unknown.span.clone()), &unknown.to_string())
+        })?;
+        Ok(config)
+    }
+
+    /// Configuration read from the file given by `--config <path>`, the defaults
+    /// when the file is absent, with the species scaled to the number of agents
+    /// and speed given on the command line, or to the number of agents of the
+    /// performance profile.
+    fn read() -> Result<Config, String> {
+        let args = Args::get();
+        let path = &args.config;
+        let mut config = match std::fs::read_to_string(path) {
+            Ok(text) => Config::parse(path, &text)?,
+            Err(error) if error.kind() == ErrorKind::NotFound => Config::default(),
+            Err(error) => return Err(format!("Could not read configuration {path}: {error}")),
+        };
+
+        if let Some(agents) = args.agents {
+            if agents == 0 {
+                return Err("Invalid --agents: expected at least 1 agent".to_string());
+            }
+            config.scale_agents(agents);
+        } else if let Some(profile) = Profile::from_args() {
+            config.scale_agents(profile.agents());
+        }
+        if let Some(speed) = args.speed {
+            for species in config.species.iter_mut() {
+                species.speed *= speed;
+            }
+        }
+        Ok(config)
+    }
+}
+
+/// Error of a configuration file, pointing at the line of the offending setting
+/// when it is known.
+fn invalid(path: &str, text: &str, span: Option<Range<usize>>, message: &str) -> String {
+    let Some(span) = span else {
+        return format!("Invalid configuration {path}: {message}");
+    };
+    let line = text[..span.start].matches('\n').count() + 1;
+    let setting = text.lines().nth(line - 1).unwrap_or_default().trim();
+    format!("Invalid configuration {path} at line {line}, `{setting}`: {message}")
+}
+
+/// Read a number greater than zero, such as a distance.
+pub fn positive<'de, D: Deserializer<'de>>(deserializer: D) -> Result<f32, D::Error> {
+    within(
+        f32::deserialize(deserializer)?,
+        |value| value > 0.0,
+        "above 0",
+    )
+}
+
+/// Read a number of zero or more, such as a weight.
+pub fn non_negative<'de, D: Deserializer<'de>>(deserializer: D) -> Result<f32, D::Error> {
+    within(
+        f32::deserialize(deserializer)?,
+        |value| value >= 0.0,
+        "of at least 0",
+    )
+}
+
+/// Read an angle above zero of at most a full turn, in radians.
+pub fn angle<'de, D: Deserializer<'de>>(deserializer: D) -> Result<f32, D::Error> {
+    within(
+        f32::deserialize(deserializer)?,
+        |value| value > 0.0 && value <= TAU,
+        "above 0 and at most 2π",
+    )
+}
+
+/// Read a factor from 0 to 1.
+pub fn fraction<'de, D: Deserializer<'de>>(deserializer: D) -> Result<f32, D::Error> {
+    within(f32::deserialize(deserializer)?, in_unit, "from 0 to 1")
+}
+
+/// Read a factor from 0 to 1 that may be left out.
+pub fn optional_fraction<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<f32>, D::Error> {
+    fraction(deserializer).map(Some)
+}
+
+/// Read a range of numbers of at least 0, as `[min, max]`.
+pub fn interval<'de, D: Deserializer<'de>>(deserializer: D) -> Result<(f32, f32), D::Error> {
+    let (min, max) = <(f32, f32)>::deserialize(deserializer)?;
+    within(min, |min| min >= 0.0, "of at least 0")?;
+    within(max, |max| max >= min, &format!("of at least {min}"))?;
+    Ok((min, max))
+}
+
+/// Read a color, each of its components from 0 to 1.
+pub fn color<'de, D: Deserializer<'de>>(deserializer: D) -> Result<(f32, f32, f32, f32), D::Error> {
+    let color @ (r, g, b, a) = <(f32, f32, f32, f32)>::deserialize(deserializer)?;
+    for component in [r, g, b, a] {
+        within(component, in_unit, "from 0 to 1")?;
+    }
+    Ok(color)
+}
+
+/// Read a width and height, both above zero.
+pub fn size<'de, D: Deserializer<'de>>(deserializer: D) -> Result<(f32, f32), D::Error> {
+    let size @ (width, height) = <(f32, f32)>::deserialize(deserializer)?;
+    for length in [width, height] {
+        within(length, |length| length > 0.0, "above 0")?;
+    }
+    Ok(size)
+}
+
+/// Read the species, at least one of which has agents.
+fn populated<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<Species>, D::Error> {
+    let species = Vec::<Species>::deserialize(deserializer)?;
+    if species.iter().all(|species| species.count == 0) {
+        return Err(D::Error::custom(
+            "expected at least one species with agents",
+        ));
+    }
+    Ok(species)
+}
+
+fn in_unit(value: f32) -> bool {
+    (0.0..=1.0).contains(&value)
+}
+
+fn within<E: Error>(value: f32, valid: impl Fn(f32) -> bool, expected: &str) -> Result<f32, E> {
+    if valid(value) {
+        Ok(value)
+    } else {
+        Err(E::custom(format!(
+            "expected a number {expected}, got {value}"
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tuning::Tunable;
+
+    const SPECIES: &str = r#"
+[[species]]
+color = [1.0, 1.0, 1.0, 1.0]
+count = 10
+speed = 1.5
+size = [15.0, 15.0]
+
+[[species]]
+color = [1.0, 0.5, 0.5, 1.0]
+count = 0
+speed = 2.0
+size = [12.0, 12.0]
+
+[[species]]
+color = [0.5, 0.5, 1.0, 1.0]
+count = 0
+speed = 1.0
+size = [20.0, 20.0]
+"#;
+
+    fn parse(text: &str) -> Result<Config, String> {
+        Config::parse("birds.toml", text)
+    }
+
+    #[test]
+    fn negative_radius_is_reported_at_its_line() {
+        let text = "seed = 1\n\n[agent]\ndetection_radius = -80.0\n";
+        assert_eq!(
+            parse(text).unwrap_err(),
+            "Invalid configuration birds.toml at line 4, `detection_radius = -80.0`: expected a number above 0, got -80"
+        );
+    }
+
+    #[test]
+    fn field_of_view_beyond_a_full_turn_is_reported_at_its_line() {
+        let text = "[agent]\nfield_of_view = 7.0\n";
+        assert_eq!(
+            parse(text).unwrap_err(),
+            "Invalid configuration birds.toml at line 2, `field_of_view = 7.0`: expected a number above 0 and at most 2π, got 7"
+        );
+    }
+
+    #[test]
+    fn zero_agents_are_reported_at_the_species() {
+        let text = SPECIES.replace("count = 10", "count = 0");
+        assert_eq!(
+            parse(&text).unwrap_err(),
+            "Invalid configuration birds.toml at line 2, `[[species]]`: expected at least one species with agents"
+        );
+    }
+
+    #[test]
+    fn factor_above_one_is_reported_at_its_line() {
+        let text = format!("{SPECIES}\n[[interaction]]\nspecies = 0\nother = 1\nalignment = 1.5\n");
+        assert_eq!(
+            parse(&text).unwrap_err(),
+            "Invalid configuration birds.toml at line 23, `alignment = 1.5`: expected a number from 0 to 1, got 1.5"
+        );
+    }
+
+    #[test]
+    fn parameters_are_read_by_their_key() {
+        let text = "[randomize]\nmin_distance = [20.0, 30.0]\n\n[[keyframe]]\ntime = 5.0\nmax_force = 0.1\n";
+        let config = parse(text).unwrap();
+        assert_eq!(config.randomize.get(Tunable::MinDistance), (20.0, 30.0));
+        assert_eq!(
+            config.randomize.get(Tunable::Speed),
+            Bounds::default().get(Tunable::Speed)
+        );
+        assert_eq!(
+            Tunable::MaxForce.value(&config.timeline.at(&config, 0.0)),
+            0.1
+        );
+    }
+
+    #[test]
+    fn unknown_parameters_are_reported_at_their_line() {
+        let text = "[randomize]\nmin_distance = [20.0, 30.0]\nmax_speed = [1.0, 2.0]\n";
+        let error = parse(text).unwrap_err();
+        assert!(
+            error.starts_with("Invalid configuration birds.toml at line 3, `max_speed = [1.0, 2.0]`: unknown field `max_speed`"),
+            "{error}"
+        );
+
+        let text = "[[keyframe]]\ntime = -1.0\n";
+        assert_eq!(
+            parse(text).unwrap_err(),
+            "Invalid configuration birds.toml at line 2, `time = -1.0`: expected a number of at least 0, got -1"
+        );
+    }
+
+    #[test]
+    fn gradient_colors_are_reported_at_their_line() {
+        let text = "seed = 1\ngradient = [[0.2, 0.3, 1.0, 1.0], [1.0, 0.2, 2.0, 1.0]]\n";
+        assert_eq!(
+            parse(text).unwrap_err(),
+            "Invalid configuration birds.toml at line 2, `gradient = [[0.2, 0.3, 1.0, 1.0], [1.0, 0.2, 2.0, 1.0]]`: expected a number from 0 to 1, got 2"
+        );
+    }
+
+    #[test]
+    fn interactions_are_checked_against_the_species_configured() {
+        let two = &SPECIES[..SPECIES.rfind("[[species]]").unwrap()];
+        let text = format!("{two}[[interaction]]\nspecies = 0\nother = 2\nalignment = 0.5\n");
+        assert_eq!(
+            parse(&text).unwrap_err(),
+            "Invalid configuration birds.toml at line 16, `other = 2`: interaction with species 2, but only species 0 to 1 are configured"
+        );
+    }
+
+    #[test]
+    fn any_number_of_species_is_read() {
+        let text = format!(
+            "{SPECIES}{}[[interaction]]\nspecies = 3\nother = 0\navoidance = 1.0\n",
+            &SPECIES[SPECIES.rfind("[[species]]").unwrap()..]
+        );
+        let config = parse(&text).unwrap();
+        assert_eq!(config.species.len(), 4);
+        assert_eq!(config.interactions.between(3, 0).avoidance, 1.0);
+        assert_eq!(config.interactions.between(0, 3).avoidance, 0.0);
+        assert_eq!(config.interactions.between(3, 3).cohesion, 1.0);
+        assert_eq!(config.interactions.between(1, 2).cohesion, 0.0);
+    }
+
+    #[test]
+    fn valid_settings_are_read() {
+        let config = parse(SPECIES).unwrap();
+        assert_eq!(config.species[0].count, 10);
+    }
+}
@@ -0,0 +1,125 @@
+use crate::bloom::Bloom;
+use crate::config::Config;
+use crate::tuning::Tunable;
+use nannou_egui::egui::{self, CollapsingHeader, Slider, Ui};
+
+/// Side panel of sliders tuning the flock while it flies.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Controls {
+    pub visible: bool,
+}
+
+/// What was changed in the panel over a frame.
+#[derive(Debug, Clone, Default)]
+pub struct Changes {
+    /// Configuration edited with the sliders, to use from now on
+    pub config: Option<Config>,
+    pub toggle_pause: bool,
+    pub reset: bool,
+}
+
+impl Controls {
+    const WIDTH: f32 = 260.0;
+
+    pub fn show(&self, ctx: &egui::Context, paused: bool, bloom: &mut Bloom) -> Changes {
+        let mut changes = Changes::default();
+        let mut config = (*Config::get()).clone();
+        let mut edited = false;
+
+        egui::SidePanel::left("controls")
+            .resizable(false)
+            .exact_width(Self::WIDTH)
+            .show_animated(ctx, self.visible, |ui| {
+                ui.horizontal(|ui| {
+                    let label = if paused { "Resume" } else { "Pause" };
+                    changes.toggle_pause = ui.button(label).clicked();
+                    changes.reset = ui.button("Reset").clicked();
+                });
+
+                let agent = &mut config.agent;
+                CollapsingHeader::new("Rules")
+                    .default_open(true)
+                    .show(ui, |ui| {
+                        edited |= slider(
+                            ui,
+                            &mut agent.separation_weight,
+                            Tunable::SeparationWeight,
+                            "separation",
+                        );
+                        edited |= slider(
+                            ui,
+                            &mut agent.alignment_weight,
+                            Tunable::AlignmentWeight,
+                            "alignment",
+                        );
+                        edited |= slider(
+                            ui,
+                            &mut agent.cohesion_weight,
+                            Tunable::CohesionWeight,
+                            "cohesion",
+                        );
+                    });
+                CollapsingHeader::new("Radii")
+                    .default_open(true)
+                    .show(ui, |ui| {
+                        edited |= slider(
+                            ui,
+                            &mut agent.detection_radius,
+                            Tunable::DetectionRadius,
+                            "detection",
+                        );
+                        edited |= slider(
+                            ui,
+                            &mut agent.min_distance,
+                            Tunable::MinDistance,
+                            "separation",
+                        );
+                        edited |=
+                            slider(ui, &mut agent.panic_radius, Tunable::PanicRadius, "panic");
+                    });
+                CollapsingHeader::new("Steering").show(ui, |ui| {
+                    edited |= slider(ui, &mut agent.max_force, Tunable::MaxForce, "max force");
+                    edited |= slider(
+                        ui,
+                        &mut agent.max_turn_rate,
+                        Tunable::MaxTurnRate,
+                        "max turn rate",
+                    );
+                });
+
+                for (i, species) in config.species.iter_mut().enumerate() {
+                    CollapsingHeader::new(format!("Species {}", i + 1)).show(ui, |ui| {
+                        edited |= ui
+                            .add(Slider::new(&mut species.count, 0..=2000).text("agents"))
+                            .changed();
+                        edited |= slider(ui, &mut species.speed, Tunable::Speed, "speed");
+
+                        let (r, g, b, a) = species.color;
+                        let mut color = [r, g, b, a];
+                        ui.horizontal(|ui| {
+                            edited |= ui.color_edit_button_rgba_unmultiplied(&mut color).changed();
+                            ui.label("color");
+                        });
+                        species.color = color.into();
+                    });
+                }
+
+                CollapsingHeader::new("Bloom").show(ui, |ui| {
+                    ui.checkbox(&mut bloom.enabled, "glow");
+                    ui.add(Slider::new(&mut bloom.threshold, 0.0..=1.0).text("threshold"));
+                    ui.add(Slider::new(&mut bloom.intensity, 0.0..=5.0).text("intensity"));
+                });
+            });
+
+        if edited {
+            changes.config = Some(config);
+        }
+        changes
+    }
+}
+
+/// Slider over the range of a tunable parameter, returning whether it moved.
+fn slider(ui: &mut Ui, value: &mut f32, tunable: Tunable, text: &str) -> bool {
+    let (min, max) = tunable.range();
+    ui.add(Slider::new(value, min..=max).text(text)).changed()
+}
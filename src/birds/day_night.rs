@@ -0,0 +1,68 @@
+use crate::args::Args;
+use nannou::prelude::*;
+
+/// Simulated time of day, darkening the sky and calming the flock at night.
+#[derive(Debug, Clone, Copy)]
+pub struct DayNight {
+    /// Time of day as a fraction of a day, starting at noon
+    pub phase: f32,
+    /// Length of a day in seconds
+    pub period: f32,
+}
+
+impl DayNight {
+    const DAY_COLOR: (f32, f32, f32, f32) = (0.25, 0.4, 0.6, 1.0);
+    const NIGHT_COLOR: (f32, f32, f32, f32) = (0.0, 0.0, 0.02, 1.0);
+    pub const TEXT_COLOR: (f32, f32, f32, f32) = (0.8, 0.8, 0.8, 1.0);
+
+    const DEFAULT_PERIOD: f32 = 120.0;
+
+    /// Skipping forward or backward moves the time of day by an hour
+    pub const SKIP: f32 = 1.0 / 24.0;
+
+    /// Cycle lasting `--day-length <seconds>` if given on the command line.
+    pub fn from_args() -> Self {
+        let period = Args::get()
+            .day_length
+            .filter(|length| *length > 0.0)
+            .unwrap_or(Self::DEFAULT_PERIOD);
+        DayNight { phase: 0.0, period }
+    }
+
+    /// Move the time of day forward by a number of seconds.
+    pub fn advance(&mut self, seconds: f32) {
+        self.skip(seconds / self.period);
+    }
+
+    /// Move the time of day by a fraction of a day, backward when negative.
+    pub fn skip(&mut self, fraction: f32) {
+        self.phase = (self.phase + fraction).rem_euclid(1.0);
+    }
+
+    /// How dark it is, from 0 at noon to 1 at midnight.
+    pub fn darkness(&self) -> f32 {
+        (1.0 - (self.phase * TAU).cos()) * 0.5
+    }
+
+    pub fn background(&self) -> Rgba {
+        let day = Vec4::from(Self::DAY_COLOR);
+        let night = Vec4::from(Self::NIGHT_COLOR);
+        let color = day.lerp(night, self.darkness());
+        rgba(color.x, color.y, color.z, color.w)
+    }
+
+    pub fn display(&self, draw: &Draw, win_rect: Rect, color: Rgba) {
+        // Noon is halfway through the 24 hours
+        let minutes = ((self.phase + 0.5).fract() * 24.0 * 60.0) as u32;
+        let text = format!("{:02}:{:02}", minutes / 60, minutes % 60);
+
+        let area = win_rect.pad(20.0);
+        draw.text(&text)
+            .xy(area.xy())
+            .wh(area.wh())
+            .right_justify()
+            .align_text_top()
+            .font_size(14)
+            .color(color);
+    }
+}
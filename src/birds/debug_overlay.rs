@@ -0,0 +1,86 @@
+use crate::agent::Agent;
+use crate::config::Config;
+use nannou::prelude::*;
+
+/// Radii and flocking forces drawn over the agents, to see why they move as they do.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DebugOverlay {
+    Off,
+    All,
+    /// Only the agent closest to the cursor
+    Hovered,
+}
+
+impl DebugOverlay {
+    const DETECTION_COLOR: (f32, f32, f32, f32) = (1.0, 1.0, 1.0, 0.15);
+    const SEPARATION_RADIUS_COLOR: (f32, f32, f32, f32) = (1.0, 0.4, 0.3, 0.3);
+    const SEPARATION_COLOR: (f32, f32, f32, f32) = (1.0, 0.3, 0.3, 0.9);
+    const ALIGNMENT_COLOR: (f32, f32, f32, f32) = (0.3, 1.0, 0.3, 0.9);
+    const COHESION_COLOR: (f32, f32, f32, f32) = (0.3, 0.5, 1.0, 0.9);
+
+    /// Length of the lines per unit of force, as forces are a fraction of a pixel
+    const FORCE_SCALE: f32 = 400.0;
+    /// Distance from the cursor within which an agent is hovered
+    const HOVER_RADIUS: f32 = 40.0;
+
+    pub fn next(self) -> Self {
+        match self {
+            DebugOverlay::Off => DebugOverlay::All,
+            DebugOverlay::All => DebugOverlay::Hovered,
+            DebugOverlay::Hovered => DebugOverlay::Off,
+        }
+    }
+
+    /// Draw the detection radius and separation distance of the agents, and the
+    /// separation, alignment and cohesion forces they last felt.
+    pub fn display(self, draw: &Draw, agents: &[Agent], mouse: Vec2, config: &Config) {
+        let min_distance = config.agent.min_distance;
+        match self {
+            DebugOverlay::Off => {}
+            DebugOverlay::All => agents
+                .iter()
+                .for_each(|agent| Self::display_agent(draw, agent, min_distance)),
+            DebugOverlay::Hovered => {
+                let hovered = agents
+                    .iter()
+                    .map(|agent| (agent, agent.position.distance_squared(mouse)))
+                    .filter(|(_, distance)| *distance < Self::HOVER_RADIUS * Self::HOVER_RADIUS)
+                    .min_by(|(_, a), (_, b)| a.total_cmp(b));
+                if let Some((agent, _)) = hovered {
+                    Self::display_agent(draw, agent, min_distance);
+                }
+            }
+        }
+    }
+
+    fn display_agent(draw: &Draw, agent: &Agent, min_distance: f32) {
+        let radii = [
+            (agent.parameters.detection_radius, Self::DETECTION_COLOR),
+            (min_distance, Self::SEPARATION_RADIUS_COLOR),
+        ];
+        for (radius, color) in radii {
+            draw.ellipse()
+                .xy(agent.position)
+                .radius(radius)
+                .no_fill()
+                .stroke_weight(1.0)
+                .stroke(Rgba::from(color));
+        }
+
+        let forces = agent.rule_forces;
+        let lines = [
+            (forces.separation, Self::SEPARATION_COLOR),
+            (forces.alignment, Self::ALIGNMENT_COLOR),
+            (forces.cohesion, Self::COHESION_COLOR),
+        ];
+        for (force, color) in lines {
+            if force != Vec2::ZERO {
+                draw.line()
+                    .start(agent.position)
+                    .end(agent.position + force * Self::FORCE_SCALE)
+                    .weight(1.5)
+                    .color(Rgba::from(color));
+            }
+        }
+    }
+}
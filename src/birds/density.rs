@@ -0,0 +1,96 @@
+use crate::agent::Agent;
+use crate::agents::Agents;
+use crate::args::Args;
+use crate::config::Config;
+use crate::species::Species;
+use nannou::prelude::*;
+use nannou::rand::Rng;
+
+/// Controller spawning and culling agents to keep a number of agents per area
+/// as the window changes size.
+#[derive(Debug, Clone, Copy)]
+pub struct Density {
+    /// Agents per million square pixels
+    pub target: f32,
+}
+
+impl Density {
+    const STEP: f32 = 1.1;
+    const RANGE: (f32, f32) = (5.0, 1000.0);
+
+    /// Agents added or removed every frame at most, so the population changes smoothly
+    const RATE: usize = 2;
+
+    /// Density given by `--density <agents>` in agents per million square pixels,
+    /// or the density of the initial population in the window by default.
+    pub fn from_args(win_rect: Rect, config: &Config) -> Self {
+        let initial = Self::population(config) as f32 / Self::area(win_rect);
+        let target = Args::get().density.unwrap_or(initial);
+        Density {
+            target: target.clamp(Self::RANGE.0, Self::RANGE.1),
+        }
+    }
+
+    /// Total number of agents every species starts with.
+    fn population(config: &Config) -> usize {
+        config.species.iter().map(|species| species.count).sum()
+    }
+
+    /// Area of a rectangle in millions of square pixels.
+    fn area(win_rect: Rect) -> f32 {
+        win_rect.w() * win_rect.h() / 1e6
+    }
+
+    /// Raise the target density by a step, or lower it when the step is negative.
+    pub fn adjust(&mut self, steps: f32) {
+        self.target = (self.target * Self::STEP.powf(steps)).clamp(Self::RANGE.0, Self::RANGE.1);
+    }
+
+    /// Target the density of a number of agents in the window.
+    pub fn hold(&mut self, agents: usize, win_rect: Rect) {
+        self.target = agents as f32 / Self::area(win_rect);
+    }
+
+    /// Spawn agents flying in from the edges or cull some to approach the target
+    /// density, keeping the species in their initial proportions.
+    pub fn update(&self, agents: &mut Agents, win_rect: Rect, config: &Config, rng: &mut impl Rng) {
+        let target = (self.target * Self::area(win_rect)).round() as usize;
+
+        for _ in 0..Self::RATE {
+            // Difference between the wanted and actual number of agents of each species
+            let counts =
+                config
+                    .species
+                    .iter()
+                    .enumerate()
+                    .map(|(species, Species { count, .. })| {
+                        let wanted =
+                            target as f32 * *count as f32 / Self::population(config) as f32;
+                        let actual = agents.species.iter().filter(|&&s| s == species).count();
+                        (species, wanted - actual as f32)
+                    });
+
+            if agents.len() < target {
+                // The species missing the most agents gets a newcomer
+                let species = counts.max_by(|(_, a), (_, b)| a.total_cmp(b));
+                if let Some((species, _)) = species {
+                    agents.push(Agent::new_at_edge(win_rect, species, config, rng));
+                }
+            } else if agents.len() > target {
+                // A random follower of the species with the most extra agents is culled
+                let species = counts.min_by(|(_, a), (_, b)| a.total_cmp(b));
+                let candidates: Vec<usize> = agents
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, agent)| Some(agent.species) == species.map(|(s, _)| s))
+                    .filter(|(_, agent)| !agent.leader)
+                    .map(|(i, _)| i)
+                    .collect();
+                if candidates.is_empty() {
+                    break;
+                }
+                agents.swap_remove(candidates[rng.gen_range(0..candidates.len())]);
+            }
+        }
+    }
+}
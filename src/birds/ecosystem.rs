@@ -0,0 +1,100 @@
+use crate::agent::Agent;
+use crate::agents::Agents;
+use crate::config::Config;
+use crate::predator::Predator;
+use nannou::prelude::*;
+use nannou::rand::Rng;
+
+/// Energy budget of prey and predators, deciding who is born and who dies.
+pub struct Ecosystem;
+
+impl Ecosystem {
+    // Prey feed on their surroundings and split their energy with their offspring
+    const PREY_ENERGY_GAIN: f32 = 1.0 / 900.0;
+    const PREY_REPRODUCTION_ENERGY: f32 = 1.0;
+    const MAX_AGENTS: usize = 1000;
+
+    // Predators starve unless they catch prey
+    const PREDATOR_ENERGY_LOSS: f32 = 1.0 / 1200.0;
+    const PREDATOR_REPRODUCTION_ENERGY: f32 = 2.0;
+    const MEAL_ENERGY: f32 = 0.35;
+    const CATCH_RADIUS: f32 = 10.0;
+    const MAX_PREDATORS: usize = 20;
+
+    /// Offspring are born this close to their parent
+    const BIRTH_RADIUS: f32 = 10.0;
+
+    /// Remove the prey caught by predators, feeding the predators.
+    /// Returns the prey that were caught.
+    pub fn hunt(agents: &mut Agents, predators: &mut [Predator]) -> Vec<Agent> {
+        let mut caught = vec![false; agents.len()];
+        for predator in predators.iter_mut() {
+            let prey = agents.positions.iter().enumerate().find(|(i, position)| {
+                !caught[*i]
+                    && position.distance_squared(predator.position)
+                        < Self::CATCH_RADIUS * Self::CATCH_RADIUS
+            });
+            if let Some((i, _)) = prey {
+                caught[i] = true;
+                predator.energy += Self::MEAL_ENERGY;
+            }
+        }
+
+        let mut eaten = Vec::new();
+        let mut caught = caught.into_iter();
+        agents.retain_mut(|agent| {
+            let alive = !caught.next().unwrap();
+            if !alive {
+                eaten.push(*agent);
+            }
+            alive
+        });
+        eaten
+    }
+
+    /// Grow the population according to the energy of every agent.
+    /// Predators must have hunted beforehand to survive.
+    pub fn update(
+        agents: &mut Agents,
+        predators: &mut Vec<Predator>,
+        config: &Config,
+        rng: &mut impl Rng,
+    ) {
+        // Prey gain energy over time and reproduce once full
+        let mut offspring = Vec::new();
+        agents.for_each_mut(|agent| {
+            agent.energy += Self::PREY_ENERGY_GAIN;
+            if agent.energy >= Self::PREY_REPRODUCTION_ENERGY {
+                agent.energy *= 0.5;
+                let mut child = Agent::spawn(near(agent.position, rng), agent.species, config, rng);
+                child.energy = agent.energy;
+                offspring.push(child);
+            }
+        });
+        offspring.truncate(Self::MAX_AGENTS.saturating_sub(agents.len()));
+        agents.extend(offspring);
+
+        // Predators lose energy over time, reproduce when well fed and die when starved
+        let mut offspring = Vec::new();
+        for predator in predators.iter_mut() {
+            predator.energy -= Self::PREDATOR_ENERGY_LOSS;
+            if predator.energy >= Self::PREDATOR_REPRODUCTION_ENERGY {
+                predator.energy *= 0.5;
+                offspring.push(Predator::spawn(
+                    near(predator.position, rng),
+                    predator.energy,
+                    rng,
+                ));
+            }
+        }
+        predators.retain(|predator| predator.energy > 0.0);
+        offspring.truncate(Self::MAX_PREDATORS.saturating_sub(predators.len()));
+        predators.extend(offspring);
+    }
+}
+
+/// Random position close to a parent.
+fn near(position: Vec2, rng: &mut impl Rng) -> Vec2 {
+    let angle = rng.gen_range(0.0..TAU);
+    position + vec2(angle.cos(), angle.sin()) * rng.gen_range(0.0..Ecosystem::BIRTH_RADIUS)
+}
@@ -0,0 +1,136 @@
+use crate::args::Args;
+use nannou::prelude::*;
+use std::fmt;
+use std::net::UdpSocket;
+
+/// Change in the structure of the flocks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FlockEvent {
+    /// A flock broke apart into several flocks
+    Split { flock: u32, parts: usize },
+    /// Several flocks joined into one
+    Merge { flock: u32, parts: usize },
+}
+
+impl FlockEvent {
+    /// OSC address the event is sent to.
+    fn address(&self) -> &'static str {
+        match self {
+            FlockEvent::Split { .. } => "/flock/split",
+            FlockEvent::Merge { .. } => "/flock/merge",
+        }
+    }
+}
+
+impl fmt::Display for FlockEvent {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FlockEvent::Split { flock, parts } => write!(f, "flock {flock} split into {parts}"),
+            FlockEvent::Merge { flock, parts } => {
+                write!(f, "{parts} flocks merged into flock {flock}")
+            }
+        }
+    }
+}
+
+/// Function called on every flock event.
+type Callback = Box<dyn FnMut(&FlockEvent) + Send>;
+
+/// Callbacks and OSC messages triggered by flock events.
+pub struct Hooks {
+    callbacks: Vec<Callback>,
+    osc: Option<(UdpSocket, String)>,
+}
+
+impl Hooks {
+    /// Hooks sending OSC messages to `--osc <host:port>` if given.
+    pub fn from_args() -> Self {
+        let osc = Args::get().osc.clone().and_then(|target| {
+            UdpSocket::bind("0.0.0.0:0")
+                .map(|socket| (socket, target))
+                .map_err(|error| eprintln!("Could not open OSC socket: {error}"))
+                .ok()
+        });
+        Hooks {
+            callbacks: Vec::new(),
+            osc,
+        }
+    }
+
+    /// Register a callback called on every event.
+    pub fn on(&mut self, callback: impl FnMut(&FlockEvent) + Send + 'static) {
+        self.callbacks.push(Box::new(callback));
+    }
+
+    pub fn dispatch(&mut self, event: &FlockEvent) {
+        for callback in &mut self.callbacks {
+            callback(event);
+        }
+
+        if let Some((socket, target)) = &self.osc {
+            let (flock, parts) = match *event {
+                FlockEvent::Split { flock, parts } | FlockEvent::Merge { flock, parts } => {
+                    (flock, parts)
+                }
+            };
+            let message = osc_message(event.address(), &[flock as i32, parts as i32]);
+            if let Err(error) = socket.send_to(&message, target) {
+                eprintln!("Could not send OSC message to {target}: {error}");
+            }
+        }
+    }
+}
+
+/// Encode an OSC message with integer arguments.
+fn osc_message(address: &str, arguments: &[i32]) -> Vec<u8> {
+    let mut message = Vec::new();
+    push_osc_string(&mut message, address);
+    push_osc_string(&mut message, &format!(",{}", "i".repeat(arguments.len())));
+    for argument in arguments {
+        message.extend_from_slice(&argument.to_be_bytes());
+    }
+    message
+}
+
+/// Append a null-terminated string padded to a multiple of four bytes.
+fn push_osc_string(message: &mut Vec<u8>, string: &str) {
+    message.extend_from_slice(string.as_bytes());
+    let padding = 4 - string.len() % 4;
+    message.resize(message.len() + padding, 0);
+}
+
+/// Brief flash of the whole window signaling an event.
+#[derive(Debug, Clone, Copy)]
+pub struct Flash {
+    color: (f32, f32, f32, f32),
+    frame: u32,
+}
+
+impl Flash {
+    const SPLIT_COLOR: (f32, f32, f32, f32) = (1.0, 0.5, 0.2, 0.15);
+    const MERGE_COLOR: (f32, f32, f32, f32) = (0.2, 0.8, 1.0, 0.15);
+    const DURATION: u32 = 20;
+
+    pub fn new(event: &FlockEvent) -> Self {
+        let color = match event {
+            FlockEvent::Split { .. } => Self::SPLIT_COLOR,
+            FlockEvent::Merge { .. } => Self::MERGE_COLOR,
+        };
+        Flash { color, frame: 0 }
+    }
+
+    /// Fade the flash, returning whether it is still visible.
+    pub fn update(&mut self) -> bool {
+        self.frame += 1;
+        self.frame < Self::DURATION
+    }
+
+    pub fn display(&self, draw: &Draw, win_rect: Rect) {
+        let (r, g, b, a) = self.color;
+        let fade = 1.0 - self.frame as f32 / Self::DURATION as f32;
+        draw.rect()
+            .xy(win_rect.xy())
+            .wh(win_rect.wh())
+            .color(rgba(r, g, b, a * fade));
+    }
+}
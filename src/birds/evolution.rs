@@ -0,0 +1,139 @@
+use crate::agent::Agent;
+use crate::agents::Agents;
+use crate::config::Config;
+use crate::parameters::Parameters;
+use crate::species::Species;
+use nannou::prelude::*;
+use nannou::rand::Rng;
+
+/// Genome of an agent and the number of frames it survived.
+#[derive(Debug, Clone, Copy)]
+struct Score {
+    species: usize,
+    genome: Parameters,
+    fitness: u32,
+}
+
+/// Breeds generations of agents, selecting the genomes that best survive predators.
+pub struct Evolution {
+    generation: u32,
+    frame: u32,
+    population: usize,
+    fallen: Vec<Score>,
+    best: Option<Score>,
+}
+
+impl Evolution {
+    pub const COLOR: (f32, f32, f32, f32) = (0.8, 0.8, 0.8, 1.0);
+
+    // A generation ends after a while or once most of the population was caught
+    const GENERATION_DURATION: u32 = 1800;
+    const EXTINCTION_THRESHOLD: f32 = 0.25;
+
+    // Parents are the fittest of a few randomly picked candidates
+    const TOURNAMENT_SIZE: usize = 4;
+
+    pub fn new(population: usize) -> Self {
+        Evolution {
+            generation: 0,
+            frame: 0,
+            population,
+            fallen: Vec::new(),
+            best: None,
+        }
+    }
+
+    /// Score the agents caught this frame, and breed the next generation once
+    /// the current one is over.
+    pub fn update(
+        &mut self,
+        agents: &mut Agents,
+        caught: &[Agent],
+        win_rect: Rect,
+        config: &Config,
+        rng: &mut impl Rng,
+    ) {
+        self.frame += 1;
+        self.fallen.extend(caught.iter().map(|agent| Score {
+            species: agent.species,
+            genome: agent.parameters,
+            fitness: self.frame,
+        }));
+
+        let extinct = (agents.len() as f32) < self.population as f32 * Self::EXTINCTION_THRESHOLD;
+        if self.frame < Self::GENERATION_DURATION && !extinct {
+            return;
+        }
+
+        // Survivors lasted the whole generation
+        let mut scores = std::mem::take(&mut self.fallen);
+        scores.extend(agents.iter().map(|agent| Score {
+            species: agent.species,
+            genome: agent.parameters,
+            fitness: self.frame,
+        }));
+        self.best = scores.iter().max_by_key(|score| score.fitness).copied();
+
+        // Breed a new population of every species from the fittest genomes
+        agents.clear();
+        for (species, Species { count, .. }) in config.species.iter().enumerate() {
+            let pool: Vec<&Score> = scores
+                .iter()
+                .filter(|score| score.species == species)
+                .collect();
+            for _ in 0..*count {
+                let mut agent = Agent::new(win_rect, species, config, rng);
+                if !pool.is_empty() {
+                    let (mother, father) = (select(&pool, rng), select(&pool, rng));
+                    let mut genome = mother.crossover(&father, rng);
+                    genome.mutate(rng);
+                    agent.velocity = agent.velocity.normalize() * genome.speed;
+                    agent.parameters = genome;
+                }
+                agents.push(agent);
+            }
+        }
+
+        self.population = agents.len();
+        self.generation += 1;
+        self.frame = 0;
+    }
+
+    pub fn display(&self, draw: &Draw, win_rect: Rect, color: Rgba) {
+        let mut text = format!(
+            "generation {} ({:.0}%)",
+            self.generation,
+            self.frame as f32 / Self::GENERATION_DURATION as f32 * 100.0
+        );
+        if let Some(best) = &self.best {
+            let genome = &best.genome;
+            text += &format!(
+                "\nbest genome survived {} frames\nspeed {:.2}  radius {:.1}\nseparation {:.2}  alignment {:.2}  cohesion {:.2}",
+                best.fitness,
+                genome.speed,
+                genome.detection_radius,
+                genome.separation_weight,
+                genome.alignment_weight,
+                genome.cohesion_weight,
+            );
+        }
+
+        let area = win_rect.pad(20.0);
+        draw.text(&text)
+            .xy(area.xy())
+            .wh(area.wh())
+            .left_justify()
+            .align_text_top()
+            .font_size(14)
+            .color(color);
+    }
+}
+
+/// Tournament selection of a parent genome.
+fn select(pool: &[&Score], rng: &mut impl Rng) -> Parameters {
+    (0..Evolution::TOURNAMENT_SIZE)
+        .map(|_| pool[rng.gen_range(0..pool.len())])
+        .max_by_key(|score| score.fitness)
+        .unwrap()
+        .genome
+}
@@ -0,0 +1,91 @@
+use nannou::prelude::*;
+use nannou::rand::Rng;
+
+/// Energy budget of an agent alternating between flapping and gliding. Flapping
+/// speeds the agent up and tires it, gliding slows it down while it recovers.
+#[derive(Debug, Clone, Copy)]
+pub struct Flight {
+    /// Energy left for flapping, from 0 when exhausted to 1 when rested
+    pub stamina: f32,
+    pub flapping: bool,
+    /// Stamina at which the agent starts flapping again while gliding
+    rested: f32,
+    /// Current factor of the cruising speed, easing between the phases
+    speed: f32,
+    /// Angle in the wingbeat cycle
+    wingbeat: f32,
+}
+
+impl Flight {
+    const FLAP_COST: f32 = 1.0 / 120.0;
+    const GLIDE_RECOVERY: f32 = 1.0 / 180.0;
+
+    /// Range of the stamina at which gliding agents start flapping again
+    const RESTED: (f32, f32) = (0.6, 1.0);
+
+    const FLAP_SPEED: f32 = 1.2;
+    const GLIDE_SPEED: f32 = 0.85;
+    const EASING: f32 = 0.05;
+
+    // Wings beat a few times a second, stretching the body a little
+    const WINGBEAT_RATE: f32 = 0.5;
+    const WINGBEAT_STRETCH: f32 = 0.15;
+
+    /// Agent in a random phase, so the flock doesn't start flapping in unison.
+    pub fn random(rng: &mut impl Rng) -> Self {
+        Flight {
+            stamina: rng.gen(),
+            flapping: rng.gen(),
+            rested: rng.gen_range(Self::RESTED.0..Self::RESTED.1),
+            speed: 1.0,
+            wingbeat: rng.gen_range(0.0..TAU),
+        }
+    }
+
+    /// Spend or recover stamina, switching phase when exhausted or rested.
+    /// Returns the factor of the cruising speed to fly at.
+    pub fn update(&mut self, rng: &mut impl Rng) -> f32 {
+        if self.flapping {
+            self.stamina -= Self::FLAP_COST;
+            self.wingbeat = (self.wingbeat + Self::WINGBEAT_RATE) % TAU;
+            if self.stamina <= 0.0 {
+                self.stamina = 0.0;
+                self.flapping = false;
+                self.rested = rng.gen_range(Self::RESTED.0..Self::RESTED.1);
+            }
+        } else {
+            self.stamina += Self::GLIDE_RECOVERY;
+            if self.stamina >= self.rested {
+                self.flapping = true;
+            }
+        }
+
+        let target = if self.flapping {
+            Self::FLAP_SPEED
+        } else {
+            Self::GLIDE_SPEED
+        };
+        self.speed += (target - self.speed) * Self::EASING;
+        self.speed
+    }
+
+    /// Factor of the width of the body, pulsing with the wingbeats and steady
+    /// with the wings spread while gliding.
+    pub fn span(&self) -> f32 {
+        if self.flapping {
+            1.0 + self.wingbeat.sin() * Self::WINGBEAT_STRETCH
+        } else {
+            1.0 + Self::WINGBEAT_STRETCH
+        }
+    }
+
+    /// Share the wings are spread by, from swept back at the bottom of a
+    /// wingbeat to spread wide at its top and while gliding.
+    pub fn spread(&self) -> f32 {
+        if self.flapping {
+            0.5 + self.wingbeat.sin() * 0.5
+        } else {
+            1.0
+        }
+    }
+}
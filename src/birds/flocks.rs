@@ -0,0 +1,134 @@
+use crate::agents::Agents;
+use crate::events::FlockEvent;
+use nannou::prelude::*;
+use std::collections::{HashMap, HashSet};
+
+/// Groups of agents connected through their detection radii.
+#[derive(Debug, Default)]
+pub struct Flocks {
+    pub count: usize,
+    /// Identifier given to the next new flock
+    next_id: u32,
+}
+
+impl Flocks {
+    pub const COLOR: (f32, f32, f32, f32) = (0.8, 0.8, 0.8, 1.0);
+
+    /// Members a flock needs to share with another for the two to split from or merge into each other
+    const MIN_SHARED_MEMBERS: usize = 5;
+
+    // Golden ratio conjugate, spreading the hues of successive flocks around the color wheel
+    const HUE_STEP: f32 = 0.618_034;
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Find the connected flocks and label every agent with its flock.
+    /// A flock keeps the label most of its members had, so its color stays stable
+    /// as agents join and leave it.
+    /// Returns the flocks that split or merged since the last update.
+    pub fn update(&mut self, agents: &mut Agents) -> Vec<FlockEvent> {
+        // Union agents that can see each other
+        let mut parents: Vec<usize> = (0..agents.len()).collect();
+        let positions = &agents.positions;
+        for i in 0..agents.len() {
+            for j in i + 1..agents.len() {
+                let radius = agents
+                    .parameters(i)
+                    .detection_radius
+                    .max(agents.parameters(j).detection_radius);
+                if positions[i].distance_squared(positions[j]) < radius * radius {
+                    let (root_i, root_j) = (find(&mut parents, i), find(&mut parents, j));
+                    parents[root_i] = root_j;
+                }
+            }
+        }
+
+        // Gather the members of every flock, largest flocks first
+        let mut members: HashMap<usize, Vec<usize>> = HashMap::new();
+        for i in 0..agents.len() {
+            members.entry(find(&mut parents, i)).or_default().push(i);
+        }
+        let mut flocks: Vec<Vec<usize>> = members.into_values().collect();
+        flocks.sort_by_key(|flock| std::cmp::Reverse(flock.len()));
+        self.count = flocks.len();
+
+        // Larger flocks pick their label first
+        let mut taken = HashSet::new();
+        let mut events = Vec::new();
+        let mut parts: HashMap<u32, usize> = HashMap::new();
+        for flock in flocks {
+            let mut votes: HashMap<u32, usize> = HashMap::new();
+            for id in flock.iter().filter_map(|&i| agents.get(i).flock) {
+                *votes.entry(id).or_default() += 1;
+            }
+
+            // Flocks sharing enough members with this one were part of it
+            let mut merged = 0;
+            for (&previous, _) in votes
+                .iter()
+                .filter(|(_, shared)| **shared >= Self::MIN_SHARED_MEMBERS)
+            {
+                merged += 1;
+                *parts.entry(previous).or_default() += 1;
+            }
+
+            let id = votes
+                .into_iter()
+                .filter(|(id, _)| !taken.contains(id))
+                .max_by_key(|&(id, votes)| (votes, std::cmp::Reverse(id)))
+                .map(|(id, _)| id)
+                .unwrap_or_else(|| {
+                    self.next_id += 1;
+                    self.next_id
+                });
+            taken.insert(id);
+            for i in flock {
+                agents.change(i, |agent| agent.flock = Some(id));
+            }
+
+            if merged > 1 {
+                events.push(FlockEvent::Merge {
+                    flock: id,
+                    parts: merged,
+                });
+            }
+        }
+
+        // Flocks whose members ended up in several flocks split
+        for (flock, parts) in parts {
+            if parts > 1 {
+                events.push(FlockEvent::Split { flock, parts });
+            }
+        }
+        events
+    }
+
+    /// Color identifying a flock.
+    pub fn color(id: u32) -> (f32, f32, f32, f32) {
+        let hue = (id as f32 * Self::HUE_STEP).fract();
+        let color: Rgba = hsla(hue, 0.7, 0.6, 1.0).into();
+        (color.red, color.green, color.blue, color.alpha)
+    }
+
+    pub fn display(&self, draw: &Draw, win_rect: Rect, color: Rgba) {
+        let area = win_rect.pad(20.0);
+        draw.text(&format!("{} flocks", self.count))
+            .xy(area.xy())
+            .wh(area.wh())
+            .left_justify()
+            .align_text_bottom()
+            .font_size(14)
+            .color(color);
+    }
+}
+
+/// Root of the set an agent belongs to, flattening the path along the way.
+fn find(parents: &mut [usize], mut i: usize) -> usize {
+    while parents[i] != i {
+        parents[i] = parents[parents[i]];
+        i = parents[i];
+    }
+    i
+}
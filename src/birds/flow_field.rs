@@ -0,0 +1,57 @@
+use nannou::prelude::*;
+use nannou::rand::Rng;
+use noise::{NoiseFn, Perlin};
+
+/// Wind blowing over the window, driven by Perlin noise.
+pub struct FlowField {
+    noise: Perlin,
+}
+
+impl FlowField {
+    pub const COLOR: (f32, f32, f32, f32) = (0.2, 0.4, 0.3, 1.0);
+
+    const STRENGTH: f32 = 0.05;
+    const SPATIAL_SCALE: f64 = 0.002;
+    const TIME_SCALE: f64 = 0.05;
+
+    const GRID_SPACING: f32 = 40.0;
+    const ARROW_LENGTH: f32 = 300.0;
+
+    pub fn new(rng: &mut impl Rng) -> Self {
+        let noise = Perlin::new(rng.gen());
+        FlowField { noise }
+    }
+
+    /// Wind at the given position and time.
+    pub fn sample(&self, position: Vec2, time: f32) -> Vec2 {
+        let angle = self.noise.get([
+            position.x as f64 * Self::SPATIAL_SCALE,
+            position.y as f64 * Self::SPATIAL_SCALE,
+            time as f64 * Self::TIME_SCALE,
+        ]) as f32
+            * TAU;
+        Vec2::new(angle.cos(), angle.sin()) * Self::STRENGTH
+    }
+
+    /// Draw the field as a grid of arrows.
+    pub fn display(&self, draw: &Draw, win_rect: Rect, time: f32, color: Rgba) {
+        let columns = (win_rect.w() / Self::GRID_SPACING) as usize;
+        let rows = (win_rect.h() / Self::GRID_SPACING) as usize;
+        for column in 0..=columns {
+            for row in 0..=rows {
+                let start = vec2(
+                    win_rect.left() + column as f32 * Self::GRID_SPACING,
+                    win_rect.bottom() + row as f32 * Self::GRID_SPACING,
+                );
+                let end = start + self.sample(start, time) * Self::ARROW_LENGTH;
+                draw.arrow()
+                    .start(start)
+                    .end(end)
+                    .head_length(4.0)
+                    .head_width(2.0)
+                    .weight(1.0)
+                    .color(color);
+            }
+        }
+    }
+}
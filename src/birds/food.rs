@@ -0,0 +1,84 @@
+use crate::agents::Agents;
+use crate::args::Args;
+use nannou::prelude::*;
+use nannou::rand::Rng;
+
+/// Food scattered over the window that hungry agents forage for.
+pub struct Food {
+    pub items: Vec<Vec2>,
+    /// Items appearing every second
+    rate: f32,
+    /// Fraction of an item waiting to appear
+    pending: f32,
+}
+
+impl Food {
+    pub const COLOR: (f32, f32, f32, f32) = (0.4, 0.9, 0.3, 1.0);
+    const SIZE: f32 = 5.0;
+
+    const INITIAL_ITEMS: usize = 20;
+    const MAX_ITEMS: usize = 50;
+    const DEFAULT_RATE: f32 = 0.5;
+
+    /// Agents eat food this close to them
+    const EAT_RADIUS: f32 = 12.0;
+
+    /// Scatter food over the window, respawning `--food-rate <per second>` items if given.
+    pub fn from_args(win_rect: Rect, rng: &mut impl Rng) -> Self {
+        let rate = Args::get()
+            .food_rate
+            .filter(|rate| *rate >= 0.0)
+            .unwrap_or(Self::DEFAULT_RATE);
+        Food {
+            items: (0..Self::INITIAL_ITEMS)
+                .map(|_| random_point(win_rect, rng))
+                .collect(),
+            rate,
+            pending: 0.0,
+        }
+    }
+
+    /// Let hungry agents eat the food they reach and grow new food.
+    pub fn update(
+        &mut self,
+        agents: &mut Agents,
+        win_rect: Rect,
+        seconds: f32,
+        rng: &mut impl Rng,
+    ) {
+        agents.for_each_mut(|agent| {
+            if !agent.is_hungry() {
+                return;
+            }
+            let eaten = self
+                .items
+                .iter()
+                .position(|item| item.distance(agent.position) < Self::EAT_RADIUS);
+            if let Some(i) = eaten {
+                self.items.swap_remove(i);
+                agent.hunger = 0.0;
+            }
+        });
+
+        self.pending += self.rate * seconds;
+        while self.pending >= 1.0 {
+            self.pending -= 1.0;
+            if self.items.len() < Self::MAX_ITEMS {
+                self.items.push(random_point(win_rect, rng));
+            }
+        }
+    }
+
+    pub fn display(&self, draw: &Draw, color: Rgba) {
+        for item in &self.items {
+            draw.ellipse().xy(*item).radius(Self::SIZE).color(color);
+        }
+    }
+}
+
+fn random_point(win_rect: Rect, rng: &mut impl Rng) -> Vec2 {
+    vec2(
+        rng.gen_range(win_rect.left()..win_rect.right()),
+        rng.gen_range(win_rect.bottom()..win_rect.top()),
+    )
+}
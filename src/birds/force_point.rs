@@ -0,0 +1,57 @@
+use nannou::prelude::*;
+
+/// Point placed by the user that pulls agents in or pushes them away.
+#[derive(Debug, Clone, Copy)]
+pub struct ForcePoint {
+    pub position: Vec2,
+    /// Positive for attractors, negative for repulsors
+    pub strength: f32,
+}
+
+impl ForcePoint {
+    pub const ATTRACTOR_COLOR: (f32, f32, f32, f32) = (0.2, 0.6, 1.0, 0.8);
+    pub const REPULSOR_COLOR: (f32, f32, f32, f32) = (1.0, 0.6, 0.2, 0.8);
+
+    const STRENGTH: f32 = 150.0;
+    const RADIUS: f32 = 12.0;
+
+    // Agents closer than this feel the force at this distance, avoiding huge forces at the center
+    const MIN_DISTANCE: f32 = 20.0;
+
+    pub fn attractor(position: Vec2) -> Self {
+        ForcePoint {
+            position,
+            strength: Self::STRENGTH,
+        }
+    }
+
+    pub fn repulsor(position: Vec2) -> Self {
+        ForcePoint {
+            position,
+            strength: -Self::STRENGTH,
+        }
+    }
+
+    /// Inverse-square force exerted on a position.
+    pub fn force(&self, position: Vec2) -> Vec2 {
+        let offset = self.position - position;
+        let distance = offset.length().max(Self::MIN_DISTANCE);
+        offset.normalize_or_zero() * self.strength / (distance * distance)
+    }
+
+    pub fn display(&self, draw: &Draw) {
+        let color = if self.strength > 0.0 {
+            Self::ATTRACTOR_COLOR
+        } else {
+            Self::REPULSOR_COLOR
+        };
+        for radius in [Self::RADIUS, Self::RADIUS * 2.0] {
+            draw.ellipse()
+                .xy(self.position)
+                .radius(radius)
+                .no_fill()
+                .stroke_weight(1.5)
+                .stroke(Rgba::from(color));
+        }
+    }
+}
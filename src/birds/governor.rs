@@ -0,0 +1,76 @@
+use crate::args::Args;
+use crate::density::Density;
+use nannou::prelude::*;
+
+/// Controller raising or lowering the density of agents to hold the frame rate at
+/// a target, so the sketch fills whatever hardware it runs on.
+#[derive(Debug, Clone, Copy)]
+pub struct Governor {
+    /// Frames per second to hold
+    pub target: f32,
+    /// Moving average of the seconds a frame takes
+    frame_time: f32,
+}
+
+impl Governor {
+    pub const COLOR: (f32, f32, f32, f32) = (0.8, 0.8, 0.8, 1.0);
+
+    /// Weight of the latest frame in the moving average of the frame time
+    const SMOOTHING: f32 = 0.05;
+
+    /// Fraction of the target the frame rate may fall short of before agents are removed
+    const TOLERANCE: f32 = 0.05;
+
+    // Density steps taken every frame, removing agents faster than adding them
+    // so a slow frame rate recovers quickly
+    const STEP_UP: f32 = 0.01;
+    const STEP_DOWN: f32 = 0.05;
+
+    /// Governor holding the frame rate given by `--target-fps <fps>`, if any.
+    pub fn from_args() -> Option<Self> {
+        let target = Args::get().target_fps?;
+        (target > 0.0).then_some(Governor {
+            target,
+            frame_time: 1.0 / target,
+        })
+    }
+
+    /// Measured frames per second.
+    pub fn fps(&self) -> f32 {
+        1.0 / self.frame_time
+    }
+
+    /// Measure the seconds the last frame took and steer the density towards
+    /// the most agents the target frame rate allows.
+    pub fn update(&mut self, seconds: f32, density: &mut Density) {
+        if seconds <= 0.0 {
+            return;
+        }
+        self.frame_time += (seconds - self.frame_time) * Self::SMOOTHING;
+
+        // The frame rate may be capped at the target by the display, so agents are
+        // added as long as it holds and only removed when it drops
+        if self.fps() < self.target * (1.0 - Self::TOLERANCE) {
+            density.adjust(-Self::STEP_DOWN);
+        } else {
+            density.adjust(Self::STEP_UP);
+        }
+    }
+
+    pub fn display(&self, draw: &Draw, win_rect: Rect, agents: usize, color: Rgba) {
+        let text = format!(
+            "{agents} agents\n{:.0} / {:.0} fps",
+            self.fps(),
+            self.target
+        );
+
+        let area = win_rect.pad(20.0);
+        draw.text(&text)
+            .xy(area.xy())
+            .wh(area.wh())
+            .left_justify()
+            .align_text_middle_y()
+            .font_size(14)
+            .color(color);
+    }
+}
@@ -0,0 +1,256 @@
+use crate::args::Args;
+use bytemuck::{Pod, Zeroable};
+use nannou::prelude::*;
+use nannou::rand::Rng;
+use nannou::wgpu::util::DeviceExt;
+
+/// Agent as stored in the storage buffers.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Pod, Zeroable)]
+struct GpuAgent {
+    position: [f32; 2],
+    velocity: [f32; 2],
+}
+
+/// Uniforms of the shaders, laid out like `Params` in the shader.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Pod, Zeroable)]
+struct Params {
+    count: u32,
+    grid_width: u32,
+    grid_height: u32,
+    cell_size: f32,
+    half_size: [f32; 2],
+    speed: f32,
+    radius: f32,
+    separation_distance: f32,
+    separation: f32,
+    alignment: f32,
+    cohesion: f32,
+    max_force: f32,
+    max_neighbors: u32,
+    agent_size: f32,
+    _padding: u32,
+}
+
+/// Flock simulated and drawn entirely on the GPU by compute shaders, the agents
+/// never leaving the storage buffers they are rendered from.
+pub struct GpuFlock {
+    count: u32,
+    params: wgpu::Buffer,
+    /// Compute bind groups reading one agent buffer and writing the other
+    compute_groups: [wgpu::BindGroup; 2],
+    /// Render bind groups reading each agent buffer
+    render_groups: [wgpu::BindGroup; 2],
+    /// Passes of a step, in order: clear, count, scan, scatter and flock
+    passes: [wgpu::ComputePipeline; 5],
+    render_pipeline: wgpu::RenderPipeline,
+    /// Agent buffer holding the latest frame
+    current: usize,
+}
+
+impl GpuFlock {
+    pub const NUM_AGENTS: u32 = 100_000;
+
+    /// Cells of the grid along each axis at most, larger windows using larger cells
+    const MAX_GRID_SIZE: u32 = 128;
+    const WORKGROUP_SIZE: u32 = 64;
+
+    const SPEED: f32 = 1.5;
+    const RADIUS: f32 = 25.0;
+    const SEPARATION_DISTANCE: f32 = 10.0;
+    const SEPARATION_WEIGHT: f32 = 1.5;
+    const ALIGNMENT_WEIGHT: f32 = 1.0;
+    const COHESION_WEIGHT: f32 = 1.0;
+    const MAX_FORCE: f32 = 0.05;
+    /// Neighbors an agent considers at most, bounding the cost of dense flocks
+    const MAX_NEIGHBORS: u32 = 48;
+    const AGENT_SIZE: f32 = 3.0;
+
+    /// Number of agents given by `--gpu-agents <count>`, or the default.
+    pub fn count_from_args() -> u32 {
+        Args::get().gpu_agents.unwrap_or(Self::NUM_AGENTS)
+    }
+
+    pub fn new(window: &Window, count: u32, rng: &mut impl Rng) -> Self {
+        let device = window.device();
+        let win_rect = window.rect();
+        let module = device.create_shader_module(wgpu::include_wgsl!("shaders/flock.wgsl"));
+
+        // Agents start at random positions flying in random directions
+        let agents: Vec<GpuAgent> = (0..count)
+            .map(|_| {
+                let angle = rng.gen_range(0.0..TAU);
+                GpuAgent {
+                    position: [
+                        rng.gen_range(win_rect.left()..win_rect.right()),
+                        rng.gen_range(win_rect.bottom()..win_rect.top()),
+                    ],
+                    velocity: [angle.cos() * Self::SPEED, angle.sin() * Self::SPEED],
+                }
+            })
+            .collect();
+        let agent_buffers = [0, 1].map(|_| {
+            device.create_buffer_init(&wgpu::BufferInitDescriptor {
+                label: Some("agents"),
+                contents: bytemuck::cast_slice(&agents),
+                usage: wgpu::BufferUsages::STORAGE,
+            })
+        });
+
+        let params = device.create_buffer_init(&wgpu::BufferInitDescriptor {
+            label: Some("params"),
+            contents: bytemuck::bytes_of(&Self::params(count, win_rect)),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+
+        // Buffers of the grid the agents are sorted into
+        let cells = (Self::MAX_GRID_SIZE * Self::MAX_GRID_SIZE) as u64;
+        let grid_buffer = |label, length: u64| {
+            device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some(label),
+                size: length.max(1) * std::mem::size_of::<u32>() as u64,
+                usage: wgpu::BufferUsages::STORAGE,
+                mapped_at_creation: false,
+            })
+        };
+        let counts = grid_buffer("counts", cells);
+        let offsets = grid_buffer("offsets", cells);
+        let cursors = grid_buffer("cursors", cells);
+        let sorted = grid_buffer("sorted", count as u64);
+
+        // Compute passes share their bindings
+        let compute = wgpu::ShaderStages::COMPUTE;
+        let compute_layout = wgpu::BindGroupLayoutBuilder::new()
+            .uniform_buffer(compute, false)
+            .storage_buffer(compute, false, true)
+            .storage_buffer(compute, false, false)
+            .storage_buffer(compute, false, false)
+            .storage_buffer(compute, false, false)
+            .storage_buffer(compute, false, false)
+            .storage_buffer(compute, false, false)
+            .build(device);
+        let compute_groups = [0, 1].map(|input| {
+            wgpu::BindGroupBuilder::new()
+                .binding(params.as_entire_binding())
+                .binding(agent_buffers[input].as_entire_binding())
+                .binding(agent_buffers[1 - input].as_entire_binding())
+                .binding(counts.as_entire_binding())
+                .binding(offsets.as_entire_binding())
+                .binding(cursors.as_entire_binding())
+                .binding(sorted.as_entire_binding())
+                .build(device, &compute_layout)
+        });
+        let compute_pipeline_layout =
+            wgpu::create_pipeline_layout(device, Some("flock"), &[&compute_layout], &[]);
+        let passes = ["clear", "count", "scan", "scatter", "flock"].map(|entry_point| {
+            device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+                label: Some(entry_point),
+                layout: Some(&compute_pipeline_layout),
+                module: &module,
+                entry_point,
+            })
+        });
+
+        // Agents are drawn straight from the buffer written last
+        let vertex = wgpu::ShaderStages::VERTEX;
+        let render_layout = wgpu::BindGroupLayoutBuilder::new()
+            .uniform_buffer(vertex, false)
+            .storage_buffer(vertex, false, true)
+            .build(device);
+        let render_groups = [0, 1].map(|current| {
+            wgpu::BindGroupBuilder::new()
+                .binding(params.as_entire_binding())
+                .binding(agent_buffers[current].as_entire_binding())
+                .build(device, &render_layout)
+        });
+        let render_pipeline_layout =
+            wgpu::create_pipeline_layout(device, Some("render"), &[&render_layout], &[]);
+        let render_pipeline =
+            wgpu::RenderPipelineBuilder::from_layout(&render_pipeline_layout, &module)
+                .vertex_entry_point("vs_main")
+                .fragment_shader(&module)
+                .fragment_entry_point("fs_main")
+                .color_format(Frame::TEXTURE_FORMAT)
+                .sample_count(window.msaa_samples())
+                .build(device);
+
+        GpuFlock {
+            count,
+            params,
+            compute_groups,
+            render_groups,
+            passes,
+            render_pipeline,
+            current: 0,
+        }
+    }
+
+    fn params(count: u32, win_rect: Rect) -> Params {
+        // Cells at least as large as the detection radius so neighbors are in adjacent cells
+        let max_size = Self::MAX_GRID_SIZE as f32;
+        let cell_size = Self::RADIUS
+            .max(win_rect.w() / max_size)
+            .max(win_rect.h() / max_size);
+        Params {
+            count,
+            grid_width: (win_rect.w() / cell_size).ceil() as u32,
+            grid_height: (win_rect.h() / cell_size).ceil() as u32,
+            cell_size,
+            half_size: [win_rect.w() * 0.5, win_rect.h() * 0.5],
+            speed: Self::SPEED,
+            radius: Self::RADIUS,
+            separation_distance: Self::SEPARATION_DISTANCE,
+            separation: Self::SEPARATION_WEIGHT,
+            alignment: Self::ALIGNMENT_WEIGHT,
+            cohesion: Self::COHESION_WEIGHT,
+            max_force: Self::MAX_FORCE,
+            max_neighbors: Self::MAX_NEIGHBORS,
+            agent_size: Self::AGENT_SIZE,
+            _padding: 0,
+        }
+    }
+
+    /// Step the flock, swapping the agent buffers.
+    pub fn update(&mut self, window: &Window) {
+        let params = Self::params(self.count, window.rect());
+        window
+            .queue()
+            .write_buffer(&self.params, 0, bytemuck::bytes_of(&params));
+
+        let cells = params.grid_width * params.grid_height;
+        let agent_groups = self.count.div_ceil(Self::WORKGROUP_SIZE);
+        let cell_groups = cells.div_ceil(Self::WORKGROUP_SIZE);
+        let workgroups = [cell_groups, agent_groups, 1, agent_groups, agent_groups];
+
+        let mut encoder = window
+            .device()
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("flock"),
+            });
+        {
+            let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+                label: Some("flock"),
+            });
+            pass.set_bind_group(0, &self.compute_groups[self.current], &[]);
+            for (pipeline, groups) in self.passes.iter().zip(workgroups) {
+                pass.set_pipeline(pipeline);
+                pass.dispatch_workgroups(groups, 1, 1);
+            }
+        }
+        window.queue().submit(Some(encoder.finish()));
+        self.current = 1 - self.current;
+    }
+
+    pub fn display(&self, frame: &Frame) {
+        let mut encoder = frame.command_encoder();
+        let mut pass = wgpu::RenderPassBuilder::new()
+            .color_attachment(frame.texture_view(), |color| {
+                color.load_op(wgpu::LoadOp::Clear(wgpu::Color::BLACK))
+            })
+            .begin(&mut encoder);
+        pass.set_pipeline(&self.render_pipeline);
+        pass.set_bind_group(0, &self.render_groups[self.current], &[]);
+        pass.draw(0..3, 0..self.count);
+    }
+}
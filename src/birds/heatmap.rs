@@ -0,0 +1,105 @@
+use crate::agents::Agents;
+use crate::colormap::Colormap;
+use nannou::prelude::*;
+
+/// Number of agents in the cells of a coarse grid over the window, drawn as a
+/// translucent heatmap behind the agents.
+#[derive(Debug, Clone)]
+pub struct Heatmap {
+    pub enabled: bool,
+    pub colormap: Colormap,
+    /// Agents in every cell, smoothed over the neighboring cells
+    cells: Vec<f32>,
+    counts: Vec<f32>,
+    columns: usize,
+    rows: usize,
+    origin: Vec2,
+}
+
+impl Heatmap {
+    const CELL_SIZE: f32 = 40.0;
+    /// Opacity of the densest cells, sparser ones fading out
+    const OPACITY: f32 = 0.5;
+
+    pub fn new() -> Self {
+        Heatmap {
+            enabled: false,
+            colormap: Colormap::Inferno,
+            cells: Vec::new(),
+            counts: Vec::new(),
+            columns: 0,
+            rows: 0,
+            origin: Vec2::ZERO,
+        }
+    }
+
+    /// Count the agents in every cell of a grid covering the window, then blur
+    /// the counts over the neighboring cells so the map isn't blocky.
+    pub fn update(&mut self, win_rect: Rect, agents: &Agents) {
+        self.columns = (win_rect.w() / Self::CELL_SIZE).ceil() as usize;
+        self.rows = (win_rect.h() / Self::CELL_SIZE).ceil() as usize;
+        self.origin = win_rect.bottom_left();
+        let (columns, rows) = (self.columns, self.rows);
+
+        self.counts.clear();
+        self.counts.resize(columns * rows, 0.0);
+        for &position in &agents.positions {
+            let cell = ((position - self.origin) / Self::CELL_SIZE).floor();
+            if cell.x >= 0.0 && cell.y >= 0.0 {
+                let (column, row) = (cell.x as usize, cell.y as usize);
+                if column < columns && row < rows {
+                    self.counts[row * columns + column] += 1.0;
+                }
+            }
+        }
+
+        // Average every cell with its neighbors, weighing itself the most
+        self.cells.clear();
+        self.cells.extend((0..columns * rows).map(|i| {
+            let (column, row) = (i % columns, i / columns);
+            let mut sum = 0.0;
+            let mut weights = 0.0;
+            for r in row.saturating_sub(1)..(row + 2).min(rows) {
+                for c in column.saturating_sub(1)..(column + 2).min(columns) {
+                    let weight = if (r, c) == (row, column) { 4.0 } else { 1.0 };
+                    sum += self.counts[r * columns + c] * weight;
+                    weights += weight;
+                }
+            }
+            sum / weights
+        }));
+    }
+
+    /// Draw the cells colored by their density relative to the densest one.
+    pub fn display(&self, draw: &Draw) {
+        let densest = self.cells.iter().copied().fold(0.0, f32::max);
+        if densest <= 0.0 {
+            return;
+        }
+        for (i, density) in self.cells.iter().enumerate() {
+            // Skip empty cells, leaving the background as it is
+            let share = density / densest;
+            if share < 0.01 {
+                continue;
+            }
+
+            let cell = vec2((i % self.columns) as f32, (i / self.columns) as f32);
+            let color = self.colormap.at(share);
+            draw.rect()
+                .xy(self.origin + (cell + 0.5) * Self::CELL_SIZE)
+                .w_h(Self::CELL_SIZE, Self::CELL_SIZE)
+                .color(rgba(
+                    color.x,
+                    color.y,
+                    color.z,
+                    share.sqrt() * Self::OPACITY,
+                ));
+        }
+    }
+}
+
+impl Default for Heatmap {
+    fn default() -> Self {
+        Self::new()
+    }
+}
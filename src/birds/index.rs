@@ -0,0 +1,125 @@
+use crate::agents::Agents;
+use crate::kd_tree::KdTree;
+use crate::quadtree::Quadtree;
+use crate::spatial_hash::SpatialHash;
+use nannou::prelude::*;
+
+/// Structure indexing the agents of a frame by position to find their neighbors,
+/// shared by the threads updating the agents.
+pub trait NeighborIndex: Send + Sync {
+    /// Positions of the indexed agents.
+    fn positions(&self) -> &[Vec2];
+
+    /// Index the agents again after a step, following them where the structure
+    /// allows instead of starting over.
+    fn update(&mut self, agents: &Agents);
+
+    /// Add the indices of the agents possibly within a radius of a position to
+    /// `found`, including all of those that are. An infinite radius finds every agent.
+    fn within(&self, position: Vec2, radius: f32, found: &mut Vec<usize>);
+
+    /// Fill `nearest` with the indices and distances of the agents nearest to a
+    /// position that are accepted by a filter, at most `count` of them.
+    fn nearest(
+        &self,
+        position: Vec2,
+        count: usize,
+        accept: &dyn Fn(usize, f32) -> bool,
+        nearest: &mut Vec<(usize, f32)>,
+    ) {
+        nearest.clear();
+        nearest.extend(
+            self.positions()
+                .iter()
+                .enumerate()
+                .map(|(i, other)| (i, other.distance(position)))
+                .filter(|(i, distance)| accept(*i, *distance)),
+        );
+        if nearest.len() > count {
+            nearest.select_nth_unstable_by(count - 1, |a, b| a.1.total_cmp(&b.1));
+            nearest.truncate(count);
+        }
+    }
+
+    /// Draw the subdivision of the structure.
+    fn display(&self, _draw: &Draw, _color: Rgba) {}
+}
+
+/// Structure agents are found through when looking for their neighbors.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Indexing {
+    /// Scan every agent
+    BruteForce,
+    Grid,
+    Quadtree,
+    KdTree,
+}
+
+impl Indexing {
+    pub const COLOR: (f32, f32, f32, f32) = (0.3, 0.8, 0.4, 0.4);
+
+    pub fn next(self) -> Self {
+        match self {
+            Indexing::BruteForce => Indexing::Grid,
+            Indexing::Grid => Indexing::Quadtree,
+            Indexing::Quadtree => Indexing::KdTree,
+            Indexing::KdTree => Indexing::BruteForce,
+        }
+    }
+
+    /// Index the agents with the structure.
+    pub fn build(self, agents: &Agents) -> Box<dyn NeighborIndex> {
+        match self {
+            Indexing::BruteForce => Box::new(BruteForce::new(agents)),
+            Indexing::Grid => Box::new(SpatialHash::new(agents)),
+            Indexing::Quadtree => Box::new(Quadtree::new(agents)),
+            Indexing::KdTree => Box::new(KdTree::new(agents)),
+        }
+    }
+
+    /// Draw the name of the structure with the time the agents took to update.
+    pub fn display(self, draw: &Draw, win_rect: Rect, update_time: f32, color: Rgba) {
+        let name = match self {
+            Indexing::BruteForce => "brute force",
+            Indexing::Grid => "spatial hash grid",
+            Indexing::Quadtree => "quadtree",
+            Indexing::KdTree => "k-d tree",
+        };
+
+        let area = win_rect.pad(20.0);
+        draw.text(&format!("{}: {:.1} ms", name, update_time * 1000.0))
+            .xy(area.xy())
+            .wh(area.wh())
+            .center_justify()
+            .align_text_bottom()
+            .font_size(14)
+            .color(rgba(color.red, color.green, color.blue, 1.0));
+    }
+}
+
+/// Index scanning every agent.
+pub struct BruteForce {
+    positions: Vec<Vec2>,
+}
+
+impl BruteForce {
+    pub fn new(agents: &Agents) -> Self {
+        BruteForce {
+            positions: agents.positions.clone(),
+        }
+    }
+}
+
+impl NeighborIndex for BruteForce {
+    fn positions(&self) -> &[Vec2] {
+        &self.positions
+    }
+
+    fn update(&mut self, agents: &Agents) {
+        self.positions.clone_from(&agents.positions);
+    }
+
+    fn within(&self, _position: Vec2, _radius: f32, found: &mut Vec<usize>) {
+        found.extend(0..self.positions.len());
+    }
+}
@@ -0,0 +1,140 @@
+use bytemuck::{Pod, Zeroable};
+use nannou::prelude::*;
+use nannou::wgpu::util::DeviceExt;
+
+/// Triangle of an agent, as read by the vertex shader.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Pod, Zeroable)]
+pub struct Instance {
+    pub position: [f32; 2],
+    pub rotation: f32,
+    pub size: [f32; 2],
+    pub color: [f32; 4],
+}
+
+/// Vertex buffer of the instances kept from frame to frame, only replaced by a
+/// larger one when the flock outgrows it.
+pub struct InstanceBuffer {
+    label: &'static str,
+    buffer: wgpu::Buffer,
+    /// Instances the buffer holds
+    capacity: usize,
+}
+
+impl InstanceBuffer {
+    /// Instances the first buffer holds
+    const CAPACITY: usize = 1024;
+
+    pub fn new(device: &wgpu::Device, label: &'static str) -> Self {
+        InstanceBuffer {
+            label,
+            buffer: Self::create(device, label, Self::CAPACITY),
+            capacity: Self::CAPACITY,
+        }
+    }
+
+    fn create(device: &wgpu::Device, label: &'static str, capacity: usize) -> wgpu::Buffer {
+        device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some(label),
+            size: (capacity * std::mem::size_of::<Instance>()) as u64,
+            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        })
+    }
+
+    /// Write the instances over those of the last frame, returning the part of
+    /// the buffer they fill.
+    pub fn write(&mut self, window: &Window, instances: &[Instance]) -> wgpu::BufferSlice<'_> {
+        if instances.len() > self.capacity {
+            self.capacity = instances.len().next_power_of_two();
+            self.buffer = Self::create(window.device(), self.label, self.capacity);
+        }
+        let contents: &[u8] = bytemuck::cast_slice(instances);
+        window.queue().write_buffer(&self.buffer, 0, contents);
+        self.buffer.slice(..contents.len() as u64)
+    }
+}
+
+/// Pipeline drawing the triangles of every agent in a single instanced call
+/// instead of one `draw.tri()` each.
+pub struct Instancing {
+    uniforms: wgpu::Buffer,
+    bind_group: wgpu::BindGroup,
+    pipeline: wgpu::RenderPipeline,
+    instances: InstanceBuffer,
+}
+
+impl Instancing {
+    const ATTRIBUTES: [wgpu::VertexAttribute; 4] =
+        wgpu::vertex_attr_array![0 => Float32x2, 1 => Float32, 2 => Float32x2, 3 => Float32x4];
+
+    pub fn new(window: &Window) -> Self {
+        let device = window.device();
+        let module = device.create_shader_module(wgpu::include_wgsl!("shaders/instances.wgsl"));
+
+        let uniforms = device.create_buffer_init(&wgpu::BufferInitDescriptor {
+            label: Some("uniforms"),
+            contents: bytemuck::bytes_of(&Self::uniforms(window.rect())),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+        let layout = wgpu::BindGroupLayoutBuilder::new()
+            .uniform_buffer(wgpu::ShaderStages::VERTEX, false)
+            .build(device);
+        let bind_group = wgpu::BindGroupBuilder::new()
+            .binding(uniforms.as_entire_binding())
+            .build(device, &layout);
+
+        // Agents fade out, so their colors are blended over what is below
+        let pipeline_layout =
+            wgpu::create_pipeline_layout(device, Some("instances"), &[&layout], &[]);
+        let pipeline = wgpu::RenderPipelineBuilder::from_layout(&pipeline_layout, &module)
+            .vertex_entry_point("vs_main")
+            .fragment_shader(&module)
+            .fragment_entry_point("fs_main")
+            .add_instance_buffer::<Instance>(&Self::ATTRIBUTES)
+            .color_format(Frame::TEXTURE_FORMAT)
+            .color_blend(wgpu::BlendComponent {
+                src_factor: wgpu::BlendFactor::SrcAlpha,
+                dst_factor: wgpu::BlendFactor::OneMinusSrcAlpha,
+                operation: wgpu::BlendOperation::Add,
+            })
+            .sample_count(window.msaa_samples())
+            .build(device);
+
+        Instancing {
+            uniforms,
+            bind_group,
+            pipeline,
+            instances: InstanceBuffer::new(device, "instances"),
+        }
+    }
+
+    /// Half of the size of the window, mapping window coordinates to clip space.
+    fn uniforms(win_rect: Rect) -> [f32; 4] {
+        [win_rect.w() * 0.5, win_rect.h() * 0.5, 0.0, 0.0]
+    }
+
+    /// Draw the instances over what is already in the frame.
+    pub fn display(&mut self, window: &Window, frame: &Frame, instances: &[Instance]) {
+        if instances.is_empty() {
+            return;
+        }
+        window.queue().write_buffer(
+            &self.uniforms,
+            0,
+            bytemuck::bytes_of(&Self::uniforms(window.rect())),
+        );
+        let buffer = self.instances.write(window, instances);
+
+        let mut encoder = frame.command_encoder();
+        let mut pass = wgpu::RenderPassBuilder::new()
+            .color_attachment(frame.texture_view(), |color| {
+                color.load_op(wgpu::LoadOp::Load)
+            })
+            .begin(&mut encoder);
+        pass.set_pipeline(&self.pipeline);
+        pass.set_bind_group(0, &self.bind_group, &[]);
+        pass.set_vertex_buffer(0, buffer);
+        pass.draw(0..3, 0..instances.len() as u32);
+    }
+}
@@ -0,0 +1,30 @@
+use crate::args::Args;
+use clap::ValueEnum;
+
+/// How agents moved by forces advance their position and velocity over a step.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Integrator {
+    /// Position advanced with the velocity from before the forces
+    Euler,
+    /// Position advanced with the velocity after the forces, conserving energy better
+    SemiImplicit,
+    /// Position advanced with the previous acceleration too, and velocity with the
+    /// average of the previous and current accelerations
+    Verlet,
+}
+
+impl Integrator {
+    /// Integrator selected on the command line with
+    /// `--integrator <euler|semi-implicit|verlet>`, explicit Euler by default.
+    pub fn from_args() -> Self {
+        Args::get().integrator
+    }
+
+    pub fn next(self) -> Self {
+        match self {
+            Integrator::Euler => Integrator::SemiImplicit,
+            Integrator::SemiImplicit => Integrator::Verlet,
+            Integrator::Verlet => Integrator::Euler,
+        }
+    }
+}
@@ -0,0 +1,200 @@
+use crate::agents::Agents;
+use crate::index::NeighborIndex;
+use nannou::prelude::*;
+
+/// Tree splitting the agents in halves at the median of alternating axes,
+/// stored implicitly: the node of a range of the order is its middle element,
+/// with the halves before and after it as children.
+pub struct KdTree {
+    positions: Vec<Vec2>,
+    order: Vec<usize>,
+}
+
+impl KdTree {
+    pub fn new(agents: &Agents) -> Self {
+        let positions = agents.positions.clone();
+        let mut order: Vec<usize> = (0..positions.len()).collect();
+        Self::build(&positions, &mut order, 0);
+        KdTree { positions, order }
+    }
+
+    /// Put the median of a range along the axis of its depth in the middle,
+    /// with smaller agents before and larger ones after it.
+    fn build(positions: &[Vec2], order: &mut [usize], depth: usize) {
+        if order.len() <= 1 {
+            return;
+        }
+        let axis = depth % 2;
+        let middle = order.len() / 2;
+        order.select_nth_unstable_by(middle, |&a, &b| {
+            positions[a][axis].total_cmp(&positions[b][axis])
+        });
+        let (before, after) = order.split_at_mut(middle);
+        Self::build(positions, before, depth + 1);
+        Self::build(positions, &mut after[1..], depth + 1);
+    }
+
+    /// Collect the agents of a range within a radius of a position.
+    fn search_within(
+        &self,
+        range: (usize, usize),
+        depth: usize,
+        position: Vec2,
+        radius: f32,
+        found: &mut Vec<usize>,
+    ) {
+        let (start, end) = range;
+        if start >= end {
+            return;
+        }
+        let middle = start + (end - start) / 2;
+        let i = self.order[middle];
+        if self.positions[i].distance_squared(position) <= radius * radius {
+            found.push(i);
+        }
+
+        // Only visit the sides the circle reaches into
+        let difference = position[depth % 2] - self.positions[i][depth % 2];
+        if difference <= radius {
+            self.search_within((start, middle), depth + 1, position, radius, found);
+        }
+        if difference >= -radius {
+            self.search_within((middle + 1, end), depth + 1, position, radius, found);
+        }
+    }
+
+    /// Keep the nearest accepted agents of a range, sorted by distance.
+    fn search_nearest(
+        &self,
+        range: (usize, usize),
+        depth: usize,
+        position: Vec2,
+        count: usize,
+        accept: &dyn Fn(usize, f32) -> bool,
+        nearest: &mut Vec<(usize, f32)>,
+    ) {
+        let (start, end) = range;
+        if start >= end {
+            return;
+        }
+        let middle = start + (end - start) / 2;
+        let i = self.order[middle];
+        let distance = self.positions[i].distance(position);
+        if accept(i, distance) {
+            let slot = nearest.partition_point(|(_, d)| *d < distance);
+            if slot < count {
+                nearest.insert(slot, (i, distance));
+                nearest.truncate(count);
+            }
+        }
+
+        // Visit the side of the position first, and the other only if it may hold nearer agents
+        let difference = position[depth % 2] - self.positions[i][depth % 2];
+        let (near, far) = if difference < 0.0 {
+            ((start, middle), (middle + 1, end))
+        } else {
+            ((middle + 1, end), (start, middle))
+        };
+        self.search_nearest(near, depth + 1, position, count, accept, nearest);
+        let farthest = if nearest.len() < count {
+            f32::INFINITY
+        } else {
+            nearest[count - 1].1
+        };
+        if difference.abs() < farthest {
+            self.search_nearest(far, depth + 1, position, count, accept, nearest);
+        }
+    }
+
+    /// Draw the splitting line of a range inside the bounds it covers.
+    fn display_node(
+        &self,
+        draw: &Draw,
+        range: (usize, usize),
+        depth: usize,
+        bounds: Rect,
+        color: Rgba,
+    ) {
+        let (start, end) = range;
+        if start >= end {
+            return;
+        }
+        let middle = start + (end - start) / 2;
+        let split = self.positions[self.order[middle]];
+
+        // Even depths split along x with a vertical line, odd ones along y
+        let axis = depth % 2;
+        let (line, before, after) = if axis == 0 {
+            let x = split.x.clamp(bounds.left(), bounds.right());
+            (
+                (vec2(x, bounds.bottom()), vec2(x, bounds.top())),
+                Rect::from_corners(bounds.bottom_left(), vec2(x, bounds.top())),
+                Rect::from_corners(vec2(x, bounds.bottom()), bounds.top_right()),
+            )
+        } else {
+            let y = split.y.clamp(bounds.bottom(), bounds.top());
+            (
+                (vec2(bounds.left(), y), vec2(bounds.right(), y)),
+                Rect::from_corners(bounds.bottom_left(), vec2(bounds.right(), y)),
+                Rect::from_corners(vec2(bounds.left(), y), bounds.top_right()),
+            )
+        };
+        draw.line()
+            .start(line.0)
+            .end(line.1)
+            .weight(1.0)
+            .color(color);
+
+        self.display_node(draw, (start, middle), depth + 1, before, color);
+        self.display_node(draw, (middle + 1, end), depth + 1, after, color);
+    }
+}
+
+impl NeighborIndex for KdTree {
+    fn positions(&self) -> &[Vec2] {
+        &self.positions
+    }
+
+    /// Build the tree again, reusing its memory.
+    fn update(&mut self, agents: &Agents) {
+        self.positions.clone_from(&agents.positions);
+        self.order.clear();
+        self.order.extend(0..self.positions.len());
+        Self::build(&self.positions, &mut self.order, 0);
+    }
+
+    fn within(&self, position: Vec2, radius: f32, found: &mut Vec<usize>) {
+        self.search_within((0, self.order.len()), 0, position, radius, found);
+    }
+
+    fn nearest(
+        &self,
+        position: Vec2,
+        count: usize,
+        accept: &dyn Fn(usize, f32) -> bool,
+        nearest: &mut Vec<(usize, f32)>,
+    ) {
+        nearest.clear();
+        if count > 0 {
+            self.search_nearest((0, self.order.len()), 0, position, count, accept, nearest);
+        }
+    }
+
+    fn display(&self, draw: &Draw, color: Rgba) {
+        // Bounds enclosing every agent
+        let Some(&first) = self.positions.first() else {
+            return;
+        };
+        let (min, max) = self
+            .positions
+            .iter()
+            .fold((first, first), |(min, max), p| (min.min(*p), max.max(*p)));
+        self.display_node(
+            draw,
+            (0, self.order.len()),
+            0,
+            Rect::from_corners(min, max),
+            color,
+        );
+    }
+}
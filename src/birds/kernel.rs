@@ -0,0 +1,35 @@
+/// How much a neighbor weighs in the averages depending on its distance.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kernel {
+    /// Every neighbor counts the same
+    Uniform,
+    /// Neighbors count inversely to their distance
+    InverseDistance,
+    /// Neighbors count less the further away they are, following a bell curve
+    Gaussian,
+}
+
+impl Kernel {
+    /// Deviation of the Gaussian kernel relative to the detection radius
+    const GAUSSIAN_DEVIATION: f32 = 0.4;
+
+    pub fn next(self) -> Self {
+        match self {
+            Kernel::Uniform => Kernel::InverseDistance,
+            Kernel::InverseDistance => Kernel::Gaussian,
+            Kernel::Gaussian => Kernel::Uniform,
+        }
+    }
+
+    /// Weight of a neighbor at a distance within the detection radius.
+    pub fn weight(self, distance: f32, radius: f32) -> f32 {
+        match self {
+            Kernel::Uniform => 1.0,
+            Kernel::InverseDistance => 1.0 / distance.max(1.0),
+            Kernel::Gaussian => {
+                let deviation = radius * Self::GAUSSIAN_DEVIATION;
+                (-distance.powi(2) / (2.0 * deviation.powi(2))).exp()
+            }
+        }
+    }
+}
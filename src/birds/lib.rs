@@ -0,0 +1,91 @@
+//! Simulation of flocking birds and everything they interact with, shared by the
+//! sketch and the benchmarks.
+
+pub mod agent;
+pub mod agent3d;
+pub mod agents;
+pub mod args;
+pub mod attractor;
+pub mod avoidance;
+pub mod background;
+pub mod batch;
+pub mod bloom;
+pub mod boundary;
+pub mod brain;
+pub mod call;
+pub mod camera;
+pub mod collision;
+pub mod color_mode;
+pub mod colormap;
+pub mod config;
+pub mod controls;
+pub mod day_night;
+pub mod debug_overlay;
+pub mod density;
+pub mod ecosystem;
+pub mod events;
+pub mod evolution;
+pub mod flight;
+pub mod flocks;
+pub mod flow_field;
+pub mod food;
+pub mod force_point;
+pub mod governor;
+pub mod gpu;
+pub mod heatmap;
+pub mod index;
+pub mod instancing;
+pub mod integrator;
+pub mod kd_tree;
+pub mod kernel;
+pub mod lod;
+pub mod long_range;
+pub mod migration;
+pub mod motion_blur;
+pub mod mouse;
+pub mod movement;
+pub mod neighbor_cache;
+pub mod obstacle;
+pub mod parameters;
+pub mod path;
+pub mod perception;
+pub mod personality;
+pub mod pheromones;
+pub mod plain_flock;
+pub mod plane;
+pub mod plexus;
+pub mod precision;
+pub mod predator;
+pub mod presets;
+pub mod profile;
+pub mod profiler;
+pub mod pursuit;
+pub mod quadtree;
+pub mod randomize;
+pub mod region;
+pub mod scratch;
+pub mod silhouette;
+pub mod sim_thread;
+pub mod spatial_hash;
+pub mod species;
+pub mod sprites;
+pub mod strike;
+pub mod surface;
+pub mod surface_agent;
+pub mod svg;
+pub mod sweep;
+pub mod terrain;
+pub mod timeline;
+pub mod timestep;
+pub mod toast;
+pub mod trails;
+pub mod triple_buffer;
+pub mod tuning;
+pub mod velocity_field;
+pub mod vicsek;
+pub mod vortex;
+pub mod wall;
+pub mod watcher;
+pub mod waypoints;
+pub mod world;
+pub mod zones;
@@ -0,0 +1,143 @@
+use crate::silhouette::Silhouette;
+use nannou::geom::Tri;
+use nannou::prelude::*;
+
+/// How much of an agent is drawn, less for large flocks or agents small on screen
+/// so huge simulations stay renderable.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Detail {
+    Point,
+    /// Short segment along the heading
+    Line,
+    Triangle,
+}
+
+impl Detail {
+    // Flocks larger than this are drawn as lines, then as points
+    const LINE_COUNT: usize = 2_000;
+    const POINT_COUNT: usize = 10_000;
+
+    // Agents shorter than this on screen are drawn as lines, then as points
+    const LINE_LENGTH: f32 = 4.0;
+    const POINT_LENGTH: f32 = 1.5;
+
+    /// Width of lines and points
+    const WEIGHT: f32 = 1.0;
+
+    /// Most detail a flock of a number of agents is drawn with.
+    pub fn of_flock(count: usize) -> Self {
+        if count > Self::POINT_COUNT {
+            Detail::Point
+        } else if count > Self::LINE_COUNT {
+            Detail::Line
+        } else {
+            Detail::Triangle
+        }
+    }
+
+    /// Detail an agent of a length on screen is drawn with, no more than this one.
+    pub fn at_length(self, length: f32) -> Self {
+        let detail = if length < Self::POINT_LENGTH {
+            Detail::Point
+        } else if length < Self::LINE_LENGTH {
+            Detail::Line
+        } else {
+            Detail::Triangle
+        };
+        self.min(detail)
+    }
+}
+
+/// Agents drawn with less than full detail, gathered into a single mesh instead
+/// of a primitive each.
+#[derive(Debug)]
+pub struct Sketch {
+    detail: Detail,
+    tris: Vec<Tri<(Vec3, Rgba)>>,
+    pub silhouette: Silhouette,
+}
+
+impl Sketch {
+    /// Sketch of a flock of a number of agents.
+    pub fn new(count: usize) -> Self {
+        Sketch {
+            detail: Detail::of_flock(count),
+            tris: Vec::new(),
+            silhouette: Silhouette::new(),
+        }
+    }
+
+    /// Start sketching a flock of a number of agents again, keeping the mesh for reuse.
+    pub fn reset(&mut self, count: usize) {
+        self.detail = Detail::of_flock(count);
+        self.tris.clear();
+    }
+
+    /// Draw a triangle of a size at a position, heading at an angle, with the
+    /// detail its length on screen and the size of the flock allow.
+    pub fn tri(&mut self, draw: &Draw, position: Vec2, angle: f32, size: Vec2, color: Rgba) {
+        if self.detail.at_length(size.x) == Detail::Triangle {
+            draw.tri().xy(position).rotate(angle).wh(size).color(color);
+        } else {
+            self.stroke(position, angle, size, color);
+        }
+    }
+
+    /// Draw the silhouette of a bird of a size at a position, heading at an
+    /// angle with its wings spread by a share, with the detail its length on
+    /// screen and the size of the flock allow.
+    pub fn bird(
+        &mut self,
+        draw: &Draw,
+        position: Vec2,
+        angle: f32,
+        size: Vec2,
+        spread: f32,
+        color: Rgba,
+    ) {
+        if self.detail.at_length(size.x) == Detail::Triangle {
+            let points = self.silhouette.points(position, angle, size, spread);
+            draw.polygon().points(points).color(color);
+        } else {
+            self.stroke(position, angle, size, color);
+        }
+    }
+
+    /// Gather a line or a point standing in for an agent drawn with less than
+    /// full detail.
+    fn stroke(&mut self, position: Vec2, angle: f32, size: Vec2, color: Rgba) {
+        let heading = vec2(angle.cos(), angle.sin());
+        let side = heading.perp() * Detail::WEIGHT * 0.5;
+        let (start, end) = match self.detail.at_length(size.x) {
+            Detail::Line | Detail::Triangle => (
+                position - heading * size.x * 0.5,
+                position + heading * size.x * 0.5,
+            ),
+            Detail::Point => (
+                position - heading * Detail::WEIGHT * 0.5,
+                position + heading * Detail::WEIGHT * 0.5,
+            ),
+        };
+
+        // Quad from the start to the end, as wide as a line
+        let corners =
+            [start - side, start + side, end + side, end - side].map(|corner| corner.extend(0.0));
+        self.tris.push(Tri([
+            (corners[0], color),
+            (corners[1], color),
+            (corners[2], color),
+        ]));
+        self.tris.push(Tri([
+            (corners[0], color),
+            (corners[2], color),
+            (corners[3], color),
+        ]));
+    }
+
+    /// Draw the gathered agents, emptying the mesh.
+    pub fn display(&mut self, draw: &Draw) {
+        if !self.tris.is_empty() {
+            draw.mesh().tris_colored(self.tris.drain(..));
+        }
+    }
+}
@@ -0,0 +1,50 @@
+use nannou::prelude::*;
+
+/// Cohesion towards the distant parts of the flock, beyond the detection radius,
+/// approximated with the Barnes-Hut algorithm over a quadtree of the agents.
+#[derive(Debug, Clone, Copy)]
+pub struct LongRange {
+    /// Opening angle under which distant quadrants are approximated by their
+    /// center of mass, exact at 0 and coarser as it widens
+    pub theta: f32,
+}
+
+impl LongRange {
+    pub const COLOR: (f32, f32, f32, f32) = (0.8, 0.8, 0.8, 1.0);
+
+    const THETA: f32 = 0.5;
+    const THETA_RANGE: (f32, f32) = (0.0, 1.5);
+    const THETA_STEP: f32 = 0.1;
+
+    pub fn new() -> Self {
+        LongRange { theta: Self::THETA }
+    }
+
+    pub fn increase(&mut self) {
+        self.theta = (self.theta + Self::THETA_STEP).min(Self::THETA_RANGE.1);
+    }
+
+    pub fn decrease(&mut self) {
+        self.theta = (self.theta - Self::THETA_STEP).max(Self::THETA_RANGE.0);
+    }
+
+    pub fn display(&self, draw: &Draw, win_rect: Rect, color: Rgba) {
+        // Below the noise and polarization of the Vicsek model
+        let text = format!("\n\nlong-range cohesion θ {:.1}", self.theta);
+
+        let area = win_rect.pad(20.0);
+        draw.text(&text)
+            .xy(area.xy())
+            .wh(area.wh())
+            .center_justify()
+            .align_text_top()
+            .font_size(14)
+            .color(color);
+    }
+}
+
+impl Default for LongRange {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,1393 @@
+/**
+* OPTIONS
+* --agents <count>: number of agents the flock starts with, split among the species
+* --speed <factor>: factor every species' speed is multiplied by
+* --seed <number>: seed of every random draw, shown in the corner, so a run can be
+*   reproduced exactly, drawn at random unless given here or in the configuration
+* --fullscreen, --windowed: open the window fullscreen, as by default, or windowed
+* --record: save every frame as a numbered png in the frames directory
+* --config <path>: TOML file of the species and agent constants, birds.toml by default,
+*   reloaded live whenever it is saved
+* --brain <rules|neural|zones>: steer agents with the flocking rules, evolved neural networks
+*   or Couzin's zone model
+* --day-length <seconds>: duration of a day and night cycle
+* --heightmap <path>: grayscale image of the terrain below the flock
+* --food-rate <items>: food items growing every second while foraging
+* --path <file>: closed polyline to flow along, one `x y` point per line
+* --margin <pixels>: width of the margin along the edges agents steer back from
+* --osc <host:port>: send flock split and merge events as OSC messages
+* --log-events: print flock split and merge events
+* --density <agents>: agents per million square pixels kept as the window changes size
+* --neighbor-refresh <steps>: steps cached neighbor lists are reused for before being
+*   gathered again
+* --target-fps <fps>: add and remove agents to hold the frame rate, showing their count
+* --svg <file>: shapes to flock around, filled ones as solid regions and others as walls
+* --sprite <png>: image facing right drawn for every agent instead of a triangle, tinted
+*   by the color of the agent
+* --profile <low|medium|high>: number of agents, trail quality, antialiasing and neighbor
+*   search suiting a laptop, an average machine or a desktop
+* --backend <cpu|gpu|thread>: simulate the flock on the CPU with every feature, only the
+*   flocking rules on the GPU with compute shaders, or the plane with its predators on a
+*   thread of its own, decoupled from rendering
+* --gpu-agents <count>: number of agents simulated on the GPU
+* --integrator <euler|semi-implicit|verlet>: how physical movement advances agents over a step
+* --precision <f32|f64>: simulate only the flocking rules, deterministically and in single or
+*   double precision, drawing in single precision
+* --sweep <file>: run the flocking rules without a window over every combination of
+*   heading noise and cohesion weight, writing the polarization, number of flocks and
+*   nearest neighbor distance of every run to a CSV file
+* --sweep-noise <from:to:count>, --sweep-cohesion <from:to:count>: values swept over,
+*   0:6.28:9 and 0:3:7 by default, or a single value to hold
+* --sweep-steps <steps>: steps every run lasts, 2000 by default
+* --sweep-runs <count>: runs of every combination, each from the next seed
+*
+* MOUSE
+* click: use the current tool
+*   startle: startle nearby agents
+*   hawk strike: scatter nearby agents away from the click
+*   gravity well: place an attractor (shift: repulsor)
+*   vortex: place a counter-clockwise vortex (shift: clockwise)
+*   wall: drag to draw a wall
+*
+* KEYS
+* q: quit
+* s: save png (shift: save the parameters, seed and number of agents to a timestamped
+*   TOML file loadable with --config)
+* t: cycle mouse tool (startle, hawk strike, gravity well, vortex, wall)
+*   (shift: toggle the profiler timing the simulation, index rebuild and drawing)
+* delete: remove the placed force points, vortices and walls, keeping the imported shapes
+* a: toggle aging (agents die of old age and are replaced)
+*   (shift: toggle wingbeats, the silhouettes flapping between swept and spread wings)
+* b: cycle boundary behavior (wrap, bounce, steer)
+*   (shift: toggle motion blur, averaging every frame over instants between steps)
+* c: cycle agent coloring (species, connected flock, personality, heading, speed,
+*   acceleration, neighbor density), flashing on flock splits and merges when coloring by flock
+*   (shift: toggle long-range cohesion towards the distant flock)
+* d: cycle domain (plane, 3D space, sphere surface, torus surface)
+*   (shift: toggle drawing the agents in a single instanced draw call)
+* e: toggle ecosystem (energy, reproduction and death)
+*   (shift: cycle predator pursuit (direct chase, intercept, stalk stragglers))
+* f: toggle flow field visualization (shift: toggle flap and glide flight phases)
+* g: toggle genetic evolution of the flocking parameters
+*   (shift: toggle bloom, bright agents glowing, its threshold and intensity set in the panel)
+* h: toggle hard collisions between agents
+*   (shift: cycle performance profile (low, medium, high), setting the number of agents,
+*   trail quality and neighbor search, antialiasing only applying with --profile)
+* i: cycle steering brain (rules, neural network, zones) (shift: cycle zone model phase)
+* j: toggle hunger-driven foraging for food
+* k: cycle neighbor weighting kernel (uniform, inverse distance, gaussian)
+*   (shift: toggle metric and topological nearest neighbors)
+* l: pass leadership on to the next agent (shift: no leader)
+* m: cycle mouse force (off, attract, repel)
+*   (shift: cycle moving attractor route (off, lissajous, circle, recorded))
+* n: toggle day and night cycle (shift: toggle caching neighbor lists between refreshes)
+* , and .: skip the time of day back and forward by an hour
+* o: toggle seasonal migration towards a distant orbiting target
+*   (shift: toggle sorting the agents by grid cell so neighbors lie close in memory)
+* p: toggle pheromone trails (shift: toggle personality traits)
+* r: cycle obstacle avoidance (radial, whisker raycasts)
+*   (shift: jump to random parameters within the configured bounds, showing them and
+*   appending them to randomized.csv)
+* u: cycle terrain influence (off, downhill, ridges), with a heightmap
+* v: toggle V formation flight
+*   (shift: cycle physical movement integrator (explicit Euler, semi-implicit Euler,
+*   velocity Verlet))
+* w: toggle waypoints (shift: toggle fading trails behind the agents)
+* x: cycle movement model (classic constant speed, physical with drag)
+*   (shift: toggle banking turns and gradual speed changes)
+* y: toggle calls rallying the agents they reach
+* z: toggle flowing along a closed path
+* 1 to 9: load the preset in the slot, built-in ones being tight murmuration, loose
+*   wanderers and panic (shift: save the current parameters into the slot)
+* tab: cycle neighbor search (brute force, spatial hash grid, quadtree, k-d tree)
+*   (shift: toggle drawing the search structure and the update time)
+* f1: cycle debug overlay of the detection radius, separation distance and separation,
+*   alignment and cohesion forces (off, every agent, the agent under the cursor)
+* f2: toggle the heatmap of the density of agents (shift: cycle its colormap)
+* f3: toggle arrows of the average velocity of the flock across the spatial grid
+* f4: toggle faint lines between agents perceiving each other, capped for large flocks
+* 0: select the next parameter to tune (separation, alignment and cohesion weights,
+*   detection radius, separation distance, panic radius, max force, max turn rate, speed)
+* left and right: lower and raise the selected parameter, showing its value
+*   (shift: shorten and lengthen the trails)
+* `: show or hide the panel of sliders tuning the rules, radii, species and bloom
+* space: pause or resume the simulation
+* enter: start or stop recording the cursor path for the moving attractor
+* [ and ]: narrow and widen the margin agents steer back from
+* minus and equals: narrow and widen the opening angle approximating the distant flock
+* up and down: raise and lower the noise in the agents' headings
+*   (shift: raise and lower the target density of agents)
+*/
+use birds::agent::Agent;
+use birds::agent3d::Agent3D;
+use birds::args::{Args, Backend};
+use birds::background::Backdrop;
+use birds::bloom::Bloom;
+use birds::boundary::Boundary;
+use birds::brain::Brain;
+use birds::call::Call;
+use birds::camera::Camera;
+use birds::color_mode::ColorMode;
+use birds::config::Config;
+use birds::controls::Controls;
+use birds::day_night::DayNight;
+use birds::debug_overlay::DebugOverlay;
+use birds::evolution::Evolution;
+use birds::flocks::Flocks;
+use birds::flow_field::FlowField;
+use birds::food::Food;
+use birds::force_point::ForcePoint;
+use birds::governor::Governor;
+use birds::gpu::GpuFlock;
+use birds::heatmap::Heatmap;
+use birds::index::Indexing;
+use birds::instancing::{Instance, Instancing};
+use birds::lod::Sketch;
+use birds::long_range::LongRange;
+use birds::migration::Migration;
+use birds::motion_blur::MotionBlur;
+use birds::obstacle::Obstacle;
+use birds::path::Path;
+use birds::pheromones::Pheromones;
+use birds::plane::{Plane, Surroundings};
+use birds::plexus::Plexus;
+use birds::precision::Precision;
+use birds::predator::Predator;
+use birds::presets::Presets;
+use birds::profile::Profile;
+use birds::profiler::{Profiler, Stage};
+use birds::randomize::Bounds;
+use birds::region::Region;
+use birds::sim_thread::SimThread;
+use birds::species::Species;
+use birds::sprites::Sprites;
+use birds::strike::Strike;
+use birds::surface::Surface;
+use birds::surface_agent::SurfaceAgent;
+use birds::sweep::Sweep;
+use birds::terrain::Terrain;
+use birds::timeline::Timeline;
+use birds::timestep::Timestep;
+use birds::toast::Toast;
+use birds::trails::Trails;
+use birds::tuning::Tunable;
+use birds::velocity_field::VelocityField;
+use birds::vicsek::Vicsek;
+use birds::vortex::Vortex;
+use birds::wall::Wall;
+use birds::watcher::FileWatcher;
+use birds::waypoints::Waypoints;
+use birds::zones::Zones;
+use nannou::prelude::*;
+use nannou::rand::rngs::StdRng;
+use nannou::rand::{Rng, SeedableRng};
+use nannou::winit::event::WindowEvent;
+use nannou_egui::Egui;
+use std::cell::RefCell;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+fn main() {
+    Args::init();
+
+    // Sweeps run without a window
+    if let Some(path) = &Args::get().sweep {
+        if let Err(error) = Sweep::run(path) {
+            eprintln!("{error}");
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    nannou::app(model).update(update).run();
+}
+
+/// Space in which the flock is simulated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Domain {
+    Plane,
+    Space,
+    Sphere,
+    Torus,
+}
+
+/// What a mouse click does.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Tool {
+    Startle,
+    Strike,
+    GravityWell,
+    Vortex,
+    Wall,
+}
+
+struct Model {
+    domain: Domain,
+    day_night: DayNight,
+    /// Whether time of day passes and affects the flock
+    day_cycle: bool,
+    /// Notifier of changes to the configuration file, to reload it live
+    config_watcher: Option<FileWatcher>,
+    /// Message confirming a reload or reporting why it failed
+    toast: Option<Toast>,
+    /// Flocking parameters saved in the slots of the number keys
+    presets: Presets,
+    egui: Egui,
+    controls: Controls,
+    /// Parameter adjusted with the left and right arrows
+    tunable: Tunable,
+    /// Settings suiting the hardware, chosen on the command line or with a key
+    profile: Option<Profile>,
+    /// Whether the simulation is stopped, still drawing its last step
+    paused: bool,
+    /// Seed every random draw follows from, shown so the run can be reproduced
+    seed: u64,
+    rng: StdRng,
+    /// Flock on the plane and everything it interacts with
+    plane: Plane,
+    /// Agents between the last two steps, their instances and their mesh, refilled
+    /// every frame so drawing reuses the same buffers
+    drawn_agents: RefCell<Vec<Agent>>,
+    instances: RefCell<Vec<Instance>>,
+    sketch: RefCell<Sketch>,
+    /// Agents in space or on a surface from back to front, with their depth
+    depth_order: RefCell<Vec<(usize, f32)>>,
+    timestep: Timestep,
+    show_index: bool,
+    debug_overlay: DebugOverlay,
+    profiler: Profiler,
+    show_profiler: bool,
+    /// Controller of the density holding the frame rate when given a target
+    governor: Option<Governor>,
+    /// Walls imported from an SVG file, kept when the placed ones are removed
+    outlines: Vec<Wall>,
+    /// Start of the wall being drawn
+    wall_start: Option<Vec2>,
+    tool: Tool,
+    show_flow_field: bool,
+    trails: Trails,
+    /// Density of the agents over a coarse grid, drawn behind them
+    heatmap: Heatmap,
+    /// Average velocity of the flock over the spatial grid, drawn as arrows
+    velocity_field: VelocityField,
+    /// Lines between agents perceiving each other
+    plexus: RefCell<Plexus>,
+    agents_3d: Vec<Agent3D>,
+    /// Agents in space as of the last step, which the agents read while updating
+    previous_agents_3d: Vec<Agent3D>,
+    bounds_3d: Cuboid,
+    sphere: Surface,
+    torus: Surface,
+    /// Agents of the current surface domain
+    surface_agents: Vec<SurfaceAgent>,
+    previous_surface_agents: Vec<SurfaceAgent>,
+    camera: Camera,
+    /// Whether the agents are drawn in a single draw call through the instancing pipeline
+    instanced: bool,
+    instancing: RefCell<Instancing>,
+    /// Image drawn for every agent on the plane, when one is given
+    sprites: Option<RefCell<Sprites>>,
+    /// Frames averaged over several instants, for footage of fast agents
+    motion_blur: RefCell<MotionBlur>,
+    /// Gradient or image of the configuration drawn behind the agents
+    backdrop: RefCell<Backdrop>,
+    /// Glow around bright agents, drawn through a post-processing stage
+    bloom: RefCell<Bloom>,
+    /// Flock simulated on the GPU instead of the CPU
+    gpu: Option<GpuFlock>,
+    /// Flock simulated on a thread of its own instead of every frame
+    sim_thread: Option<SimThread>,
+    /// Flock simulated deterministically in a chosen precision instead
+    precision: Option<Precision>,
+}
+
+impl Model {
+    const BOUNDS_COLOR: (f32, f32, f32, f32) = (0.3, 0.3, 0.3, 1.0);
+    const CAMERA_ORBIT_SPEED: f32 = 0.05;
+    const STARTLE_RADIUS: f32 = 80.0;
+
+    /// Surface of the current domain, the sphere outside of surface domains.
+    fn surface(&self) -> Surface {
+        match self.domain {
+            Domain::Torus => self.torus,
+            _ => self.sphere,
+        }
+    }
+}
+
+fn model(app: &App) -> Model {
+    let mut window = app.new_window().title("Birds");
+    if !Args::get().windowed {
+        window = window.fullscreen();
+    }
+    let profile = Profile::from_args();
+    if let Some(profile) = profile {
+        window = window.msaa_samples(profile.msaa_samples());
+    }
+    let window = window
+        .view(view)
+        .key_released(key_released)
+        .mouse_pressed(mouse_pressed)
+        .mouse_released(mouse_released)
+        .raw_event(raw_window_event)
+        .build()
+        .unwrap();
+
+    let instancing = Instancing::new(&app.window(window).unwrap());
+    let motion_blur = RefCell::new(MotionBlur::new(&app.window(window).unwrap()));
+    let bloom = RefCell::new(Bloom::new(&app.window(window).unwrap()));
+    let sprites = Args::get().sprite.as_ref().and_then(|path| {
+        Sprites::load(&app.window(window).unwrap(), path)
+            .map_err(|error| eprintln!("Could not load sprite {path}: {error}"))
+            .ok()
+    });
+    let egui = Egui::from_window(&app.window(window).unwrap());
+
+    // Every random draw follows from the seed, so a run can be reproduced exactly
+    let seed = Args::get()
+        .seed
+        .or(Config::get().seed)
+        .unwrap_or_else(random);
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    // The GPU flock replaces the simulation when requested
+    let gpu = (Args::get().backend == Backend::Gpu).then(|| {
+        let window = app.window(window).unwrap();
+        GpuFlock::new(&window, GpuFlock::count_from_args(), &mut rng)
+    });
+    let config = Config::get();
+    let sim_thread = (Args::get().backend == Backend::Thread).then(|| {
+        let plane = Plane::new(app.window_rect(), profile, &config, &mut rng);
+        SimThread::spawn(plane, app.window_rect(), StdRng::seed_from_u64(rng.gen()))
+    });
+
+    let plane = Plane::new(app.window_rect(), profile, &config, &mut rng);
+
+    // Walls imported from the shapes stay when the placed ones are removed
+    let outlines = plane.walls.clone();
+
+    // The precise flock replaces the simulation when requested, starting as the plane does
+    let precision = Precision::from_args(&plane.agents);
+
+    // Changes to the configuration file are applied as it is saved
+    let config_watcher = FileWatcher::new(&Args::get().config)
+        .map_err(|error| {
+            eprintln!(
+                "Could not watch configuration {}: {error}",
+                Args::get().config
+            )
+        })
+        .ok();
+
+    // The 3D flock lives in a cube fitting the window, viewed from twice its size
+    let side = app.window_rect().w().min(app.window_rect().h());
+    let bounds_3d = Cuboid::from_xyz_whd(Vec3::ZERO, Vec3::splat(side));
+    let mut agents_3d = Vec::new();
+    for (species, Species { count, .. }) in config.species.iter().enumerate() {
+        agents_3d.extend((0..*count).map(|_| Agent3D::new(&bounds_3d, species, &config, &mut rng)));
+    }
+    let camera = Camera::new(side * 2.0);
+
+    Model {
+        domain: Domain::Plane,
+        day_night: DayNight::from_args(),
+        day_cycle: false,
+        config_watcher,
+        toast: None,
+        presets: Presets::load(),
+        egui,
+        controls: Controls::default(),
+        tunable: Tunable::SeparationWeight,
+        profile,
+        paused: false,
+        seed,
+        rng,
+        plane,
+        drawn_agents: RefCell::new(Vec::new()),
+        instances: RefCell::new(Vec::new()),
+        depth_order: RefCell::new(Vec::new()),
+        sketch: RefCell::new(Sketch::new(0)),
+        timestep: Timestep::new(),
+        show_index: false,
+        debug_overlay: DebugOverlay::Off,
+        profiler: Profiler::new(),
+        show_profiler: false,
+        governor: Governor::from_args(),
+        outlines,
+        wall_start: None,
+        tool: Tool::Startle,
+        show_flow_field: false,
+        trails: Trails::new(),
+        heatmap: Heatmap::new(),
+        velocity_field: VelocityField::new(),
+        plexus: RefCell::new(Plexus::new()),
+        agents_3d,
+        previous_agents_3d: Vec::new(),
+        bounds_3d,
+        sphere: Surface::Sphere { radius: side * 0.4 },
+        torus: Surface::Torus {
+            major: side * 0.3,
+            minor: side * 0.12,
+        },
+        surface_agents: Vec::new(),
+        previous_surface_agents: Vec::new(),
+        camera,
+        instanced: false,
+        instancing: RefCell::new(instancing),
+        sprites: sprites.map(RefCell::new),
+        motion_blur,
+        backdrop: RefCell::new(Backdrop::new()),
+        bloom,
+        gpu,
+        sim_thread,
+        precision,
+    }
+}
+
+fn update(app: &App, model: &mut Model, update: Update) {
+    if let Some(gpu) = &mut model.gpu {
+        gpu.update(&app.main_window());
+        return;
+    }
+    if let Some(sim_thread) = &mut model.sim_thread {
+        sim_thread.update(app.window_rect(), app.mouse.position());
+        return;
+    }
+    if let Some(precision) = &mut model.precision {
+        let config = Config::get();
+        for _ in 0..model.timestep.advance(update.since_last.as_secs_f32()) {
+            precision.update(app.window_rect(), &config);
+            model.timestep.tick();
+        }
+        return;
+    }
+
+    // Tune the flock with the control panel
+    model.egui.set_elapsed_time(update.since_start);
+    let changes = model.controls.show(
+        &model.egui.begin_frame(),
+        model.paused,
+        model.bloom.get_mut(),
+    );
+    if let Some(config) = changes.config {
+        let (previous, config) = Config::set(config);
+        reconfigure_agents(model, &previous, &config);
+
+        // The population follows the number of agents of every species
+        let population = config.species.iter().map(|species| species.count).sum();
+        model.plane.density.hold(population, app.window_rect());
+    }
+    if changes.toggle_pause {
+        model.paused = !model.paused;
+    }
+    if changes.reset {
+        // The flock starts over from the seed, as it first was
+        model.rng = StdRng::seed_from_u64(model.seed);
+        model.plane.agents = Plane::spawn(app.window_rect(), &Config::get(), &mut model.rng);
+        model.plane.previous_agents.clear();
+    }
+
+    // Apply the configuration file as soon as it is saved
+    if model
+        .config_watcher
+        .as_ref()
+        .is_some_and(FileWatcher::changed)
+    {
+        reload_config(model);
+    }
+    if model.toast.as_mut().is_some_and(|toast| !toast.update()) {
+        model.toast = None;
+    }
+
+    // Count the agents in the cells of the density heatmap
+    if model.heatmap.enabled && model.domain == Domain::Plane {
+        model.heatmap.update(app.window_rect(), &model.plane.agents);
+    }
+
+    // Average the velocity of the flock around the cells of the grid
+    if model.velocity_field.enabled && model.domain == Domain::Plane {
+        model.velocity_field.update(&model.plane.agents);
+    }
+
+    if model.paused {
+        return;
+    }
+
+    // Grow or shrink the flock to hold the frame rate
+    if let Some(governor) = &mut model.governor {
+        governor.update(update.since_last.as_secs_f32(), &mut model.plane.density);
+    }
+
+    // Step the simulation at a fixed rate, as many times as the frame took
+    model.profiler.end_frame();
+    let start = Instant::now();
+    let steps = model.timestep.advance(update.since_last.as_secs_f32());
+    for _ in 0..steps {
+        // Every step sees the time it simulates, however long frames take
+        let time = model.timestep.time();
+        if model.day_cycle {
+            model.day_night.advance(Timestep::DT);
+        }
+
+        match model.domain {
+            Domain::Plane => {
+                let surroundings = Surroundings {
+                    bounds: app.window_rect(),
+                    mouse: app.mouse.position(),
+                    time,
+                    step: model.timestep.step,
+                    darkness: if model.day_cycle {
+                        model.day_night.darkness()
+                    } else {
+                        0.0
+                    },
+                };
+                model.plane.step(&surroundings, &mut model.rng);
+                model.profiler.add(Stage::Index, model.plane.index_time);
+            }
+            Domain::Space => update_space(model, time),
+            Domain::Sphere | Domain::Torus => update_surface(model, time),
+        }
+        model.timestep.tick();
+        if model.timestep.step.is_multiple_of(Timeline::STEPS) {
+            follow_timeline(model);
+        }
+    }
+    model.profiler.record(Stage::Simulation, start);
+}
+
+/// Read the configuration file again, carrying the agents over to it.
+fn reload_config(model: &mut Model) {
+    let path = &Args::get().config;
+    match Config::reload() {
+        Ok((previous, config)) => {
+            reconfigure_agents(model, &previous, &config);
+            model.toast = Some(Toast::info(format!("Reloaded configuration {path}")));
+        }
+        Err(error) => {
+            eprintln!("{error}");
+            model.toast = Some(Toast::error(error));
+        }
+    }
+}
+
+/// Carry the agents over from a configuration to the one replacing it.
+fn reconfigure_agents(model: &mut Model, previous: &Config, config: &Config) {
+    // Agents of the species left out of the configuration leave the flock
+    let count = config.species.len();
+    if count < previous.species.len() {
+        model.plane.agents.retain_mut(|agent| agent.species < count);
+        model.agents_3d.retain(|agent| agent.species < count);
+        model.surface_agents.retain(|agent| agent.species < count);
+    }
+    model.plane.agents.for_each_mut(|agent| {
+        agent
+            .parameters
+            .reconfigure(agent.species, previous, config);
+    });
+}
+
+/// Set the parameters keyframed in the configuration to their value at this
+/// time of the simulation.
+fn follow_timeline(model: &mut Model) {
+    let config = Config::get();
+    if config.timeline.is_empty() {
+        return;
+    }
+    let time = model.timestep.time();
+    let (previous, config) = Config::set(config.timeline.at(&config, time));
+    reconfigure_agents(model, &previous, &config);
+}
+
+/// Adjust the selected parameter by a number of steps, showing its new value.
+fn tune(model: &mut Model, steps: f32) {
+    let (previous, config) = Config::set(model.tunable.adjust(&Config::get(), steps));
+    reconfigure_agents(model, &previous, &config);
+
+    let value = model.tunable.value(&config);
+    model.toast = Some(Toast::brief(format!(
+        "{}: {value:.3}",
+        model.tunable.name()
+    )));
+}
+
+fn show_trail_length(model: &mut Model) {
+    let length = model.trails.length();
+    model.toast = Some(Toast::brief(format!("trail length: {length:.0} frames")));
+}
+
+/// Switch to the next performance profile, resizing the flock and rebuilding the
+/// trails and neighbor index to suit it.
+fn next_profile(app: &App, model: &mut Model) {
+    let profile = model.profile.map_or(Profile::Low, Profile::next);
+    model.profile = Some(profile);
+
+    let mut config = (*Config::get()).clone();
+    config.scale_agents(profile.agents());
+    let (previous, config) = Config::set(config);
+    reconfigure_agents(model, &previous, &config);
+    model
+        .plane
+        .density
+        .hold(profile.agents(), app.window_rect());
+
+    model.plane.pheromones = Pheromones::new(app.window_rect(), profile.trail_cell_size());
+    model.plane.indexing = profile.indexing();
+    model.plane.index = model.plane.indexing.build(&model.plane.agents);
+    model.toast = Some(Toast::info(format!(
+        "Performance profile: {}",
+        profile.name()
+    )));
+}
+
+/// Jump to parameters drawn within the configured bounds, showing them and
+/// appending them to the history file.
+fn randomize(model: &mut Model) {
+    let config = Config::get();
+    let (previous, config) = Config::set(config.randomize.draw(&config, &mut model.rng));
+    reconfigure_agents(model, &previous, &config);
+
+    let values: Vec<String> = Tunable::ALL
+        .iter()
+        .map(|tunable| format!("{}: {:.3}", tunable.name(), tunable.value(&config)))
+        .collect();
+    model.toast = Some(match Bounds::record(&config) {
+        Ok(()) => Toast::info(format!("Randomized {}", values.join(", "))),
+        Err(error) => {
+            eprintln!("{error}");
+            Toast::error(error)
+        }
+    });
+}
+
+/// Save the live configuration, with the seed and number of agents of every species,
+/// to a timestamped TOML file next to the saved pngs.
+fn save_parameters(app: &App, model: &mut Model) {
+    let mut config = (*Config::get()).clone();
+    config.seed = Some(model.seed);
+    for (species, settings) in config.species.iter_mut().enumerate() {
+        settings.count = model
+            .plane
+            .agents
+            .species
+            .iter()
+            .filter(|&&other| other == species)
+            .count();
+    }
+
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |since| since.as_secs());
+    let path = format!("{}-{timestamp}.toml", app.exe_name().unwrap());
+    model.toast = Some(match config.save(&path) {
+        Ok(()) => Toast::info(format!("Saved parameters to {path}")),
+        Err(error) => Toast::error(error),
+    });
+}
+
+/// Load the preset in a slot, or save the current parameters into it.
+fn use_preset(model: &mut Model, slot: usize, save: bool) {
+    let toast = if save {
+        match model.presets.save(slot, Config::get().agent) {
+            Ok(preset) => Toast::info(format!("Saved preset {slot}: {}", preset.name)),
+            Err(error) => Toast::error(error),
+        }
+    } else {
+        match model.presets.get(slot) {
+            Some(preset) => {
+                let toast = Toast::info(format!("Loaded preset {slot}: {}", preset.name));
+                let (previous, config) = Config::set(Config {
+                    agent: preset.agent,
+                    ..(*Config::get()).clone()
+                });
+                reconfigure_agents(model, &previous, &config);
+                toast
+            }
+            None => Toast::error(format!("No preset saved in slot {slot}")),
+        }
+    };
+    model.toast = Some(toast);
+}
+
+fn update_space(model: &mut Model, time: f32) {
+    // Slowly orbit the camera to reveal the depth of the flock
+    model.camera.yaw = time * Model::CAMERA_ORBIT_SPEED;
+
+    let config = Config::get();
+    model.previous_agents_3d.clone_from(&model.agents_3d);
+    model
+        .agents_3d
+        .iter_mut()
+        .for_each(|agent| agent.update(&model.bounds_3d, &model.previous_agents_3d, &config));
+}
+
+fn update_surface(model: &mut Model, time: f32) {
+    // Slowly spin the globe
+    model.camera.yaw = time * Model::CAMERA_ORBIT_SPEED;
+
+    let config = Config::get();
+    let surface = model.surface();
+    model
+        .previous_surface_agents
+        .clone_from(&model.surface_agents);
+    model
+        .surface_agents
+        .iter_mut()
+        .for_each(|agent| agent.update(&surface, &model.previous_surface_agents, &config));
+}
+
+fn view(app: &App, model: &Model, frame: Frame) {
+    // Save every frame when recording
+    if Args::get().record {
+        app.main_window()
+            .capture_frame(format!("frames/{:05}.png", frame.nth()));
+    }
+
+    if let Some(gpu) = &model.gpu {
+        gpu.display(&frame);
+        return;
+    }
+    if let Some(sim_thread) = &model.sim_thread {
+        let draw = app.draw();
+        draw.background().color(BLACK);
+        sim_thread.display(&draw);
+        draw.to_frame(app, &frame).unwrap();
+        return;
+    }
+    if let Some(precision) = &model.precision {
+        let draw = app.draw();
+        draw.background().color(BLACK);
+        precision.display(&draw, &mut model.sketch.borrow_mut(), &Config::get());
+        draw.to_frame(app, &frame).unwrap();
+        return;
+    }
+
+    // Begin drawing, with the configuration read once for the whole frame
+    let start = Instant::now();
+    let draw = app.draw();
+    let config = Config::get();
+
+    // Clear the background to black, or to the color of the sky, or only veil the
+    // last frame with it so agents leave fading trails
+    let background = if model.day_cycle {
+        model.day_night.background()
+    } else {
+        rgba(0.0, 0.0, 0.0, 1.0)
+    };
+    let mut agents = model.drawn_agents.borrow_mut();
+    let blurred = model.motion_blur.borrow().enabled && model.domain == Domain::Plane;
+    // Motion blur writes the frame itself, so the glow is left out while it is on
+    let glowing = model.bloom.borrow().enabled && !blurred;
+    let batched = batched(model) && !blurred && !glowing;
+    // Draw the gradient or image of the configuration over the background, as
+    // faint as the veil when trails are left, unless the sky is colored
+    let window = app.main_window();
+    let mut backdrop = model.backdrop.borrow_mut();
+    let mut display_backdrop = |draw: &Draw, opacity: f32| {
+        if !model.day_cycle {
+            backdrop.display(draw, &window, &config.background, opacity);
+        }
+    };
+    if blurred {
+        // Average the plane over instants between the last two steps, drawing the
+        // overlays over it only once
+        let mut motion_blur = model.motion_blur.borrow_mut();
+        for (i, alpha) in MotionBlur::alphas().enumerate() {
+            let sample = Draw::new();
+            sample.background().color(background);
+            display_backdrop(&sample, 1.0);
+            interpolate_agents(model, alpha, &mut agents);
+            view_plane(app, &sample, model, &agents, false, &config);
+            motion_blur.accumulate(&window, &frame, &sample, i == 0);
+        }
+        motion_blur.display(&frame);
+    } else {
+        if model.trails.enabled && frame.nth() > 0 {
+            model.trails.display(&draw, app.window_rect(), background);
+            display_backdrop(&draw, model.trails.fade());
+        } else {
+            draw.background().color(background);
+            display_backdrop(&draw, 1.0);
+        }
+
+        // Agents are drawn between the last two steps of the simulation
+        interpolate_agents(model, model.timestep.alpha(), &mut agents);
+        match model.domain {
+            Domain::Plane => view_plane(app, &draw, model, &agents, batched, &config),
+            Domain::Space => view_space(&draw, model, &config),
+            Domain::Sphere | Domain::Torus => view_surface(&draw, model, &config),
+        }
+
+        // Draw the scene with its glow, drawing the overlays over it afterwards
+        if glowing {
+            model.bloom.borrow_mut().display(&window, &frame, &draw);
+            draw.reset();
+        }
+    }
+
+    // Draw how long the stages of the frame take
+    if model.show_profiler {
+        model.profiler.display(&draw, app.window_rect());
+    }
+
+    // Draw the outcome of the last configuration reload
+    if let Some(toast) = &model.toast {
+        toast.display(&draw, app.window_rect());
+    }
+
+    // Draw the seed the run can be reproduced from
+    let area = app.window_rect().pad(20.0);
+    draw.text(&format!("seed {}", model.seed))
+        .xy(area.xy())
+        .wh(area.wh())
+        .right_justify()
+        .align_text_bottom()
+        .font_size(12)
+        .color(rgba(1.0, 1.0, 1.0, 0.4));
+
+    // Write the result of our drawing to the window's frame.
+    draw.to_frame(app, &frame).unwrap();
+
+    // Draw the agents over it in a single call, as sprites or triangles
+    if model.domain == Domain::Plane && batched {
+        let mut instances = model.instances.borrow_mut();
+        instances.clear();
+        instances.extend(
+            agents
+                .iter()
+                .map(|agent| agent.instance(agent.color(model.plane.color_mode, &config), &config)),
+        );
+        match &model.sprites {
+            Some(sprites) => sprites
+                .borrow_mut()
+                .display(&app.main_window(), &frame, &instances),
+            None => model
+                .instancing
+                .borrow_mut()
+                .display(&app.main_window(), &frame, &instances),
+        }
+    }
+
+    // Draw the control panel over everything
+    model.egui.draw_to_frame(&frame).unwrap();
+    model.profiler.record(Stage::Draw, start);
+}
+
+/// Agents a fraction of the way from the last step to the next one.
+fn interpolate_agents(model: &Model, alpha: f32, agents: &mut Vec<Agent>) {
+    agents.clear();
+    agents.extend(model.plane.agents.iter().enumerate().map(|(i, agent)| {
+        if i < model.plane.previous_agents.len() {
+            agent.interpolate(&model.plane.previous_agents.get(i), alpha)
+        } else {
+            agent
+        }
+    }));
+}
+
+/// Whether the agents on the plane are drawn in a single call after everything else.
+fn batched(model: &Model) -> bool {
+    model.instanced || model.sprites.is_some()
+}
+
+/// Draw the plane, the agents only showing their leader circles when they are drawn
+/// in a single call afterwards.
+fn view_plane(
+    app: &App,
+    draw: &Draw,
+    model: &Model,
+    agents: &[Agent],
+    batched: bool,
+    config: &Config,
+) {
+    let time = model.timestep.time();
+
+    // Draw the margin agents steer back from
+    model.plane.boundary.display(
+        draw,
+        &app.window_rect(),
+        model.plane.margin,
+        Boundary::MARGIN_COLOR.into(),
+    );
+
+    // Draw terrain
+    if let Some(terrain) = &model.plane.terrain {
+        terrain.display(draw, Terrain::COLOR);
+    }
+
+    // Draw flow field
+    if model.show_flow_field {
+        model
+            .plane
+            .flow_field
+            .display(draw, app.window_rect(), time, FlowField::COLOR.into());
+    }
+
+    // Draw how the flock flows
+    if model.velocity_field.enabled {
+        model
+            .velocity_field
+            .display(draw, VelocityField::COLOR.into());
+    }
+
+    // Draw the density of the agents
+    if model.heatmap.enabled {
+        model.heatmap.display(draw);
+    }
+
+    // Draw pheromone trails
+    if model.plane.follow_pheromones {
+        model.plane.pheromones.display(draw, Pheromones::COLOR);
+    }
+
+    // Draw path
+    if model.plane.follow_path {
+        model.plane.path.display(draw, Path::COLOR.into());
+    }
+
+    // Draw waypoints
+    if model.plane.follow_waypoints {
+        model.plane.waypoints.display(draw, Waypoints::COLOR.into());
+    }
+
+    // Draw vortices
+    model.plane.vortices.iter().for_each(|vortex| {
+        vortex.display(draw, Vortex::COLOR.into());
+    });
+
+    // Draw force points
+    model.plane.force_points.iter().for_each(|point| {
+        point.display(draw);
+    });
+
+    // Draw food
+    if model.plane.forage {
+        model.plane.food.display(draw, Food::COLOR.into());
+    }
+
+    // Draw obstacles
+    model.plane.obstacles.iter().for_each(|obstacle| {
+        obstacle.display(draw, Obstacle::COLOR.into());
+    });
+    model.plane.regions.iter().for_each(|region| {
+        region.display(draw, Region::COLOR.into());
+    });
+
+    // Draw walls, including the one being drawn
+    model.plane.walls.iter().for_each(|wall| {
+        wall.display(draw, Wall::COLOR.into());
+    });
+    if let Some(start) = model.wall_start {
+        Wall::new(start, app.mouse.position()).display(draw, Wall::COLOR.into());
+    }
+
+    // Draw the lines between agents perceiving each other behind them
+    let mut plexus = model.plexus.borrow_mut();
+    if plexus.enabled {
+        plexus.display(draw, agents, Plexus::COLOR);
+    }
+
+    // Draw agents, only their leader circles when drawn in a single call
+    let mut sketch = model.sketch.borrow_mut();
+    sketch.reset(agents.len());
+    agents.iter().for_each(|agent| {
+        let color = agent.color(model.plane.color_mode, config);
+        if batched {
+            agent.display_leader(draw, color, config);
+        } else {
+            agent.display(draw, color, &mut sketch, config);
+        }
+    });
+    sketch.display(draw);
+
+    // Draw the radii and flocking forces of the agents
+    model
+        .debug_overlay
+        .display(draw, agents, app.mouse.position(), config);
+
+    // Draw predators
+    model.plane.predators.iter().for_each(|predator| {
+        predator.display(draw, Predator::COLOR.into());
+    });
+
+    // Draw calls
+    model.plane.calls.iter().for_each(|call| {
+        call.display(draw, Call::COLOR.into());
+    });
+
+    // Draw the shockwaves of hawk strikes
+    model.plane.strikes.iter().for_each(|strike| {
+        strike.display(draw, Strike::COLOR.into());
+    });
+
+    // Draw the moving attractor
+    model.plane.attractor.display(draw, app.window_rect(), time);
+
+    // Draw the direction of migration
+    if model.plane.migrate {
+        Migration::display(draw, app.window_rect(), time, Migration::COLOR.into());
+    }
+
+    // Draw the reach of the cursor
+    model.plane.mouse_force.display(draw, app.mouse.position());
+
+    // Draw the radii of the zone model
+    if model.plane.brain == Brain::Zones {
+        model
+            .plane
+            .zones
+            .display(draw, app.window_rect(), Zones::COLOR.into());
+    }
+
+    // Draw the order of the flock under noise
+    if model.plane.vicsek.noise > 0.0 {
+        model.plane.vicsek.display(
+            draw,
+            app.window_rect(),
+            &model.plane.agents,
+            Vicsek::COLOR.into(),
+        );
+    }
+
+    // Draw the opening angle of long-range cohesion
+    if model.plane.long_range_cohesion {
+        model
+            .plane
+            .long_range
+            .display(draw, app.window_rect(), LongRange::COLOR.into());
+    }
+
+    // Draw flashes of flock events
+    model.plane.flashes.iter().for_each(|flash| {
+        flash.display(draw, app.window_rect());
+    });
+
+    // Draw the number of flocks
+    if model.plane.color_mode == ColorMode::Flock {
+        model
+            .plane
+            .flocks
+            .display(draw, app.window_rect(), Flocks::COLOR.into());
+    }
+
+    // Draw the time of day
+    if model.day_cycle {
+        model
+            .day_night
+            .display(draw, app.window_rect(), DayNight::TEXT_COLOR.into());
+    }
+
+    // Draw the progress of evolution
+    if let Some(evolution) = &model.plane.evolution {
+        evolution.display(draw, app.window_rect(), Evolution::COLOR.into());
+    }
+
+    // Draw the number of agents the frame rate allows
+    if let Some(governor) = &model.governor {
+        governor.display(
+            draw,
+            app.window_rect(),
+            model.plane.agents.len(),
+            Governor::COLOR.into(),
+        );
+    }
+
+    // Draw the subdivision of the neighbor index and how long the agents took to update
+    if model.show_index {
+        model.plane.index.display(draw, Indexing::COLOR.into());
+        model.plane.indexing.display(
+            draw,
+            app.window_rect(),
+            model.plane.update_time,
+            Indexing::COLOR.into(),
+        );
+    }
+}
+
+fn view_space(draw: &Draw, model: &Model, config: &Config) {
+    // Draw the edges of the volume
+    model
+        .camera
+        .display_cuboid(draw, &model.bounds_3d, Model::BOUNDS_COLOR.into());
+
+    // Draw agents from back to front so closer agents are drawn on top
+    let mut order = model.depth_order.borrow_mut();
+    let agents = &model.agents_3d;
+    model
+        .camera
+        .back_to_front(agents.iter().map(|agent| agent.position), &mut order);
+    let mut sketch = model.sketch.borrow_mut();
+    sketch.reset(agents.len());
+    order
+        .iter()
+        .for_each(|&(i, _)| agents[i].display(draw, &model.camera, &mut sketch, config));
+    sketch.display(draw);
+}
+
+fn view_surface(draw: &Draw, model: &Model, config: &Config) {
+    // Draw the grid of the surface
+    model
+        .surface()
+        .display(draw, &model.camera, Model::BOUNDS_COLOR.into());
+
+    // Draw agents from back to front so closer agents are drawn on top
+    let mut order = model.depth_order.borrow_mut();
+    let agents = &model.surface_agents;
+    model
+        .camera
+        .back_to_front(agents.iter().map(|agent| agent.position), &mut order);
+    let mut sketch = model.sketch.borrow_mut();
+    sketch.reset(agents.len());
+    order
+        .iter()
+        .for_each(|&(i, _)| agents[i].display(draw, &model.camera, &mut sketch, config));
+    sketch.display(draw);
+}
+
+fn raw_window_event(_app: &App, model: &mut Model, event: &WindowEvent) {
+    model.egui.handle_raw_event(event);
+}
+
+fn key_released(app: &App, model: &mut Model, key: Key) {
+    // Keys typed into the control panel are meant for it
+    if model.egui.ctx().wants_keyboard_input() {
+        return;
+    }
+
+    let slots = [
+        Key::Key1,
+        Key::Key2,
+        Key::Key3,
+        Key::Key4,
+        Key::Key5,
+        Key::Key6,
+        Key::Key7,
+        Key::Key8,
+        Key::Key9,
+    ];
+    if let Some(slot) = slots.iter().position(|slot| *slot == key) {
+        use_preset(model, slot + 1, app.keys.mods.shift());
+        return;
+    }
+
+    match key {
+        Key::Q => app.quit(),
+        Key::A if app.keys.mods.shift() => {
+            let mut sketch = model.sketch.borrow_mut();
+            sketch.silhouette.animated = !sketch.silhouette.animated;
+        }
+        Key::A => model.plane.aging = !model.plane.aging,
+        Key::B if app.keys.mods.shift() => {
+            let mut motion_blur = model.motion_blur.borrow_mut();
+            motion_blur.enabled = !motion_blur.enabled;
+        }
+        Key::B => model.plane.boundary = model.plane.boundary.next(),
+        Key::C if app.keys.mods.shift() => {
+            model.plane.long_range_cohesion = !model.plane.long_range_cohesion
+        }
+        Key::C => model.plane.color_mode = model.plane.color_mode.next(),
+        Key::D if app.keys.mods.shift() => model.instanced = !model.instanced,
+        Key::D => {
+            model.domain = match model.domain {
+                Domain::Plane => Domain::Space,
+                Domain::Space => Domain::Sphere,
+                Domain::Sphere => Domain::Torus,
+                Domain::Torus => Domain::Plane,
+            };
+
+            // Populate the new surface
+            if matches!(model.domain, Domain::Sphere | Domain::Torus) {
+                let config = Config::get();
+                let surface = model.surface();
+                model.surface_agents.clear();
+                for (species, Species { count, .. }) in config.species.iter().enumerate() {
+                    model
+                        .surface_agents
+                        .extend((0..*count).map(|_| {
+                            SurfaceAgent::new(&surface, species, &config, &mut model.rng)
+                        }));
+                }
+            }
+        }
+        Key::E if app.keys.mods.shift() => model.plane.pursuit = model.plane.pursuit.next(),
+        Key::E => model.plane.ecosystem = !model.plane.ecosystem,
+        Key::F if app.keys.mods.shift() => model.plane.flapping = !model.plane.flapping,
+        Key::F => model.show_flow_field = !model.show_flow_field,
+        Key::G if app.keys.mods.shift() => {
+            let bloom = model.bloom.get_mut();
+            bloom.enabled = !bloom.enabled;
+        }
+        Key::G => {
+            model.plane.evolution = match model.plane.evolution {
+                Some(_) => None,
+                None => Some(Evolution::new(model.plane.agents.len())),
+            }
+        }
+        Key::H if app.keys.mods.shift() => next_profile(app, model),
+        Key::H => model.plane.collisions = !model.plane.collisions,
+        Key::I if app.keys.mods.shift() => model.plane.zones = model.plane.zones.next(),
+        Key::I => model.plane.brain = model.plane.brain.next(),
+        Key::J => model.plane.forage = !model.plane.forage,
+        Key::K if app.keys.mods.shift() => model.plane.perception = model.plane.perception.next(),
+        Key::K => model.plane.kernel = model.plane.kernel.next(),
+        Key::L if app.keys.mods.shift() => model
+            .plane
+            .agents
+            .for_each_mut(|agent| agent.leader = false),
+        Key::L => {
+            // Leadership passes on to the agent following the current leader
+            let next = model
+                .plane
+                .agents
+                .iter()
+                .position(|agent| agent.leader)
+                .map_or(0, |leader| leader + 1)
+                % model.plane.agents.len().max(1);
+            for i in 0..model.plane.agents.len() {
+                model
+                    .plane
+                    .agents
+                    .change(i, |agent| agent.leader = i == next);
+            }
+        }
+        Key::M if app.keys.mods.shift() => model.plane.attractor.next(),
+        Key::M => model.plane.mouse_force = model.plane.mouse_force.next(),
+        Key::N if app.keys.mods.shift() => {
+            model.plane.cache_neighbors = !model.plane.cache_neighbors;
+            model.plane.neighbor_cache.clear();
+        }
+        Key::N => model.day_cycle = !model.day_cycle,
+        Key::Tab if app.keys.mods.shift() => model.show_index = !model.show_index,
+        Key::Tab => {
+            model.plane.indexing = model.plane.indexing.next();
+            model.plane.index = model.plane.indexing.build(&model.plane.agents);
+        }
+        Key::F1 => model.debug_overlay = model.debug_overlay.next(),
+        Key::F2 if app.keys.mods.shift() => {
+            model.heatmap.colormap = model.heatmap.colormap.next();
+            model.toast = Some(Toast::brief(format!(
+                "heatmap colormap: {}",
+                model.heatmap.colormap.name()
+            )));
+        }
+        Key::F2 => model.heatmap.enabled = !model.heatmap.enabled,
+        Key::F3 => model.velocity_field.enabled = !model.velocity_field.enabled,
+        Key::F4 => {
+            let plexus = model.plexus.get_mut();
+            plexus.enabled = !plexus.enabled;
+        }
+        Key::Key0 => {
+            model.tunable = model.tunable.next();
+            tune(model, 0.0);
+        }
+        Key::Left if app.keys.mods.shift() => {
+            model.trails.shorten();
+            show_trail_length(model);
+        }
+        Key::Right if app.keys.mods.shift() => {
+            model.trails.lengthen();
+            show_trail_length(model);
+        }
+        Key::Left => tune(model, -1.0),
+        Key::Right => tune(model, 1.0),
+        Key::Grave => model.controls.visible = !model.controls.visible,
+        Key::Space => model.paused = !model.paused,
+        Key::Return => model
+            .plane
+            .attractor
+            .toggle_recording(model.timestep.time()),
+        Key::LBracket => model.plane.margin = Boundary::adjust_margin(model.plane.margin, -1.0),
+        Key::RBracket => model.plane.margin = Boundary::adjust_margin(model.plane.margin, 1.0),
+        Key::Up if app.keys.mods.shift() => model.plane.density.adjust(1.0),
+        Key::Down if app.keys.mods.shift() => model.plane.density.adjust(-1.0),
+        Key::Minus => model.plane.long_range.decrease(),
+        Key::Equals => model.plane.long_range.increase(),
+        Key::Up => model.plane.vicsek.increase(),
+        Key::Down => model.plane.vicsek.decrease(),
+        Key::Comma => model.day_night.skip(-DayNight::SKIP),
+        Key::Period => model.day_night.skip(DayNight::SKIP),
+        Key::O if app.keys.mods.shift() => model.plane.sort_agents = !model.plane.sort_agents,
+        Key::O => model.plane.migrate = !model.plane.migrate,
+        Key::P if app.keys.mods.shift() => model.plane.personalities = !model.plane.personalities,
+        Key::P => model.plane.follow_pheromones = !model.plane.follow_pheromones,
+        Key::U => {
+            if let Some(terrain) = &mut model.plane.terrain {
+                terrain.slope = terrain.slope.next();
+            }
+        }
+        Key::V if app.keys.mods.shift() => model.plane.integrator = model.plane.integrator.next(),
+        Key::V => model.plane.formation = !model.plane.formation,
+        Key::W if app.keys.mods.shift() => model.trails.enabled = !model.trails.enabled,
+        Key::W => model.plane.follow_waypoints = !model.plane.follow_waypoints,
+        Key::T if app.keys.mods.shift() => model.show_profiler = !model.show_profiler,
+        Key::T => {
+            model.tool = match model.tool {
+                Tool::Startle => Tool::Strike,
+                Tool::Strike => Tool::GravityWell,
+                Tool::GravityWell => Tool::Vortex,
+                Tool::Vortex => Tool::Wall,
+                Tool::Wall => Tool::Startle,
+            }
+        }
+        Key::Delete => {
+            model.plane.force_points.clear();
+            model.plane.vortices.clear();
+            model.plane.walls = model.outlines.clone();
+        }
+        Key::X if app.keys.mods.shift() => model.plane.banking = !model.plane.banking,
+        Key::X => model.plane.movement = model.plane.movement.next(),
+        Key::R if app.keys.mods.shift() => randomize(model),
+        Key::R => model.plane.avoidance = model.plane.avoidance.next(),
+        Key::Y => model.plane.calling = !model.plane.calling,
+        Key::Z => model.plane.follow_path = !model.plane.follow_path,
+        Key::S if app.keys.mods.shift() => save_parameters(app, model),
+        Key::S => {
+            app.main_window()
+                .capture_frame(app.exe_name().unwrap() + ".png");
+        }
+        _other_key => {}
+    }
+}
+
+fn mouse_pressed(app: &App, model: &mut Model, button: MouseButton) {
+    // Clicks on the control panel are meant for it
+    if button != MouseButton::Left || model.egui.ctx().wants_pointer_input() {
+        return;
+    }
+
+    let mouse = app.mouse.position();
+    match model.tool {
+        Tool::Startle => {
+            // Startle the agents around the cursor, panic spreads from there
+            for i in 0..model.plane.agents.len() {
+                if model.plane.agents.positions[i].distance(mouse) < Model::STARTLE_RADIUS {
+                    model.plane.agents.change(i, Agent::startle);
+                }
+            }
+        }
+        Tool::Strike => model
+            .plane
+            .strikes
+            .push(Strike::new(mouse, &mut model.plane.agents)),
+        Tool::GravityWell if app.keys.mods.shift() => {
+            model.plane.force_points.push(ForcePoint::repulsor(mouse))
+        }
+        Tool::GravityWell => model.plane.force_points.push(ForcePoint::attractor(mouse)),
+        Tool::Vortex if app.keys.mods.shift() => {
+            model.plane.vortices.push(Vortex::clockwise(mouse))
+        }
+        Tool::Vortex => model.plane.vortices.push(Vortex::counter_clockwise(mouse)),
+        Tool::Wall => model.wall_start = Some(mouse),
+    }
+}
+
+fn mouse_released(app: &App, model: &mut Model, button: MouseButton) {
+    // Finish the wall being drawn
+    if button == MouseButton::Left {
+        if let Some(start) = model.wall_start.take() {
+            model
+                .plane
+                .walls
+                .push(Wall::new(start, app.mouse.position()));
+        }
+    }
+}
@@ -0,0 +1,37 @@
+use nannou::prelude::*;
+
+/// Distant target slowly orbiting the window, drawing the flock across the screen.
+pub struct Migration;
+
+impl Migration {
+    pub const COLOR: (f32, f32, f32, f32) = (0.9, 0.8, 0.4, 0.8);
+
+    /// Seconds for the target to complete an orbit
+    const PERIOD: f32 = 300.0;
+
+    /// Distance of the target from the center relative to the size of the window
+    const DISTANCE: f32 = 1.5;
+
+    const ARROW_LENGTH: f32 = 40.0;
+
+    /// Position of the target at a given time.
+    pub fn target(win_rect: Rect, time: f32) -> Vec2 {
+        let angle = time / Self::PERIOD * TAU;
+        win_rect.xy() + vec2(angle.cos(), angle.sin()) * win_rect.wh() * Self::DISTANCE
+    }
+
+    /// Draw an arrow at the edge of the window pointing towards the target.
+    pub fn display(draw: &Draw, win_rect: Rect, time: f32, color: Rgba) {
+        let direction = (Self::target(win_rect, time) - win_rect.xy()).normalize_or_zero();
+
+        // Scale the direction so the arrow ends on the edge of the window
+        let half = win_rect.wh() * 0.5;
+        let scale = (half.x / direction.x.abs()).min(half.y / direction.y.abs());
+        let end = win_rect.xy() + direction * scale;
+        draw.arrow()
+            .start(end - direction * Self::ARROW_LENGTH)
+            .end(end)
+            .weight(3.0)
+            .color(color);
+    }
+}
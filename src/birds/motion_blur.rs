@@ -0,0 +1,151 @@
+use nannou::draw::{Renderer, RendererBuilder};
+use nannou::prelude::*;
+
+/// Frames averaged over several instants between the last two steps, blurring fast
+/// agents along their motion as an open camera shutter would.
+///
+/// Every sample is drawn into a texture of its own, then added into a floating
+/// point texture with its share of the average, which is finally written to the
+/// frame.
+pub struct MotionBlur {
+    pub enabled: bool,
+    renderer: Renderer,
+    sample: wgpu::Texture,
+    accumulation: wgpu::Texture,
+    bind_group: wgpu::BindGroup,
+    pipeline: wgpu::RenderPipeline,
+    reshaper: wgpu::TextureReshaper,
+}
+
+impl MotionBlur {
+    /// Instants averaged into every frame
+    pub const SAMPLES: usize = 8;
+    /// Format of the samples and their sum, precise enough to add small shares
+    const FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba16Float;
+
+    pub fn new(window: &Window) -> Self {
+        let device = window.device();
+        let size = window.inner_size_pixels();
+        let size = [size.0.max(1), size.1.max(1)];
+        let texture = || {
+            wgpu::TextureBuilder::new()
+                .size(size)
+                .format(Self::FORMAT)
+                .usage(
+                    wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+                )
+                .build(device)
+        };
+        let (sample, accumulation) = (texture(), texture());
+        let renderer =
+            RendererBuilder::new().build(device, size, window.scale_factor(), 1, Self::FORMAT);
+
+        let module = device.create_shader_module(wgpu::include_wgsl!("shaders/accumulate.wgsl"));
+        let layout = wgpu::BindGroupLayoutBuilder::new()
+            .texture(
+                wgpu::ShaderStages::FRAGMENT,
+                false,
+                wgpu::TextureViewDimension::D2,
+                wgpu::TextureSampleType::Float { filterable: false },
+            )
+            .build(device);
+        let bind_group = wgpu::BindGroupBuilder::new()
+            .texture_view(&sample.view().build())
+            .build(device, &layout);
+
+        // Samples are added in, each weighed by the blend constant
+        let add = wgpu::BlendComponent {
+            src_factor: wgpu::BlendFactor::Constant,
+            dst_factor: wgpu::BlendFactor::One,
+            operation: wgpu::BlendOperation::Add,
+        };
+        let pipeline_layout =
+            wgpu::create_pipeline_layout(device, Some("accumulate"), &[&layout], &[]);
+        let pipeline = wgpu::RenderPipelineBuilder::from_layout(&pipeline_layout, &module)
+            .vertex_entry_point("vs_main")
+            .fragment_shader(&module)
+            .fragment_entry_point("fs_main")
+            .color_format(Self::FORMAT)
+            .color_blend(add)
+            .alpha_blend(add)
+            .build(device);
+
+        let reshaper = wgpu::TextureReshaper::new(
+            device,
+            &accumulation.view().build(),
+            1,
+            accumulation.sample_type(),
+            window.msaa_samples(),
+            Frame::TEXTURE_FORMAT,
+        );
+
+        MotionBlur {
+            enabled: false,
+            renderer,
+            sample,
+            accumulation,
+            bind_group,
+            pipeline,
+            reshaper,
+        }
+    }
+
+    /// Instants sampled, as fractions of the way from the last step to the next one.
+    pub fn alphas() -> impl Iterator<Item = f32> {
+        (1..=Self::SAMPLES).map(|i| i as f32 / Self::SAMPLES as f32)
+    }
+
+    /// Add the drawing of a sample to the average, starting a new average with the
+    /// first sample of a frame.
+    pub fn accumulate(&mut self, window: &Window, frame: &Frame, draw: &Draw, first: bool) {
+        // Start over at the new size when the window is resized
+        let size = window.inner_size_pixels();
+        if [size.0, size.1] != self.sample.size() {
+            *self = MotionBlur {
+                enabled: self.enabled,
+                ..Self::new(window)
+            };
+        }
+
+        let device = window.device();
+        let mut encoder = frame.command_encoder();
+        let view = self.sample.view().build();
+        self.renderer.encode_render_pass(
+            device,
+            &mut encoder,
+            draw,
+            window.scale_factor(),
+            self.sample.size(),
+            &view,
+            None,
+        );
+
+        let accumulation = self.accumulation.view().build();
+        let mut pass = wgpu::RenderPassBuilder::new()
+            .color_attachment(&accumulation, |color| {
+                color.load_op(if first {
+                    wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT)
+                } else {
+                    wgpu::LoadOp::Load
+                })
+            })
+            .begin(&mut encoder);
+        let weight = 1.0 / Self::SAMPLES as f64;
+        pass.set_blend_constant(wgpu::Color {
+            r: weight,
+            g: weight,
+            b: weight,
+            a: weight,
+        });
+        pass.set_pipeline(&self.pipeline);
+        pass.set_bind_group(0, &self.bind_group, &[]);
+        pass.draw(0..3, 0..1);
+    }
+
+    /// Write the average of the samples to the frame.
+    pub fn display(&self, frame: &Frame) {
+        let mut encoder = frame.command_encoder();
+        self.reshaper
+            .encode_render_pass(frame.texture_view(), &mut encoder);
+    }
+}
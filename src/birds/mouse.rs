@@ -0,0 +1,51 @@
+use nannou::prelude::*;
+
+/// Force the cursor exerts on the agents.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MouseForce {
+    Off,
+    Attract,
+    Repel,
+}
+
+impl MouseForce {
+    const ATTRACT_COLOR: (f32, f32, f32, f32) = (0.2, 0.8, 0.3, 0.6);
+    const REPEL_COLOR: (f32, f32, f32, f32) = (0.8, 0.2, 0.2, 0.6);
+
+    /// Distance at which the force has halved
+    const RADIUS: f32 = 150.0;
+
+    pub fn next(self) -> Self {
+        match self {
+            MouseForce::Off => MouseForce::Attract,
+            MouseForce::Attract => MouseForce::Repel,
+            MouseForce::Repel => MouseForce::Off,
+        }
+    }
+
+    /// Direction of the force at a position, its length falling off with the
+    /// distance to the cursor from 1 down to 0.
+    pub fn force(self, position: Vec2, mouse: Vec2) -> Vec2 {
+        let offset = mouse - position;
+        let falloff = 1.0 / (1.0 + offset.length_squared() / (Self::RADIUS * Self::RADIUS));
+        match self {
+            MouseForce::Off => Vec2::ZERO,
+            MouseForce::Attract => offset.normalize_or_zero() * falloff,
+            MouseForce::Repel => -offset.normalize_or_zero() * falloff,
+        }
+    }
+
+    pub fn display(self, draw: &Draw, mouse: Vec2) {
+        let color = match self {
+            MouseForce::Off => return,
+            MouseForce::Attract => Self::ATTRACT_COLOR,
+            MouseForce::Repel => Self::REPEL_COLOR,
+        };
+        draw.ellipse()
+            .xy(mouse)
+            .radius(Self::RADIUS)
+            .no_fill()
+            .stroke_weight(1.0)
+            .stroke(Rgba::from(color));
+    }
+}
@@ -0,0 +1,17 @@
+/// How the steering forces move agents.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Movement {
+    /// Agents always fly at their cruising speed, the forces only turning them
+    Classic,
+    /// Agents accelerate with the forces and their thrust, slowed down by drag
+    Physical,
+}
+
+impl Movement {
+    pub fn next(self) -> Self {
+        match self {
+            Movement::Classic => Movement::Physical,
+            Movement::Physical => Movement::Classic,
+        }
+    }
+}
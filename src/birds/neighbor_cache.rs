@@ -0,0 +1,132 @@
+use crate::agent::Agent;
+use crate::agents::Agents;
+use crate::args::Args;
+use crate::index::NeighborIndex;
+use rayon::prelude::*;
+use std::collections::HashMap;
+use std::ops::Range;
+
+/// Neighbor lists of every agent, gathered within a padded radius every few steps
+/// and reused in between, trading a little accuracy for speed in large flocks.
+#[derive(Debug, Default)]
+pub struct NeighborCache {
+    /// Lists of the agents in order, a run of agents per chunk
+    chunks: Vec<Chunk>,
+    /// Place of the list of every agent, by seed
+    places: HashMap<u64, usize>,
+    /// Seeds of the agents the lists index into, in order
+    seeds: Vec<u64>,
+    /// Steps the lists are reused for before being gathered again
+    refresh: u32,
+    /// Steps since the lists were gathered
+    age: u32,
+}
+
+/// Lists of a run of agents stored one after the other, so they share memory
+/// that only grows when the run has more neighbors than ever before.
+#[derive(Debug, Default)]
+struct Chunk {
+    indices: Vec<usize>,
+    /// Radius every list was gathered within, and where it starts and ends in the indices
+    lists: Vec<(f32, usize, usize)>,
+}
+
+impl NeighborCache {
+    const REFRESH: u32 = 5;
+
+    /// Extra distance the lists are gathered within, covering how far agents
+    /// may close in on each other before the next refresh
+    const PADDING: f32 = 20.0;
+
+    /// Agents whose lists are gathered together by a thread
+    const CHUNK: usize = 128;
+
+    /// Cache refreshed every number of steps given by `--neighbor-refresh <steps>`.
+    pub fn from_args() -> Self {
+        NeighborCache {
+            refresh: Args::get().neighbor_refresh.unwrap_or(Self::REFRESH).max(1),
+            ..Default::default()
+        }
+    }
+
+    /// Forget the lists, so they are gathered again on the next update.
+    pub fn clear(&mut self) {
+        self.chunks.clear();
+        self.places.clear();
+        self.seeds.clear();
+    }
+
+    /// Whether the next update gathers the lists again anyway, so the agents may be
+    /// reordered without wasting the lists.
+    pub fn due(&self) -> bool {
+        self.age + 1 >= self.refresh
+    }
+
+    /// Gather the lists again when they are old or the agents changed since,
+    /// refilling the memory of the lists already gathered.
+    pub fn update(&mut self, agents: &Agents, index: &dyn NeighborIndex) {
+        let changed = agents.seeds != self.seeds;
+        self.age += 1;
+        if !changed && self.age < self.refresh {
+            return;
+        }
+
+        self.age = 0;
+        if changed {
+            self.seeds.clone_from(&agents.seeds);
+            self.places.clear();
+            self.places.extend(
+                agents
+                    .seeds
+                    .iter()
+                    .enumerate()
+                    .map(|(place, &seed)| (seed, place)),
+            );
+        }
+        self.chunks
+            .resize_with(agents.len().div_ceil(Self::CHUNK), Default::default);
+        self.chunks
+            .par_iter_mut()
+            .enumerate()
+            .for_each(|(i, chunk)| {
+                let start = i * Self::CHUNK;
+                let run = start..(start + Self::CHUNK).min(agents.len());
+                chunk.gather(run, agents, index);
+            });
+    }
+
+    /// Indices of the agents possibly within a radius of an agent, if its list
+    /// covers that radius.
+    pub fn within(&self, agent: &Agent, radius: f32) -> Option<&[usize]> {
+        let place = *self.places.get(&agent.seed)?;
+        let chunk = &self.chunks[place / Self::CHUNK];
+        let (cached, start, end) = chunk.lists[place % Self::CHUNK];
+        (radius <= cached).then(|| &chunk.indices[start..end])
+    }
+}
+
+impl Chunk {
+    /// Gather the lists of a run of agents within their padded radius.
+    fn gather(&mut self, run: Range<usize>, agents: &Agents, index: &dyn NeighborIndex) {
+        self.indices.clear();
+        self.lists.clear();
+        for agent in run {
+            let position = agents.positions[agent];
+            let radius = agents.parameters(agent).detection_radius + NeighborCache::PADDING;
+            let start = self.indices.len();
+            index.within(position, radius, &mut self.indices);
+
+            // Keep the agents actually within the radius
+            let mut end = start;
+            for found in start..self.indices.len() {
+                let i = self.indices[found];
+                if agents.positions[i].distance_squared(position) < radius * radius {
+                    self.indices[end] = i;
+                    end += 1;
+                }
+            }
+            self.indices.truncate(end);
+            self.lists.push((radius, start, end));
+        }
+    }
+}
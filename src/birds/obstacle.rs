@@ -0,0 +1,64 @@
+use nannou::prelude::*;
+use nannou::rand::Rng;
+
+#[derive(Debug, Clone, Copy)]
+pub struct Obstacle {
+    pub position: Vec2,
+    pub radius: f32,
+}
+
+impl Obstacle {
+    pub const COLOR: (f32, f32, f32, f32) = (0.3, 0.3, 0.3, 1.0);
+    pub const NUM_OBSTACLES: usize = 5;
+
+    const MIN_RADIUS: f32 = 30.0;
+    const MAX_RADIUS: f32 = 80.0;
+
+    pub fn new(win_rect: Rect, rng: &mut impl Rng) -> Self {
+        // Random position and radius
+        let position = vec2(
+            rng.gen_range(win_rect.left()..win_rect.right()),
+            rng.gen_range(win_rect.bottom()..win_rect.top()),
+        );
+        let radius = rng.gen_range(Self::MIN_RADIUS..Self::MAX_RADIUS);
+
+        // Return new obstacle
+        Obstacle { position, radius }
+    }
+
+    /// Distance from a point to the edge of the obstacle, negative when inside.
+    pub fn distance(&self, point: Vec2) -> f32 {
+        self.position.distance(point) - self.radius
+    }
+
+    /// Check if the obstacle stands in the way of the segment between two points.
+    pub fn blocks(&self, start: Vec2, end: Vec2) -> bool {
+        let segment = end - start;
+        let t = ((self.position - start).dot(segment) / segment.length_squared().max(1e-6))
+            .clamp(0.0, 1.0);
+        (start + segment * t).distance_squared(self.position) < self.radius * self.radius
+    }
+
+    /// Distance along a ray of unit direction at which it enters the obstacle,
+    /// zero when the origin lies inside.
+    pub fn raycast(&self, origin: Vec2, direction: Vec2) -> Option<f32> {
+        let offset = origin - self.position;
+        let b = offset.dot(direction);
+        let c = offset.length_squared() - self.radius * self.radius;
+        if c <= 0.0 {
+            return Some(0.0);
+        }
+        let discriminant = b * b - c;
+        if b > 0.0 || discriminant < 0.0 {
+            return None;
+        }
+        Some(-b - discriminant.sqrt())
+    }
+
+    pub fn display(&self, draw: &Draw, color: Rgba) {
+        draw.ellipse()
+            .xy(self.position)
+            .radius(self.radius)
+            .color(color);
+    }
+}
@@ -0,0 +1,147 @@
+use crate::brain::Network;
+use crate::config::Config;
+use nannou::prelude::*;
+use nannou::rand::Rng;
+
+/// Normal distribution from which agent parameters are drawn.
+#[derive(Debug, Clone, Copy)]
+pub struct Distribution {
+    pub mean: f32,
+    pub deviation: f32,
+}
+
+impl Distribution {
+    pub const fn new(mean: f32, deviation: f32) -> Self {
+        Distribution { mean, deviation }
+    }
+
+    /// Draw a non-negative value within three deviations of the mean.
+    pub fn sample(&self, rng: &mut impl Rng) -> f32 {
+        self.sample_signed(rng).max(0.0)
+    }
+
+    /// Draw a value within three deviations of the mean.
+    pub fn sample_signed(&self, rng: &mut impl Rng) -> f32 {
+        // Box-Muller transform of two uniform samples
+        let (u, v) = (1.0 - rng.gen::<f32>(), rng.gen::<f32>());
+        let normal = (-2.0 * u.ln()).sqrt() * (TAU * v).cos();
+        self.mean + normal.clamp(-3.0, 3.0) * self.deviation
+    }
+}
+
+/// Flocking parameters of an individual agent.
+#[derive(Debug, Clone, Copy)]
+pub struct Parameters {
+    pub speed: f32,
+    pub detection_radius: f32,
+    pub separation_weight: f32,
+    pub alignment_weight: f32,
+    pub cohesion_weight: f32,
+    pub network: Network,
+}
+
+impl Parameters {
+    /// Multiplier of the species speed
+    const SPEED: Distribution = Distribution::new(1.0, 0.1);
+
+    // Deviations of the parameters from their configured averages
+    const DETECTION_RADIUS_DEVIATION: f32 = 10.0;
+    const SEPARATION_WEIGHT_DEVIATION: f32 = 0.3;
+    const ALIGNMENT_WEIGHT_DEVIATION: f32 = 0.2;
+    const COHESION_WEIGHT_DEVIATION: f32 = 0.2;
+
+    // Chance of each gene mutating, and the deviation relative to its value
+    const MUTATION_RATE: f32 = 0.2;
+    const MUTATION_SCALE: f32 = 0.15;
+
+    /// Draw the parameters of a new agent of a species.
+    pub fn random(config: &Config, species: usize, rng: &mut impl Rng) -> Self {
+        let species = &config.species[species];
+        let config = &config.agent;
+        let speed = species.speed * Self::SPEED.sample(rng);
+        let network = Network::random(rng);
+        let mut sample = |mean: f32, deviation: f32| Distribution::new(mean, deviation).sample(rng);
+        Parameters {
+            speed,
+            detection_radius: sample(config.detection_radius, Self::DETECTION_RADIUS_DEVIATION),
+            separation_weight: sample(
+                config.separation_weight * species.separation_weight,
+                Self::SEPARATION_WEIGHT_DEVIATION,
+            ),
+            alignment_weight: sample(
+                config.alignment_weight * species.alignment_weight,
+                Self::ALIGNMENT_WEIGHT_DEVIATION,
+            ),
+            cohesion_weight: sample(
+                config.cohesion_weight * species.cohesion_weight,
+                Self::COHESION_WEIGHT_DEVIATION,
+            ),
+            network,
+        }
+    }
+
+    /// Carry the parameters over to a new configuration, scaling the speed with the
+    /// species speed and shifting the rest by how much their averages changed.
+    pub fn reconfigure(&mut self, species: usize, previous: &Config, config: &Config) {
+        let (from_species, to_species) = (&previous.species[species], &config.species[species]);
+        if from_species.speed > 0.0 {
+            self.speed *= to_species.speed / from_species.speed;
+        }
+
+        let (from, to) = (&previous.agent, &config.agent);
+        for (gene, from, to) in [
+            (
+                &mut self.detection_radius,
+                from.detection_radius,
+                to.detection_radius,
+            ),
+            (
+                &mut self.separation_weight,
+                from.separation_weight * from_species.separation_weight,
+                to.separation_weight * to_species.separation_weight,
+            ),
+            (
+                &mut self.alignment_weight,
+                from.alignment_weight * from_species.alignment_weight,
+                to.alignment_weight * to_species.alignment_weight,
+            ),
+            (
+                &mut self.cohesion_weight,
+                from.cohesion_weight * from_species.cohesion_weight,
+                to.cohesion_weight * to_species.cohesion_weight,
+            ),
+        ] {
+            *gene = (*gene + to - from).max(0.0);
+        }
+    }
+
+    /// Child genome taking each gene from either parent.
+    pub fn crossover(&self, other: &Parameters, rng: &mut impl Rng) -> Self {
+        let network = self.network.crossover(&other.network, rng);
+        let mut pick = |a: f32, b: f32| if rng.gen() { a } else { b };
+        Parameters {
+            speed: pick(self.speed, other.speed),
+            detection_radius: pick(self.detection_radius, other.detection_radius),
+            separation_weight: pick(self.separation_weight, other.separation_weight),
+            alignment_weight: pick(self.alignment_weight, other.alignment_weight),
+            cohesion_weight: pick(self.cohesion_weight, other.cohesion_weight),
+            network,
+        }
+    }
+
+    /// Randomly perturb some of the genes.
+    pub fn mutate(&mut self, rng: &mut impl Rng) {
+        for gene in [
+            &mut self.speed,
+            &mut self.detection_radius,
+            &mut self.separation_weight,
+            &mut self.alignment_weight,
+            &mut self.cohesion_weight,
+        ] {
+            if rng.gen::<f32>() < Self::MUTATION_RATE {
+                *gene = Distribution::new(*gene, *gene * Self::MUTATION_SCALE).sample(rng);
+            }
+        }
+        self.network.mutate(rng);
+    }
+}
@@ -0,0 +1,98 @@
+use crate::args::Args;
+use nannou::prelude::*;
+use std::fs;
+
+/// Closed curve the flock flows along like a river.
+pub struct Path {
+    points: Vec<Vec2>,
+}
+
+impl Path {
+    pub const COLOR: (f32, f32, f32, f32) = (0.3, 0.7, 0.8, 0.6);
+    const NUM_CIRCLE_POINTS: usize = 64;
+
+    /// Distance from the path at which agents are pulled back at full strength
+    const WIDTH: f32 = 60.0;
+
+    /// Polyline read from the file given by `--path <file>`, one `x y` point per
+    /// line in window coordinates, or a circle filling the window by default.
+    pub fn from_args(win_rect: Rect) -> Self {
+        let points = Args::get()
+            .path
+            .as_ref()
+            .and_then(|file| {
+                fs::read_to_string(file)
+                    .map_err(|error| eprintln!("Could not read path {file}: {error}"))
+                    .ok()
+            })
+            .map(|text| parse(&text))
+            .filter(|points| points.len() >= 2);
+
+        match points {
+            Some(points) => Path { points },
+            None => Self::circle(win_rect),
+        }
+    }
+
+    fn circle(win_rect: Rect) -> Self {
+        let radius = win_rect.w().min(win_rect.h()) * 0.35;
+        let points = (0..Self::NUM_CIRCLE_POINTS)
+            .map(|i| {
+                let angle = i as f32 / Self::NUM_CIRCLE_POINTS as f32 * TAU;
+                win_rect.xy() + vec2(angle.cos(), angle.sin()) * radius
+            })
+            .collect();
+        Path { points }
+    }
+
+    /// Segments of the closed curve.
+    fn segments(&self) -> impl Iterator<Item = (Vec2, Vec2)> + '_ {
+        self.points
+            .iter()
+            .zip(self.points.iter().cycle().skip(1))
+            .map(|(start, end)| (*start, *end))
+    }
+
+    /// Direction to flow in at a position: along the closest segment, and back
+    /// towards the curve the further the position strays from it.
+    pub fn flow(&self, position: Vec2) -> Vec2 {
+        let closest = self
+            .segments()
+            .map(|(start, end)| {
+                let segment = end - start;
+                let t = ((position - start).dot(segment) / segment.length_squared().max(1e-6))
+                    .clamp(0.0, 1.0);
+                (start + segment * t, segment)
+            })
+            .min_by(|(a, _), (b, _)| {
+                a.distance_squared(position)
+                    .total_cmp(&b.distance_squared(position))
+            });
+
+        match closest {
+            Some((point, segment)) => {
+                let back = (point - position) / Self::WIDTH;
+                segment.normalize_or_zero() + back.clamp_length_max(1.0)
+            }
+            None => Vec2::ZERO,
+        }
+    }
+
+    pub fn display(&self, draw: &Draw, color: Rgba) {
+        let loop_points = self.points.iter().chain(self.points.first()).copied();
+        draw.polyline().weight(2.0).points(loop_points).color(color);
+    }
+}
+
+/// Points of a polyline written as one `x y` pair per line, skipping malformed lines.
+fn parse(text: &str) -> Vec<Vec2> {
+    text.lines()
+        .filter_map(|line| {
+            let mut coordinates = line.split_whitespace().map(str::parse::<f32>);
+            match (coordinates.next(), coordinates.next()) {
+                (Some(Ok(x)), Some(Ok(y))) => Some(vec2(x, y)),
+                _ => None,
+            }
+        })
+        .collect()
+}
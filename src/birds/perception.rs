@@ -0,0 +1,17 @@
+/// Which neighbors agents interact with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Perception {
+    /// All neighbors inside the detection radius
+    Metric,
+    /// A fixed number of nearest neighbors, however far they are
+    Topological,
+}
+
+impl Perception {
+    pub fn next(self) -> Self {
+        match self {
+            Perception::Metric => Perception::Topological,
+            Perception::Topological => Perception::Metric,
+        }
+    }
+}
@@ -0,0 +1,55 @@
+use nannou::prelude::*;
+use nannou::rand::Rng;
+
+/// Persistent traits setting an agent apart from the rest of its flock.
+#[derive(Debug, Clone, Copy)]
+pub struct Personality {
+    /// From -1 for shy agents, fleeing early and hard, to 1 for bold ones
+    pub boldness: f32,
+    /// From -1 for loners, keeping to themselves, to 1 for social agents
+    pub sociability: f32,
+}
+
+impl Personality {
+    /// Personality of agents whose traits are ignored
+    pub const NEUTRAL: Personality = Personality {
+        boldness: 0.0,
+        sociability: 0.0,
+    };
+
+    const BOLD_COLOR: (f32, f32, f32, f32) = (1.0, 0.4, 0.2, 1.0);
+    const SHY_COLOR: (f32, f32, f32, f32) = (0.3, 0.5, 1.0, 1.0);
+
+    // How much the traits scale the flee threshold and the flocking weights at their extremes
+    const BOLDNESS_EFFECT: f32 = 0.5;
+    const SOCIABILITY_EFFECT: f32 = 0.5;
+
+    pub fn random(rng: &mut impl Rng) -> Self {
+        Personality {
+            boldness: rng.gen_range(-1.0..1.0),
+            sociability: rng.gen_range(-1.0..1.0),
+        }
+    }
+
+    /// Scale of the distance at which and of the strength with which the agent flees.
+    pub fn fearfulness(&self) -> f32 {
+        1.0 - self.boldness * Self::BOLDNESS_EFFECT
+    }
+
+    /// Scale of the alignment and cohesion weights.
+    pub fn gregariousness(&self) -> f32 {
+        1.0 + self.sociability * Self::SOCIABILITY_EFFECT
+    }
+
+    /// Tint a color by boldness, loners standing out dimmer.
+    pub fn tint(&self, color: Vec4) -> Vec4 {
+        let trait_color = if self.boldness > 0.0 {
+            Vec4::from(Self::BOLD_COLOR)
+        } else {
+            Vec4::from(Self::SHY_COLOR)
+        };
+        let tinted = color.lerp(trait_color, self.boldness.abs() * 0.7);
+        let brightness = 0.75 + self.sociability * 0.25;
+        (tinted.truncate() * brightness).extend(tinted.w)
+    }
+}
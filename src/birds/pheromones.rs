@@ -0,0 +1,107 @@
+use nannou::prelude::*;
+
+/// Scalar field covering the window that agents deposit into and follow.
+pub struct Pheromones {
+    cells: Vec<f32>,
+    scratch: Vec<f32>,
+    columns: usize,
+    rows: usize,
+    origin: Vec2,
+    cell_size: f32,
+}
+
+impl Pheromones {
+    pub const COLOR: (f32, f32, f32) = (0.9, 0.6, 0.2);
+    /// Side of the cells unless a performance profile chooses otherwise
+    pub const CELL_SIZE: f32 = 16.0;
+
+    pub const DEPOSIT: f32 = 0.05;
+    const MAX_CONCENTRATION: f32 = 4.0;
+
+    // Fraction of the pheromones lost and spread to neighboring cells every frame
+    const EVAPORATION: f32 = 0.01;
+    const DIFFUSION: f32 = 0.1;
+
+    /// Field over the window in square cells of a side, finer trails costing more.
+    pub fn new(win_rect: Rect, cell_size: f32) -> Self {
+        let columns = (win_rect.w() / cell_size).ceil() as usize;
+        let rows = (win_rect.h() / cell_size).ceil() as usize;
+        Pheromones {
+            cells: vec![0.0; columns * rows],
+            scratch: vec![0.0; columns * rows],
+            columns,
+            rows,
+            origin: win_rect.bottom_left(),
+            cell_size,
+        }
+    }
+
+    /// Index of the cell containing a position, if it is on the grid.
+    fn cell(&self, position: Vec2) -> Option<usize> {
+        let cell = ((position - self.origin) / self.cell_size).floor();
+        let (column, row) = (cell.x as isize, cell.y as isize);
+        if column < 0 || row < 0 || column >= self.columns as isize || row >= self.rows as isize {
+            return None;
+        }
+        Some(row as usize * self.columns + column as usize)
+    }
+
+    /// Concentration at a position, zero outside of the grid.
+    pub fn sample(&self, position: Vec2) -> f32 {
+        self.cell(position).map_or(0.0, |i| self.cells[i])
+    }
+
+    /// Direction in which the concentration increases the most.
+    pub fn gradient(&self, position: Vec2) -> Vec2 {
+        let dx = vec2(self.cell_size, 0.0);
+        let dy = vec2(0.0, self.cell_size);
+        vec2(
+            self.sample(position + dx) - self.sample(position - dx),
+            self.sample(position + dy) - self.sample(position - dy),
+        ) * 0.5
+    }
+
+    pub fn deposit(&mut self, position: Vec2, amount: f32) {
+        if let Some(i) = self.cell(position) {
+            self.cells[i] = (self.cells[i] + amount).min(Self::MAX_CONCENTRATION);
+        }
+    }
+
+    /// Diffuse and evaporate the pheromones.
+    pub fn update(&mut self) {
+        let (columns, rows) = (self.columns, self.rows);
+        for row in 0..rows {
+            for column in 0..columns {
+                let i = row * columns + column;
+
+                // Average of the neighboring cells, the edges reflecting back
+                let left = self.cells[if column > 0 { i - 1 } else { i }];
+                let right = self.cells[if column + 1 < columns { i + 1 } else { i }];
+                let below = self.cells[if row > 0 { i - columns } else { i }];
+                let above = self.cells[if row + 1 < rows { i + columns } else { i }];
+                let neighbors = (left + right + below + above) * 0.25;
+
+                let diffused = self.cells[i] + (neighbors - self.cells[i]) * Self::DIFFUSION;
+                self.scratch[i] = diffused * (1.0 - Self::EVAPORATION);
+            }
+        }
+        std::mem::swap(&mut self.cells, &mut self.scratch);
+    }
+
+    pub fn display(&self, draw: &Draw, color: (f32, f32, f32)) {
+        let (r, g, b) = color;
+        for (i, concentration) in self.cells.iter().enumerate() {
+            // Skip cells that would barely be visible
+            let alpha = (concentration / Self::MAX_CONCENTRATION).sqrt() * 0.6;
+            if alpha < 0.02 {
+                continue;
+            }
+
+            let cell = vec2((i % self.columns) as f32, (i / self.columns) as f32);
+            draw.rect()
+                .xy(self.origin + (cell + 0.5) * self.cell_size)
+                .w_h(self.cell_size, self.cell_size)
+                .color(rgba(r, g, b, alpha));
+        }
+    }
+}
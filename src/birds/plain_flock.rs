@@ -0,0 +1,65 @@
+use crate::agents::Agents;
+use crate::config::Config;
+use crate::flow_field::FlowField;
+use crate::index::{Indexing, NeighborIndex};
+use crate::scratch::ScratchPool;
+use crate::timestep::Timestep;
+use crate::vicsek::Vicsek;
+use crate::world::World;
+use nannou::prelude::*;
+use noise::Perlin;
+
+/// Flock following only the flocking rules on the plane, stepped without a window
+/// by the parameter sweeps.
+pub struct PlainFlock {
+    pub agents: Agents,
+    /// Buffer the next step is written into
+    next: Agents,
+    index: Box<dyn NeighborIndex>,
+    scratch: ScratchPool,
+    flow_field: FlowField,
+    noise: Perlin,
+    pub vicsek: Vicsek,
+    step: u64,
+}
+
+impl PlainFlock {
+    pub fn new(agents: Agents, flow_field: FlowField, noise: Perlin) -> Self {
+        PlainFlock {
+            next: agents.clone(),
+            index: Indexing::Grid.build(&agents),
+            agents,
+            scratch: ScratchPool::new(),
+            flow_field,
+            noise,
+            vicsek: Vicsek::new(),
+            step: 0,
+        }
+    }
+
+    /// Update every agent once within bounds.
+    pub fn step(&mut self, bounds: Rect) {
+        self.index.update(&self.agents);
+        let config = Config::get();
+        let mut world = World::plain(
+            &config,
+            bounds,
+            &self.agents,
+            self.index.as_ref(),
+            &self.flow_field,
+            &self.noise,
+        );
+        world.vicsek = self.vicsek;
+        world.step = self.step;
+        world.time = self.step as f32 * Timestep::DT;
+
+        let scratch = &self.scratch;
+        self.next.clone_from(&self.agents);
+        self.next.par_for_each_mut(
+            || scratch.take(),
+            |scratch, agent| agent.update(&world, scratch),
+        );
+        std::mem::swap(&mut self.agents, &mut self.next);
+        self.step += 1;
+    }
+}
@@ -0,0 +1,460 @@
+use crate::agent::Agent;
+use crate::agents::Agents;
+use crate::args::Args;
+use crate::attractor::MovingAttractor;
+use crate::avoidance::Avoidance;
+use crate::boundary::Boundary;
+use crate::brain::Brain;
+use crate::call::Call;
+use crate::collision::Collider;
+use crate::color_mode::ColorMode;
+use crate::config::Config;
+use crate::density::Density;
+use crate::ecosystem::Ecosystem;
+use crate::events::{Flash, Hooks};
+use crate::evolution::Evolution;
+use crate::flocks::Flocks;
+use crate::flow_field::FlowField;
+use crate::food::Food;
+use crate::force_point::ForcePoint;
+use crate::index::{Indexing, NeighborIndex};
+use crate::integrator::Integrator;
+use crate::kernel::Kernel;
+use crate::long_range::LongRange;
+use crate::migration::Migration;
+use crate::mouse::MouseForce;
+use crate::movement::Movement;
+use crate::neighbor_cache::NeighborCache;
+use crate::obstacle::Obstacle;
+use crate::path::Path;
+use crate::perception::Perception;
+use crate::pheromones::Pheromones;
+use crate::predator::{Hunt, Predator};
+use crate::profile::Profile;
+use crate::pursuit::Pursuit;
+use crate::quadtree::Quadtree;
+use crate::region::Region;
+use crate::scratch::{Scratch, ScratchPool};
+use crate::spatial_hash::CellSort;
+use crate::species::Species;
+use crate::strike::Strike;
+use crate::svg;
+use crate::terrain::{Slope, Terrain};
+use crate::timestep::Timestep;
+use crate::vicsek::Vicsek;
+use crate::vortex::Vortex;
+use crate::wall::Wall;
+use crate::waypoints::Waypoints;
+use crate::world::World;
+use crate::zones::{Phase, Zones};
+use nannou::prelude::*;
+use nannou::rand::Rng;
+use noise::Perlin;
+use std::time::Instant;
+
+/// Flock on the plane with everything it interacts with and the settings of every
+/// behavior, stepped by the sketch every frame or by the simulation thread.
+pub struct Plane {
+    pub boundary: Boundary,
+    pub margin: f32,
+    pub brain: Brain,
+    pub zones: Zones,
+    pub kernel: Kernel,
+    pub perception: Perception,
+    pub personalities: bool,
+    pub vicsek: Vicsek,
+    pub formation: bool,
+    pub collisions: bool,
+    pub collider: Collider,
+    pub movement: Movement,
+    pub integrator: Integrator,
+    pub banking: bool,
+    pub flapping: bool,
+    pub color_mode: ColorMode,
+    pub flocks: Flocks,
+    pub hooks: Hooks,
+    pub flashes: Vec<Flash>,
+    pub agents: Agents,
+    /// Agents of the previous step, the buffer the next step is written into
+    pub previous_agents: Agents,
+    /// Leaders among the agents, gathered again every step
+    pub leaders: Vec<Agent>,
+    /// Buffers the agents fill while updating, reused every step
+    pub scratch: ScratchPool,
+    /// Buffers the predators search for prey with, reused every step
+    pub predator_scratch: Scratch,
+    /// Structure the agents find their neighbors through
+    pub indexing: Indexing,
+    pub index: Box<dyn NeighborIndex>,
+    pub neighbor_cache: NeighborCache,
+    pub cache_neighbors: bool,
+    /// Whether the agents are reordered by grid cell every step
+    pub sort_agents: bool,
+    pub cell_sort: CellSort,
+    pub long_range: LongRange,
+    /// Quadtree of the agents for long-range cohesion, rebuilt every step while enabled
+    pub cohesion_tree: Quadtree,
+    pub long_range_cohesion: bool,
+    /// Seconds the last step took to update the agents and to rebuild the neighbor index
+    pub update_time: f32,
+    pub index_time: f32,
+    pub density: Density,
+    pub predators: Vec<Predator>,
+    pub pursuit: Pursuit,
+    pub obstacles: Vec<Obstacle>,
+    pub avoidance: Avoidance,
+    pub ecosystem: bool,
+    pub aging: bool,
+    pub evolution: Option<Evolution>,
+    pub flow_field: FlowField,
+    pub force_points: Vec<ForcePoint>,
+    pub vortices: Vec<Vortex>,
+    pub walls: Vec<Wall>,
+    pub regions: Vec<Region>,
+    pub strikes: Vec<Strike>,
+    pub noise: Perlin,
+    pub waypoints: Waypoints,
+    pub follow_waypoints: bool,
+    pub migrate: bool,
+    pub attractor: MovingAttractor,
+    pub path: Path,
+    pub follow_path: bool,
+    pub mouse_force: MouseForce,
+    pub pheromones: Pheromones,
+    pub follow_pheromones: bool,
+    pub terrain: Option<Terrain>,
+    pub food: Food,
+    pub forage: bool,
+    pub calls: Vec<Call>,
+    pub calling: bool,
+}
+
+/// What the plane is told of the sketch around it at every step.
+#[derive(Debug, Clone, Copy)]
+pub struct Surroundings {
+    pub bounds: Rect,
+    pub mouse: Vec2,
+    /// Seconds simulated since the start
+    pub time: f32,
+    pub step: u64,
+    /// How dark the night is, from 0 by day to 1
+    pub darkness: f32,
+}
+
+impl Plane {
+    /// Flock of the configured species spread over the window, with the predators,
+    /// obstacles and the terrain and shapes given on the command line.
+    pub fn new(
+        win_rect: Rect,
+        profile: Option<Profile>,
+        config: &Config,
+        rng: &mut impl Rng,
+    ) -> Self {
+        let agents = Plane::spawn(win_rect, config, rng);
+
+        let predators = (0..Predator::NUM_PREDATORS)
+            .map(|_| Predator::new(win_rect, rng))
+            .collect();
+
+        let obstacles = (0..Obstacle::NUM_OBSTACLES)
+            .map(|_| Obstacle::new(win_rect, rng))
+            .collect();
+
+        // Flock events are logged and sent over OSC if requested
+        let mut hooks = Hooks::from_args();
+        if Args::get().log_events {
+            hooks.on(|event| println!("{event}"));
+        }
+
+        // The terrain is only there when a heightmap is given
+        let terrain = Args::get().heightmap.as_ref().and_then(|path| {
+            Terrain::load(path, win_rect)
+                .map_err(|error| eprintln!("Could not load heightmap {path}: {error}"))
+                .ok()
+        });
+
+        // Shapes to flock around are only there when an SVG file is given
+        let shapes = Args::get().svg.as_ref().and_then(|file| {
+            svg::load(file, win_rect)
+                .map_err(|error| eprintln!("Could not load shapes {file}: {error}"))
+                .ok()
+        });
+        let (walls, regions) = shapes.map_or((Vec::new(), Vec::new()), |shapes| {
+            (shapes.walls, shapes.regions)
+        });
+
+        let flow_field = FlowField::new(rng);
+        let noise = Perlin::new(rng.gen());
+        let food = Food::from_args(win_rect, rng);
+        let indexing = profile.map_or(Indexing::Grid, Profile::indexing);
+
+        Plane {
+            boundary: Boundary::Wrap,
+            margin: Boundary::margin_from_args(),
+            brain: Brain::from_args(),
+            zones: Zones::new(Phase::Torus),
+            kernel: Kernel::Uniform,
+            perception: Perception::Metric,
+            personalities: false,
+            vicsek: Vicsek::new(),
+            formation: false,
+            collisions: false,
+            collider: Collider::new(),
+            movement: Movement::Classic,
+            integrator: Integrator::from_args(),
+            banking: false,
+            flapping: false,
+            color_mode: ColorMode::Species,
+            flocks: Flocks::new(),
+            hooks,
+            flashes: Vec::new(),
+            agents,
+            previous_agents: Agents::new(),
+            leaders: Vec::new(),
+            scratch: ScratchPool::new(),
+            predator_scratch: Scratch::default(),
+            indexing,
+            index: indexing.build(&Agents::new()),
+            neighbor_cache: NeighborCache::from_args(),
+            cache_neighbors: false,
+            sort_agents: false,
+            cell_sort: CellSort::new(),
+            long_range: LongRange::new(),
+            cohesion_tree: Quadtree::new(&Agents::new()),
+            long_range_cohesion: false,
+            update_time: 0.0,
+            index_time: 0.0,
+            density: Density::from_args(win_rect, config),
+            predators,
+            pursuit: Pursuit::Intercept,
+            obstacles,
+            avoidance: Avoidance::Radial,
+            ecosystem: false,
+            aging: false,
+            evolution: None,
+            flow_field,
+            force_points: Vec::new(),
+            vortices: Vec::new(),
+            walls,
+            regions,
+            strikes: Vec::new(),
+            noise,
+            waypoints: Waypoints::new(win_rect),
+            follow_waypoints: false,
+            migrate: false,
+            attractor: MovingAttractor::new(),
+            path: Path::from_args(win_rect),
+            follow_path: false,
+            mouse_force: MouseForce::Off,
+            pheromones: Pheromones::new(
+                win_rect,
+                profile.map_or(Pheromones::CELL_SIZE, Profile::trail_cell_size),
+            ),
+            follow_pheromones: false,
+            terrain,
+            food,
+            forage: false,
+            calls: Vec::new(),
+            calling: false,
+        }
+    }
+
+    /// Agents of every species in their configured numbers, spread over the window.
+    pub fn spawn(win_rect: Rect, config: &Config, rng: &mut impl Rng) -> Agents {
+        let mut agents = Agents::new();
+        for (species, Species { count, .. }) in config.species.iter().enumerate() {
+            agents.extend((0..*count).map(|_| Agent::new(win_rect, species, config, rng)));
+        }
+        agents
+    }
+
+    /// Update the flock and everything it interacts with by a step.
+    pub fn step(&mut self, surroundings: &Surroundings, rng: &mut impl Rng) {
+        let config = Config::get();
+        let Surroundings {
+            bounds,
+            mouse,
+            time,
+            step,
+            darkness,
+        } = *surroundings;
+
+        // Move on along the waypoints as the flock progresses
+        if self.follow_waypoints && !self.agents.is_empty() {
+            let centroid = self.agents.positions.iter().sum::<Vec2>() / self.agents.len() as f32;
+            self.waypoints.update(centroid);
+        }
+
+        // Record the path of the cursor for the moving attractor to replay
+        self.attractor.record(time, mouse);
+
+        let start = Instant::now();
+        if self.sort_agents && (!self.cache_neighbors || self.neighbor_cache.due()) {
+            self.cell_sort.sort(&mut self.agents);
+        }
+        self.index.update(&self.agents);
+        if self.cache_neighbors {
+            self.neighbor_cache
+                .update(&self.agents, self.index.as_ref());
+        }
+        if self.long_range_cohesion {
+            self.cohesion_tree.update(&self.agents);
+        }
+        self.index_time = start.elapsed().as_secs_f32();
+        self.leaders.clear();
+        self.leaders
+            .extend(self.agents.iter().filter(|agent| agent.leader));
+        let world = World {
+            config: &config,
+            bounds,
+            boundary: self.boundary,
+            margin: self.margin,
+            brain: self.brain,
+            zones: self.zones,
+            kernel: self.kernel,
+            perception: self.perception,
+            personalities: self.personalities,
+            vicsek: self.vicsek,
+            formation: self.formation,
+            movement: self.movement,
+            integrator: self.integrator,
+            banking: self.banking,
+            flapping: self.flapping,
+            time,
+            step,
+            darkness,
+            agents: &self.agents,
+            index: self.index.as_ref(),
+            neighbor_cache: self.cache_neighbors.then_some(&self.neighbor_cache),
+            cohesion_tree: self.long_range_cohesion.then_some(&self.cohesion_tree),
+            long_range: self.long_range,
+            leaders: &self.leaders,
+            predators: &self.predators,
+            obstacles: &self.obstacles,
+            walls: &self.walls,
+            avoidance: self.avoidance,
+            flow_field: &self.flow_field,
+            force_points: &self.force_points,
+            vortices: &self.vortices,
+            noise: &self.noise,
+            waypoint: self.follow_waypoints.then(|| self.waypoints.target()),
+            migration: self.migrate.then(|| Migration::target(bounds, time)),
+            path: self.follow_path.then_some(&self.path),
+            attractor: self.attractor.position(bounds, time),
+            mouse,
+            mouse_force: self.mouse_force,
+            pheromones: self.follow_pheromones.then_some(&self.pheromones),
+            food: self.forage.then_some(self.food.items.as_slice()),
+            terrain: self
+                .terrain
+                .as_ref()
+                .filter(|terrain| terrain.slope != Slope::Off),
+        };
+
+        // Agents read the current frame from one buffer and write the next frame into
+        // a copy of it in the other, so they can be updated in parallel
+        let start = Instant::now();
+        self.previous_agents.clone_from(&self.agents);
+        self.previous_agents.par_for_each_mut(
+            || self.scratch.take(),
+            |scratch, agent| agent.update(&world, scratch),
+        );
+        std::mem::swap(&mut self.agents, &mut self.previous_agents);
+        self.update_time = start.elapsed().as_secs_f32();
+
+        // Agents' bodies should never overlap
+        if self.collisions {
+            self.collider.resolve(&mut self.agents, &config);
+        }
+
+        // Agents should never end up inside the imported shapes
+        for region in &self.regions {
+            let agents = &mut self.agents;
+            for (position, velocity) in agents.positions.iter_mut().zip(&mut agents.velocities) {
+                (*position, *velocity) = region.eject(*position, *velocity);
+            }
+        }
+        let hunt = Hunt {
+            pursuit: self.pursuit,
+            agents: &self.previous_agents,
+            index: self.index.as_ref(),
+        };
+        self.predators.iter_mut().for_each(|predator| {
+            predator.update(
+                bounds,
+                self.boundary,
+                self.margin,
+                &hunt,
+                &mut self.predator_scratch,
+            )
+        });
+
+        // Hungry agents eat and food grows back
+        if self.forage {
+            self.food
+                .update(&mut self.agents, bounds, Timestep::DT, rng);
+        }
+
+        // Agents call out, the calls spreading and rallying the agents they reach
+        if self.calling {
+            Call::emit(&self.agents, rng, &mut self.calls);
+        }
+        let agents = &mut self.agents;
+        self.calls.retain_mut(|call| call.update(agents));
+
+        // Shockwaves of hawk strikes fade away
+        self.strikes.retain_mut(Strike::update);
+
+        // Agents mark their path, the trails fading and spreading over time
+        if self.follow_pheromones {
+            for &position in &self.agents.positions {
+                self.pheromones.deposit(position, Pheromones::DEPOSIT);
+            }
+            self.pheromones.update();
+        }
+
+        // Agents die of old age and are replaced by newcomers flying in from the edges
+        if self.aging {
+            let mut newcomers = Vec::new();
+            self.agents.retain_mut(|agent| {
+                let alive = agent.grow_older();
+                if !alive {
+                    newcomers.push(Agent::new_at_edge(bounds, agent.species, &config, rng));
+                }
+                alive
+            });
+            self.agents.extend(newcomers);
+        }
+
+        // The population follows the window size unless births and deaths decide it
+        if !self.ecosystem && self.evolution.is_none() {
+            self.density.update(&mut self.agents, bounds, &config, rng);
+        }
+
+        // Predators catch prey when prey can die
+        let caught = if self.ecosystem || self.evolution.is_some() {
+            Ecosystem::hunt(&mut self.agents, &mut self.predators)
+        } else {
+            Vec::new()
+        };
+
+        // Agents are born and die according to their energy
+        if self.ecosystem {
+            Ecosystem::update(&mut self.agents, &mut self.predators, &config, rng);
+        }
+
+        // Agents are grouped into flocks to be colored by flock, signaling splits and merges
+        if self.color_mode == ColorMode::Flock {
+            for event in self.flocks.update(&mut self.agents) {
+                self.hooks.dispatch(&event);
+                self.flashes.push(Flash::new(&event));
+            }
+        }
+        self.flashes.retain_mut(Flash::update);
+
+        // Agents evolve to escape predators
+        if let Some(evolution) = &mut self.evolution {
+            evolution.update(&mut self.agents, &caught, bounds, &config, rng);
+        }
+    }
+}
@@ -0,0 +1,97 @@
+use crate::agent::{Agent, Perceived};
+use nannou::geom::Tri;
+use nannou::prelude::*;
+use std::collections::HashMap;
+
+/// Faint lines between agents that perceive each other, more opaque the closer
+/// they are, gathered into a single mesh.
+#[derive(Debug, Clone, Default)]
+pub struct Plexus {
+    pub enabled: bool,
+    tris: Vec<Tri<(Vec3, Rgba)>>,
+    /// Place in the flock of every agent by its seed
+    places: HashMap<u64, usize>,
+    /// Lines drawn from every agent
+    linked: Vec<usize>,
+}
+
+impl Plexus {
+    pub const COLOR: (f32, f32, f32) = (0.8, 0.9, 1.0);
+
+    /// Opacity of the lines between touching agents
+    const OPACITY: f32 = 0.35;
+    const WEIGHT: f32 = 1.0;
+
+    // Large flocks only draw so many lines, each agent linking to its first few
+    // neighbors, so dense clusters don't turn into solid blobs
+    const MAX_LINES: usize = 20_000;
+    const MAX_LINES_PER_AGENT: usize = Perceived::CAPACITY;
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Draw a line between every pair of agents that perceived each other in
+    /// their last step, up to a number of lines.
+    pub fn display(&mut self, draw: &Draw, agents: &[Agent], color: (f32, f32, f32)) {
+        let (r, g, b) = color;
+        self.places.clear();
+        self.places
+            .extend(agents.iter().enumerate().map(|(i, agent)| (agent.seed, i)));
+        self.linked.clear();
+        self.linked.resize(agents.len(), 0);
+        let mut lines = 0;
+        self.tris.clear();
+        'agents: for (i, agent) in agents.iter().enumerate() {
+            // Every pair is drawn once, from the agent that comes first, counting
+            // towards the lines of both
+            for seed in agent.perceived.seeds() {
+                if self.linked[i] == Self::MAX_LINES_PER_AGENT {
+                    break;
+                }
+                let Some(&j) = self.places.get(seed) else {
+                    continue;
+                };
+                let other = &agents[j];
+                if j <= i
+                    || !other.perceived.contains(agent.seed)
+                    || self.linked[j] == Self::MAX_LINES_PER_AGENT
+                {
+                    continue;
+                }
+                if lines == Self::MAX_LINES {
+                    break 'agents;
+                }
+
+                // Quad from one agent to the other, as wide as a line. Nearest
+                // neighbors may lie beyond the detection radius, their lines fading out
+                let distance = agent.position.distance(other.position);
+                let reach = agent
+                    .parameters
+                    .detection_radius
+                    .min(other.parameters.detection_radius);
+                let color = rgba(r, g, b, Self::OPACITY * (1.0 - distance / reach).max(0.0));
+                let side = (other.position - agent.position).normalize_or_zero().perp()
+                    * Self::WEIGHT
+                    * 0.5;
+                let corners = [
+                    agent.position - side,
+                    agent.position + side,
+                    other.position + side,
+                    other.position - side,
+                ]
+                .map(|corner| (corner.extend(0.0), color));
+                self.tris.push(Tri([corners[0], corners[1], corners[2]]));
+                self.tris.push(Tri([corners[0], corners[2], corners[3]]));
+
+                lines += 1;
+                self.linked[i] += 1;
+                self.linked[j] += 1;
+            }
+        }
+
+        if !self.tris.is_empty() {
+            draw.mesh().tris_colored(self.tris.drain(..));
+        }
+    }
+}
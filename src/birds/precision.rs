@@ -0,0 +1,294 @@
+use crate::agents::Agents;
+use crate::args::{Args, FloatType};
+use crate::config::Config;
+use crate::lod::Sketch;
+use nannou::prelude::*;
+use num_traits::Float;
+use std::fmt::Debug;
+use std::ops::{Add, AddAssign, Div, Mul, Sub, SubAssign};
+
+/// Floating point type the precise flock is simulated in.
+pub trait Scalar: Float + Debug + Send + Sync {}
+
+impl<S: Float + Debug + Send + Sync> Scalar for S {}
+
+/// Convert a constant to the scalar type.
+fn scalar<S: Scalar>(value: f32) -> S {
+    S::from(value).unwrap()
+}
+
+/// Two dimensional vector of any scalar type.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Vector<S> {
+    pub x: S,
+    pub y: S,
+}
+
+impl<S: Scalar> Vector<S> {
+    pub fn zero() -> Self {
+        Vector {
+            x: S::zero(),
+            y: S::zero(),
+        }
+    }
+
+    pub fn from_f32(vector: Vec2) -> Self {
+        Vector {
+            x: scalar(vector.x),
+            y: scalar(vector.y),
+        }
+    }
+
+    /// Vector rounded to single precision for drawing.
+    pub fn to_f32(self) -> Vec2 {
+        vec2(self.x.to_f32().unwrap(), self.y.to_f32().unwrap())
+    }
+
+    pub fn length_squared(self) -> S {
+        self.x * self.x + self.y * self.y
+    }
+
+    pub fn length(self) -> S {
+        self.length_squared().sqrt()
+    }
+
+    pub fn normalize_or_zero(self) -> Self {
+        let length = self.length();
+        if length > S::zero() {
+            self / length
+        } else {
+            Self::zero()
+        }
+    }
+
+    pub fn lerp(self, other: Self, t: S) -> Self {
+        self + (other - self) * t
+    }
+}
+
+impl<S: Scalar> Add for Vector<S> {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Vector {
+            x: self.x + other.x,
+            y: self.y + other.y,
+        }
+    }
+}
+
+impl<S: Scalar> Sub for Vector<S> {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        Vector {
+            x: self.x - other.x,
+            y: self.y - other.y,
+        }
+    }
+}
+
+impl<S: Scalar> Mul<S> for Vector<S> {
+    type Output = Self;
+
+    fn mul(self, factor: S) -> Self {
+        Vector {
+            x: self.x * factor,
+            y: self.y * factor,
+        }
+    }
+}
+
+impl<S: Scalar> Div<S> for Vector<S> {
+    type Output = Self;
+
+    fn div(self, divisor: S) -> Self {
+        Vector {
+            x: self.x / divisor,
+            y: self.y / divisor,
+        }
+    }
+}
+
+impl<S: Scalar> AddAssign for Vector<S> {
+    fn add_assign(&mut self, other: Self) {
+        *self = *self + other;
+    }
+}
+
+impl<S: Scalar> SubAssign for Vector<S> {
+    fn sub_assign(&mut self, other: Self) {
+        *self = *self - other;
+    }
+}
+
+/// Agent of the precise flock, only following the flocking rules.
+#[derive(Debug, Clone, Copy)]
+pub struct Body<S> {
+    pub position: Vector<S>,
+    pub velocity: Vector<S>,
+    pub species: usize,
+}
+
+/// Flock following only the flocking rules, simulated deterministically in a
+/// scalar type of choice so long runs can be studied without single precision drift.
+#[derive(Debug, Clone)]
+pub struct PreciseFlock<S> {
+    bodies: Vec<Body<S>>,
+    /// Bodies as of the last step, which the bodies read while updating
+    previous: Vec<Body<S>>,
+}
+
+impl<S: Scalar> PreciseFlock<S> {
+    const DETECTION_RADIUS: f32 = 60.0;
+    const MIN_DISTANCE: f32 = 30.0;
+
+    const SEPARATION_FACTOR: f32 = 0.05;
+    const AVERAGE_VELOCITY_FACTOR: f32 = 0.01;
+    const AVERAGE_POSITION_FACTOR: f32 = 1e-3;
+
+    /// Flock starting where the agents are, heading where they head.
+    pub fn new(agents: &Agents) -> Self {
+        let bodies: Vec<Body<S>> = agents
+            .positions
+            .iter()
+            .zip(&agents.velocities)
+            .zip(&agents.species)
+            .map(|((&position, &velocity), &species)| Body {
+                position: Vector::from_f32(position),
+                velocity: Vector::from_f32(velocity),
+                species,
+            })
+            .collect();
+        PreciseFlock {
+            previous: bodies.clone(),
+            bodies,
+        }
+    }
+
+    /// Step every body, wrapping around the bounds.
+    pub fn update(&mut self, win_rect: Rect, config: &Config) {
+        let detection_radius = scalar::<S>(Self::DETECTION_RADIUS);
+        let min_distance = scalar::<S>(Self::MIN_DISTANCE);
+        let (left, right) = (scalar::<S>(win_rect.left()), scalar::<S>(win_rect.right()));
+        let (bottom, top) = (scalar::<S>(win_rect.bottom()), scalar::<S>(win_rect.top()));
+        let interactions = &config.interactions;
+
+        self.previous.clone_from(&self.bodies);
+        for body in self.bodies.iter_mut() {
+            // Move body, wrapping around the edges
+            body.position += body.velocity;
+            if body.position.x < left {
+                body.position.x = right;
+            } else if body.position.x > right {
+                body.position.x = left;
+            }
+            if body.position.y < bottom {
+                body.position.y = top;
+            } else if body.position.y > top {
+                body.position.y = bottom;
+            }
+
+            // Average offset towards, velocity of and separation from neighbors
+            let mut average_offset = Vector::zero();
+            let mut average_velocity = Vector::zero();
+            let mut separation = Vector::zero();
+            let mut cohesion_weight = S::zero();
+            let mut alignment_weight = S::zero();
+            for other in &self.previous {
+                let offset = other.position - body.position;
+                let distance_squared = offset.length_squared();
+                if distance_squared >= detection_radius * detection_radius
+                    || distance_squared == S::zero()
+                {
+                    continue;
+                }
+                let distance = distance_squared.sqrt();
+                let weights = interactions.between(body.species, other.species);
+                let (alignment, cohesion) =
+                    (scalar::<S>(weights.alignment), scalar(weights.cohesion));
+
+                average_velocity += other.velocity * alignment;
+                average_offset += offset * cohesion;
+                alignment_weight = alignment_weight + alignment;
+                cohesion_weight = cohesion_weight + cohesion;
+
+                // The closer the neighbor, the stronger the separation
+                if distance < min_distance {
+                    separation -= offset / distance
+                        * scalar(weights.separation)
+                        * (S::one() - distance / min_distance);
+                }
+            }
+
+            // Body should move towards the same direction as its neighbors
+            if alignment_weight > S::zero() {
+                body.velocity = body.velocity.lerp(
+                    average_velocity / alignment_weight,
+                    scalar(Self::AVERAGE_VELOCITY_FACTOR),
+                );
+            }
+
+            // Body should move towards the average position of its neighbors
+            if cohesion_weight > S::zero() {
+                body.velocity +=
+                    average_offset / cohesion_weight * scalar(Self::AVERAGE_POSITION_FACTOR);
+            }
+
+            // Body should keep a minimum distance to its neighbors
+            body.velocity += separation * scalar(Self::SEPARATION_FACTOR);
+
+            // Normalize velocity and set speed
+            body.velocity =
+                body.velocity.normalize_or_zero() * scalar(config.species[body.species].speed);
+        }
+    }
+
+    /// Draw the bodies, rounded to single precision.
+    pub fn display(&self, draw: &Draw, sketch: &mut Sketch, config: &Config) {
+        sketch.reset(self.bodies.len());
+        for body in &self.bodies {
+            let species = &config.species[body.species];
+            sketch.tri(
+                draw,
+                body.position.to_f32(),
+                body.velocity.to_f32().angle(),
+                Vec2::from(species.size),
+                species.color.into(),
+            );
+        }
+        sketch.display(draw);
+    }
+}
+
+/// Precise flock in the precision selected on the command line.
+#[derive(Debug, Clone)]
+pub enum Precision {
+    Single(PreciseFlock<f32>),
+    Double(PreciseFlock<f64>),
+}
+
+impl Precision {
+    /// Precise flock of the agents simulated in the precision given by
+    /// `--precision <f32|f64>`, if any.
+    pub fn from_args(agents: &Agents) -> Option<Self> {
+        match Args::get().precision? {
+            FloatType::F32 => Some(Precision::Single(PreciseFlock::new(agents))),
+            FloatType::F64 => Some(Precision::Double(PreciseFlock::new(agents))),
+        }
+    }
+
+    pub fn update(&mut self, win_rect: Rect, config: &Config) {
+        match self {
+            Precision::Single(flock) => flock.update(win_rect, config),
+            Precision::Double(flock) => flock.update(win_rect, config),
+        }
+    }
+
+    pub fn display(&self, draw: &Draw, sketch: &mut Sketch, config: &Config) {
+        match self {
+            Precision::Single(flock) => flock.display(draw, sketch, config),
+            Precision::Double(flock) => flock.display(draw, sketch, config),
+        }
+    }
+}
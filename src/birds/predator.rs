@@ -0,0 +1,96 @@
+use crate::agents::Agents;
+use crate::boundary::Boundary;
+use crate::index::NeighborIndex;
+use crate::pursuit::Pursuit;
+use crate::scratch::Scratch;
+use nannou::prelude::*;
+use nannou::rand::Rng;
+
+/// What a predator perceives of the flock it hunts during an update.
+pub struct Hunt<'a> {
+    pub pursuit: Pursuit,
+    pub agents: &'a Agents,
+    /// Structure for finding the agents close to a position
+    pub index: &'a dyn NeighborIndex,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Predator {
+    pub position: Vec2,
+    pub velocity: Vec2,
+    pub energy: f32,
+}
+
+impl Predator {
+    const SIZE: (f32, f32) = (25.0, 25.0);
+    pub const COLOR: (f32, f32, f32, f32) = (1.0, 0.2, 0.2, 1.0);
+    pub const NUM_PREDATORS: usize = 2;
+
+    const SPEED: f32 = 1.8;
+    const HUNT_RADIUS: f32 = 200.0;
+
+    const CHASE_FACTOR: f32 = 0.05;
+
+    pub fn new(win_rect: Rect, rng: &mut impl Rng) -> Self {
+        // Random position
+        let position = vec2(
+            rng.gen_range(win_rect.left()..win_rect.right()),
+            rng.gen_range(win_rect.bottom()..win_rect.top()),
+        );
+        Self::spawn(position, 1.0, rng)
+    }
+
+    /// Create a predator with the given energy at a position, flying in a random direction.
+    pub fn spawn(position: Vec2, energy: f32, rng: &mut impl Rng) -> Self {
+        // Random velocity
+        let velocity =
+            Vec2::new(rng.gen_range(-1.0..1.0), rng.gen_range(-1.0..1.0)).normalize() * Self::SPEED;
+
+        // Return new predator
+        Predator {
+            position,
+            velocity,
+            energy,
+        }
+    }
+
+    pub fn update(
+        &mut self,
+        win_rect: Rect,
+        boundary: Boundary,
+        margin: f32,
+        hunt: &Hunt,
+        scratch: &mut Scratch,
+    ) {
+        // Move predator
+        (self.position, self.velocity) = boundary.step(self.position, self.velocity, &win_rect);
+
+        // Predator should chase its prey, otherwise it keeps gliding straight ahead
+        if let Some(target) = hunt.pursuit.target(
+            self.position,
+            Self::SPEED,
+            Self::HUNT_RADIUS,
+            hunt.agents,
+            hunt.index,
+            scratch,
+        ) {
+            let desired = (target - self.position).normalize_or_zero() * Self::SPEED;
+            self.velocity = self.velocity.lerp(desired, Self::CHASE_FACTOR);
+        }
+
+        // Predator should turn back before reaching the edges of the window
+        let back = boundary.steer(self.position, &win_rect, margin);
+        self.velocity += back * Self::CHASE_FACTOR * Self::SPEED;
+
+        // Normalize velocity and set speed
+        self.velocity = self.velocity.normalize() * Self::SPEED;
+    }
+
+    pub fn display(&self, draw: &Draw, color: Rgba) {
+        draw.tri()
+            .xy(self.position)
+            .rotate(self.velocity.angle())
+            .wh(Self::SIZE.into())
+            .color(color);
+    }
+}
@@ -0,0 +1,140 @@
+use crate::config::AgentConfig;
+use serde::{Deserialize, Serialize};
+use std::io::ErrorKind;
+
+/// Flocking parameters saved under a name.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Preset {
+    pub name: String,
+    pub agent: AgentConfig,
+}
+
+/// Preset in its slot, as written to disk.
+#[derive(Debug, Deserialize, Serialize)]
+struct Slot {
+    slot: usize,
+    #[serde(flatten)]
+    preset: Preset,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+struct File {
+    #[serde(default)]
+    preset: Vec<Slot>,
+}
+
+/// Presets in the slots of the number keys, persisted between runs.
+#[derive(Debug, Clone)]
+pub struct Presets {
+    slots: [Option<Preset>; Presets::NUM_SLOTS],
+}
+
+impl Presets {
+    pub const NUM_SLOTS: usize = 9;
+
+    /// File the presets are saved to, next to where the sketch is run
+    const PATH: &'static str = "presets.toml";
+
+    /// Presets saved by previous runs, the built-in ones if none were.
+    pub fn load() -> Self {
+        let file = match std::fs::read_to_string(Self::PATH) {
+            Ok(text) => toml::from_str::<File>(&text)
+                .map_err(|error| eprintln!("Could not parse presets {}: {error}", Self::PATH))
+                .ok(),
+            Err(error) => {
+                if error.kind() != ErrorKind::NotFound {
+                    eprintln!("Could not read presets {}: {error}", Self::PATH);
+                }
+                None
+            }
+        };
+        let Some(file) = file else {
+            return Self::default();
+        };
+
+        let mut presets = Presets {
+            slots: Default::default(),
+        };
+        for Slot { slot, preset } in file.preset {
+            if let Some(entry) = slot.checked_sub(1).and_then(|i| presets.slots.get_mut(i)) {
+                *entry = Some(preset);
+            }
+        }
+        presets
+    }
+
+    /// Preset in a slot, numbered from 1.
+    pub fn get(&self, slot: usize) -> Option<&Preset> {
+        self.slots.get(slot.checked_sub(1)?)?.as_ref()
+    }
+
+    /// Save parameters into a slot, numbered from 1, keeping the name of the preset
+    /// they replace, and write every preset to disk.
+    pub fn save(&mut self, slot: usize, agent: AgentConfig) -> Result<&Preset, String> {
+        let entry = slot
+            .checked_sub(1)
+            .and_then(|i| self.slots.get_mut(i))
+            .ok_or(format!("No preset slot {slot}"))?;
+        let name = entry
+            .take()
+            .map_or(format!("preset {slot}"), |preset| preset.name);
+        *entry = Some(Preset { name, agent });
+
+        let file = File {
+            preset: (1..=Self::NUM_SLOTS)
+                .filter_map(|slot| {
+                    let preset = self.get(slot)?.clone();
+                    Some(Slot { slot, preset })
+                })
+                .collect(),
+        };
+        let text = toml::to_string(&file).map_err(|error| error.to_string())?;
+        std::fs::write(Self::PATH, text)
+            .map_err(|error| format!("Could not save presets {}: {error}", Self::PATH))?;
+        Ok(self.get(slot).unwrap())
+    }
+}
+
+impl Default for Presets {
+    /// Built-in presets in the first slots, the others empty.
+    fn default() -> Self {
+        let agent = AgentConfig::default();
+        let mut slots: [Option<Preset>; Self::NUM_SLOTS] = Default::default();
+        slots[0] = Some(Preset {
+            name: "tight murmuration".to_string(),
+            agent: AgentConfig {
+                min_distance: 15.0,
+                detection_radius: 80.0,
+                separation_weight: 1.2,
+                alignment_weight: 1.6,
+                cohesion_weight: 1.4,
+                ..agent
+            },
+        });
+        slots[1] = Some(Preset {
+            name: "loose wanderers".to_string(),
+            agent: AgentConfig {
+                min_distance: 45.0,
+                detection_radius: 40.0,
+                separation_weight: 1.0,
+                alignment_weight: 0.3,
+                cohesion_weight: 0.2,
+                ..agent
+            },
+        });
+        slots[2] = Some(Preset {
+            name: "panic".to_string(),
+            agent: AgentConfig {
+                min_distance: 40.0,
+                panic_radius: 240.0,
+                max_force: 0.12,
+                max_turn_rate: 0.2,
+                separation_weight: 3.0,
+                alignment_weight: 0.5,
+                cohesion_weight: 0.3,
+                ..agent
+            },
+        });
+        Presets { slots }
+    }
+}
@@ -0,0 +1,69 @@
+use crate::args::Args;
+use crate::index::Indexing;
+use clap::ValueEnum;
+
+/// Built-in settings trading quality for speed, so the sketch runs well on
+/// anything from a laptop's integrated GPU to a desktop.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Profile {
+    Low,
+    Medium,
+    High,
+}
+
+impl Profile {
+    pub fn from_args() -> Option<Self> {
+        Args::get().profile
+    }
+
+    pub fn next(self) -> Self {
+        match self {
+            Profile::Low => Profile::Medium,
+            Profile::Medium => Profile::High,
+            Profile::High => Profile::Low,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Profile::Low => "low",
+            Profile::Medium => "medium",
+            Profile::High => "high",
+        }
+    }
+
+    /// Number of agents the flock starts with, split among the species.
+    pub fn agents(self) -> usize {
+        match self {
+            Profile::Low => 150,
+            Profile::Medium => 600,
+            Profile::High => 2500,
+        }
+    }
+
+    /// Side in pixels of the cells of the pheromone trails.
+    pub fn trail_cell_size(self) -> f32 {
+        match self {
+            Profile::Low => 32.0,
+            Profile::Medium => 16.0,
+            Profile::High => 8.0,
+        }
+    }
+
+    /// Samples per pixel smoothing the edges of shapes, 4 being the most every
+    /// GPU supports.
+    pub fn msaa_samples(self) -> u32 {
+        match self {
+            Profile::Low => 1,
+            Profile::Medium | Profile::High => 4,
+        }
+    }
+
+    /// Structure neighbors are searched in.
+    pub fn indexing(self) -> Indexing {
+        match self {
+            Profile::Low | Profile::Medium => Indexing::Grid,
+            Profile::High => Indexing::KdTree,
+        }
+    }
+}
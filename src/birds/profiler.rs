@@ -0,0 +1,105 @@
+use nannou::prelude::*;
+use std::cell::Cell;
+use std::collections::VecDeque;
+use std::time::Instant;
+
+/// Stage of a frame timed by the profiler.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stage {
+    /// Simulation steps, rebuilding the neighbor index included
+    Simulation,
+    /// Rebuilding the neighbor index
+    Index,
+    Draw,
+}
+
+/// On-screen profiler timing the stages of every frame, with a rolling graph of
+/// the last frames.
+#[derive(Debug, Default)]
+pub struct Profiler {
+    /// Milliseconds spent on each stage during the current frame, recorded from
+    /// both the update and the view
+    current: [Cell<f32>; 3],
+    /// Milliseconds of the simulation outside the index, the index and the draw
+    /// of the last frames, oldest first
+    history: VecDeque<[f32; 3]>,
+}
+
+impl Profiler {
+    pub const COLORS: [(f32, f32, f32, f32); 3] = [
+        (0.3, 0.7, 1.0, 1.0),
+        (0.3, 0.9, 0.4, 1.0),
+        (1.0, 0.6, 0.2, 1.0),
+    ];
+    const NAMES: [&'static str; 3] = ["simulation", "index", "draw"];
+
+    /// Frames shown in the graph
+    const HISTORY: usize = 120;
+
+    /// Milliseconds at the top of the graph, two frames at 60 frames per second
+    const GRAPH_MS: f32 = 1000.0 / 30.0;
+    const GRAPH_SIZE: (f32, f32) = (240.0, 80.0);
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add the milliseconds elapsed since a start to a stage of the current frame.
+    pub fn record(&self, stage: Stage, start: Instant) {
+        self.add(stage, start.elapsed().as_secs_f32());
+    }
+
+    /// Add seconds timed elsewhere to a stage of the current frame.
+    pub fn add(&self, stage: Stage, seconds: f32) {
+        let cell = &self.current[stage as usize];
+        cell.set(cell.get() + seconds * 1000.0);
+    }
+
+    /// Close the current frame, adding it to the graph, and start the next one.
+    pub fn end_frame(&mut self) {
+        let [simulation, index, draw] = self.current.each_ref().map(Cell::take);
+        self.history
+            .push_back([(simulation - index).max(0.0), index, draw]);
+        if self.history.len() > Self::HISTORY {
+            self.history.pop_front();
+        }
+    }
+
+    /// Draw the timings of the last frame and the graph of the previous ones,
+    /// each stage stacked on top of the one before.
+    pub fn display(&self, draw: &Draw, win_rect: Rect) {
+        let Some(last) = self.history.back() else {
+            return;
+        };
+        let (width, height) = Self::GRAPH_SIZE;
+        let area = win_rect.pad(20.0);
+        let graph = Rect::from_w_h(width, height).mid_right_of(area);
+
+        draw.rect()
+            .xy(graph.xy())
+            .wh(graph.wh())
+            .no_fill()
+            .stroke_weight(1.0)
+            .stroke(rgba(0.5, 0.5, 0.5, 0.5));
+
+        for stage in 0..3 {
+            let points = self.history.iter().enumerate().map(|(i, frame)| {
+                let ms: f32 = frame[..=stage].iter().sum();
+                let x = graph.left() + i as f32 / (Self::HISTORY - 1) as f32 * width;
+                let y = graph.bottom() + (ms / Self::GRAPH_MS).min(1.0) * height;
+                pt2(x, y)
+            });
+            draw.polyline()
+                .weight(1.0)
+                .points(points)
+                .color(Rgba::from(Self::COLORS[stage]));
+
+            draw.text(&format!("{} {:.1} ms", Self::NAMES[stage], last[stage]))
+                .x_y(graph.x(), graph.top() + 14.0 * (3 - stage) as f32)
+                .w(width)
+                .right_justify()
+                .font_size(12)
+                .color(Rgba::from(Self::COLORS[stage]));
+        }
+    }
+}
@@ -0,0 +1,207 @@
+use crate::agents::Agents;
+use crate::index::NeighborIndex;
+use crate::scratch::Scratch;
+use nannou::prelude::*;
+
+/// Strategies predators pick and chase their prey with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Pursuit {
+    /// Chase the closest prey where it currently is
+    Direct,
+    /// Aim at where the closest prey will be when the predator reaches it
+    Intercept,
+    /// Single out the most isolated prey on the edge of the flock and intercept it
+    Stalk,
+}
+
+impl Pursuit {
+    // Prey with fewer neighbors than others inside this radius are stragglers
+    const STRAGGLER_RADIUS: f32 = 50.0;
+
+    // How far ahead in frames a prey's position is predicted at most
+    const MAX_LOOKAHEAD: f32 = 60.0;
+
+    pub fn next(self) -> Self {
+        match self {
+            Pursuit::Direct => Pursuit::Intercept,
+            Pursuit::Intercept => Pursuit::Stalk,
+            Pursuit::Stalk => Pursuit::Direct,
+        }
+    }
+
+    /// Point a pursuer at a position and flying at a speed should head towards, if
+    /// any prey is inside its range, searching for it with the buffers of a scratch.
+    pub fn target(
+        self,
+        position: Vec2,
+        speed: f32,
+        range: f32,
+        agents: &Agents,
+        index: &dyn NeighborIndex,
+        scratch: &mut Scratch,
+    ) -> Option<Vec2> {
+        let Scratch {
+            candidates,
+            neighbors,
+            nearby,
+            ..
+        } = scratch;
+        let prey = match self {
+            Pursuit::Direct | Pursuit::Intercept => {
+                index.nearest(position, 1, &|_, distance| distance < range, neighbors);
+                neighbors.first().map(|&(i, _)| i)
+            }
+            // Prey with the fewest neighbors, the closest one breaking ties
+            Pursuit::Stalk => {
+                candidates.clear();
+                index.within(position, range, candidates);
+                candidates
+                    .iter()
+                    .map(|&i| (i, position.distance_squared(agents.positions[i])))
+                    .filter(|(_, distance_squared)| *distance_squared < range * range)
+                    .map(|(i, distance)| {
+                        let prey = agents.positions[i];
+                        (i, Self::neighbors(prey, index, nearby), distance)
+                    })
+                    .min_by(|(_, a, da), (_, b, db)| a.cmp(b).then(da.total_cmp(db)))
+                    .map(|(i, _, _)| i)
+            }
+        }?;
+
+        let (target, velocity) = (agents.positions[prey], agents.velocities[prey]);
+        Some(match self {
+            Pursuit::Direct => target,
+            Pursuit::Intercept | Pursuit::Stalk => intercept(position, speed, target, velocity),
+        })
+    }
+
+    /// Number of other agents close to an agent at a position, found into a buffer.
+    fn neighbors(position: Vec2, index: &dyn NeighborIndex, nearby: &mut Vec<usize>) -> usize {
+        let positions = index.positions();
+        nearby.clear();
+        index.within(position, Self::STRAGGLER_RADIUS, nearby);
+        nearby
+            .iter()
+            .filter(|&&i| {
+                let distance_squared = positions[i].distance_squared(position);
+                distance_squared > 0.0
+                    && distance_squared < Self::STRAGGLER_RADIUS * Self::STRAGGLER_RADIUS
+            })
+            .count()
+    }
+}
+
+/// Point where a pursuer flying at a speed meets a target moving at a constant
+/// velocity, or the target's furthest predicted position if it cannot be caught.
+pub fn intercept(position: Vec2, speed: f32, target: Vec2, velocity: Vec2) -> Vec2 {
+    // Solve |target + velocity * t - position| = speed * t for the earliest positive time t
+    let offset = target - position;
+    let a = velocity.length_squared() - speed * speed;
+    let b = 2.0 * offset.dot(velocity);
+    let c = offset.length_squared();
+
+    let time = if a.abs() < f32::EPSILON {
+        // Equal speeds, only one solution
+        if b < 0.0 {
+            -c / b
+        } else {
+            Pursuit::MAX_LOOKAHEAD
+        }
+    } else {
+        let discriminant = b * b - 4.0 * a * c;
+        if discriminant < 0.0 {
+            Pursuit::MAX_LOOKAHEAD
+        } else {
+            let root = discriminant.sqrt();
+            let (t1, t2) = ((-b - root) / (2.0 * a), (-b + root) / (2.0 * a));
+            [t1, t2]
+                .into_iter()
+                .filter(|t| *t > 0.0)
+                .min_by(|a, b| a.total_cmp(b))
+                .unwrap_or(Pursuit::MAX_LOOKAHEAD)
+        }
+    };
+
+    target + velocity * time.min(Pursuit::MAX_LOOKAHEAD)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::Agent;
+    use crate::config::Config;
+    use crate::index::Indexing;
+    use nannou::rand::rngs::SmallRng;
+    use nannou::rand::SeedableRng;
+
+    const RANGE: f32 = 100.0;
+
+    /// Prey around a pursuer at the origin: a loner exactly at the edge of its
+    /// range, one whose only neighbor is exactly at the straggler radius, and a pair.
+    fn layout() -> Agents {
+        let config = Config::get();
+        let mut rng = SmallRng::seed_from_u64(0);
+        [
+            vec2(RANGE, 0.0),
+            vec2(0.0, 60.0),
+            vec2(0.0, 60.0 + Pursuit::STRAGGLER_RADIUS),
+            vec2(-40.0, 0.0),
+            vec2(-40.0, -10.0),
+        ]
+        .into_iter()
+        .map(|position| Agent::spawn(position, 0, &config, &mut rng))
+        .collect()
+    }
+
+    /// Prey stalked from the origin, comparing distances rather than their
+    /// squares as the pursuit used to.
+    fn reference(agents: &Agents) -> Option<usize> {
+        let positions = &agents.positions;
+        let neighbors = |position: Vec2| {
+            positions
+                .iter()
+                .map(|other| other.distance(position))
+                .filter(|&distance| distance > 0.0 && distance < Pursuit::STRAGGLER_RADIUS)
+                .count()
+        };
+        positions
+            .iter()
+            .enumerate()
+            .map(|(i, &position)| (i, position, position.length()))
+            .filter(|&(_, _, distance)| distance < RANGE)
+            .map(|(i, position, distance)| (i, neighbors(position), distance))
+            .min_by(|(_, a, da), (_, b, db)| a.cmp(b).then(da.total_cmp(db)))
+            .map(|(i, _, _)| i)
+    }
+
+    #[test]
+    fn squared_distances_stalk_the_same_prey() {
+        let agents = layout();
+        let prey = reference(&agents);
+        assert_eq!(prey, Some(1));
+        let expected = intercept(Vec2::ZERO, 2.0, agents.positions[1], agents.velocities[1]);
+
+        let mut scratch = Scratch::default();
+        for indexing in [Indexing::BruteForce, Indexing::Grid, Indexing::KdTree] {
+            let index = indexing.build(&agents);
+            let nearby = &mut scratch.nearby;
+            assert_eq!(
+                Pursuit::neighbors(agents.positions[1], index.as_ref(), nearby),
+                0
+            );
+            assert_eq!(
+                Pursuit::neighbors(agents.positions[3], index.as_ref(), nearby),
+                1
+            );
+            let target = Pursuit::Stalk.target(
+                Vec2::ZERO,
+                2.0,
+                RANGE,
+                &agents,
+                index.as_ref(),
+                &mut scratch,
+            );
+            assert_eq!(target, Some(expected), "{indexing:?}");
+        }
+    }
+}
@@ -0,0 +1,256 @@
+use crate::agents::Agents;
+use crate::index::NeighborIndex;
+use nannou::prelude::*;
+
+/// Tree recursively splitting the area covered by the agents into quadrants,
+/// so range queries only visit the quadrants overlapping the range and distant
+/// quadrants can stand in for all of their agents.
+///
+/// Rebuilding the tree reuses its nodes and their lists of agents, so steady-state
+/// rebuilds allocate nothing once the tree has grown.
+pub struct Quadtree {
+    /// Nodes of the tree, followed by those left over from larger trees
+    nodes: Vec<Node>,
+    /// Nodes in use
+    len: usize,
+    positions: Vec<Vec2>,
+}
+
+struct Node {
+    bounds: Rect,
+    depth: usize,
+    /// Agents inside a leaf
+    items: Vec<usize>,
+    /// Index of the first of the four children of a split node
+    children: Option<usize>,
+    /// Number of agents inside the node and their center of mass
+    mass: f32,
+    center: Vec2,
+}
+
+impl Quadtree {
+    /// Agents a leaf holds before splitting
+    const CAPACITY: usize = 8;
+    /// Depth past which leaves never split, for agents stacked on the same spot
+    const MAX_DEPTH: usize = 10;
+
+    pub fn new(agents: &Agents) -> Self {
+        let mut tree = Quadtree {
+            nodes: Vec::new(),
+            len: 0,
+            positions: Vec::new(),
+        };
+        tree.rebuild(agents);
+        tree
+    }
+
+    /// Insert every agent again, reusing the memory of the tree.
+    fn rebuild(&mut self, agents: &Agents) {
+        self.positions.clone_from(&agents.positions);
+
+        // Root covering every agent
+        let (min, max) = self.positions.iter().fold(
+            (Vec2::splat(f32::MAX), Vec2::splat(f32::MIN)),
+            |(min, max), p| (min.min(*p), max.max(*p)),
+        );
+        let bounds = if self.positions.is_empty() {
+            Rect::from_w_h(0.0, 0.0)
+        } else {
+            Rect::from_corners(min, max)
+        };
+
+        self.len = 0;
+        self.add(bounds, 0);
+        for i in 0..self.positions.len() {
+            self.insert(i);
+        }
+        self.aggregate();
+    }
+
+    /// Add an empty leaf, in a node left over from a larger tree if there is one.
+    fn add(&mut self, bounds: Rect, depth: usize) {
+        if self.len == self.nodes.len() {
+            self.nodes.push(Node {
+                bounds,
+                depth,
+                items: Vec::new(),
+                children: None,
+                mass: 0.0,
+                center: Vec2::ZERO,
+            });
+        } else {
+            let node = &mut self.nodes[self.len];
+            node.bounds = bounds;
+            node.depth = depth;
+            node.items.clear();
+            node.children = None;
+        }
+        self.len += 1;
+    }
+
+    /// Count the agents inside every node and find their center of mass.
+    /// Children come after their parent, so going backwards sums them up first.
+    fn aggregate(&mut self) {
+        for node in (0..self.len).rev() {
+            let (mass, sum) = match self.nodes[node].children {
+                Some(children) => (children..children + 4).fold((0.0, Vec2::ZERO), |sum, i| {
+                    let child = &self.nodes[i];
+                    (sum.0 + child.mass, sum.1 + child.center * child.mass)
+                }),
+                None => {
+                    let items = &self.nodes[node].items;
+                    let sum = items.iter().map(|&i| &self.positions[i]).sum();
+                    (items.len() as f32, sum)
+                }
+            };
+            let node = &mut self.nodes[node];
+            node.mass = mass;
+            node.center = if mass > 0.0 {
+                sum / mass
+            } else {
+                node.bounds.xy()
+            };
+        }
+    }
+
+    /// Pull of the agents further than a radius from a position, each pulling
+    /// towards itself inversely to its distance.
+    /// Following Barnes-Hut, quadrants entirely beyond the radius that look narrower
+    /// than the opening angle `theta` pull as a single agent at their center of mass.
+    pub fn attraction(&self, position: Vec2, radius: f32, theta: f32) -> Vec2 {
+        let pull = |center: Vec2, mass: f32| {
+            let offset = center - position;
+            let distance_squared = offset.length_squared();
+            if distance_squared > 0.0 {
+                offset / distance_squared * mass
+            } else {
+                Vec2::ZERO
+            }
+        };
+
+        self.pull_of(0, position, radius, theta, &pull)
+    }
+
+    /// Pull of the agents of a node further than a radius from a position.
+    fn pull_of(
+        &self,
+        node: usize,
+        position: Vec2,
+        radius: f32,
+        theta: f32,
+        pull: &impl Fn(Vec2, f32) -> Vec2,
+    ) -> Vec2 {
+        let node = &self.nodes[node];
+        if node.mass == 0.0 {
+            return Vec2::ZERO;
+        }
+
+        // Distant quadrants small enough from here are approximated
+        let closest = position.clamp(node.bounds.bottom_left(), node.bounds.top_right());
+        let beyond = closest.distance_squared(position) > radius * radius;
+        let width = node.bounds.w().max(node.bounds.h());
+        if beyond && width < theta * node.center.distance(position) {
+            return pull(node.center, node.mass);
+        }
+
+        match node.children {
+            Some(children) => (children..children + 4).fold(Vec2::ZERO, |force, child| {
+                force + self.pull_of(child, position, radius, theta, pull)
+            }),
+            None => node
+                .items
+                .iter()
+                .map(|&i| self.positions[i])
+                .filter(|other| other.distance_squared(position) > radius * radius)
+                .fold(Vec2::ZERO, |force, other| force + pull(other, 1.0)),
+        }
+    }
+
+    /// Quadrant of a node containing a position, counting from the bottom left.
+    fn quadrant(bounds: &Rect, position: Vec2) -> usize {
+        let center = bounds.xy();
+        (position.x >= center.x) as usize + 2 * (position.y >= center.y) as usize
+    }
+
+    fn insert(&mut self, i: usize) {
+        let position = self.positions[i];
+
+        // Descend to the leaf containing the position
+        let mut node = 0;
+        while let Some(children) = self.nodes[node].children {
+            node = children + Self::quadrant(&self.nodes[node].bounds, position);
+        }
+
+        self.nodes[node].items.push(i);
+        if self.nodes[node].items.len() > Self::CAPACITY && self.nodes[node].depth < Self::MAX_DEPTH
+        {
+            self.split(node);
+        }
+    }
+
+    /// Split a leaf into four quadrants, moving its agents down into them.
+    fn split(&mut self, node: usize) {
+        let bounds = self.nodes[node].bounds;
+        let depth = self.nodes[node].depth + 1;
+        let children = self.len;
+        let (w, h) = (bounds.w() * 0.5, bounds.h() * 0.5);
+        for quadrant in 0..4 {
+            let corner =
+                bounds.bottom_left() + vec2((quadrant % 2) as f32 * w, (quadrant / 2) as f32 * h);
+            self.add(Rect::from_corners(corner, corner + vec2(w, h)), depth);
+        }
+        self.nodes[node].children = Some(children);
+
+        // The agents go down into the children, the node keeping its list for later trees
+        for item in 0..self.nodes[node].items.len() {
+            self.insert(self.nodes[node].items[item]);
+        }
+        self.nodes[node].items.clear();
+    }
+
+    /// Add the agents in the leaves of a node overlapping a circle.
+    fn search_within(&self, node: usize, position: Vec2, radius: f32, found: &mut Vec<usize>) {
+        let node = &self.nodes[node];
+
+        // Skip quadrants whose closest point lies outside the circle
+        let closest = position.clamp(node.bounds.bottom_left(), node.bounds.top_right());
+        if closest.distance_squared(position) > radius * radius {
+            return;
+        }
+
+        match node.children {
+            Some(children) => (children..children + 4)
+                .for_each(|child| self.search_within(child, position, radius, found)),
+            None => found.extend_from_slice(&node.items),
+        }
+    }
+}
+
+impl NeighborIndex for Quadtree {
+    fn positions(&self) -> &[Vec2] {
+        &self.positions
+    }
+
+    fn update(&mut self, agents: &Agents) {
+        self.rebuild(agents);
+    }
+
+    /// Agents in the leaves overlapping the circle.
+    fn within(&self, position: Vec2, radius: f32, found: &mut Vec<usize>) {
+        self.search_within(0, position, radius, found);
+    }
+
+    fn display(&self, draw: &Draw, color: Rgba) {
+        for node in self.nodes[..self.len]
+            .iter()
+            .filter(|node| node.children.is_none())
+        {
+            draw.rect()
+                .xy(node.bounds.xy())
+                .wh(node.bounds.wh())
+                .no_fill()
+                .stroke_weight(1.0)
+                .stroke(color);
+        }
+    }
+}
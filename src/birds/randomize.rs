@@ -0,0 +1,135 @@
+use crate::config::{self, Config};
+use crate::tuning::Tunable;
+use nannou::rand::Rng;
+use serde::de::{MapAccess, Visitor};
+use serde::ser::SerializeMap;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt::{self, Formatter};
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Ranges random sets of parameters are drawn from, narrower than the ranges
+/// they can be tuned within so the flock stays plausible. Configured as
+///
+/// ```toml
+/// [randomize]
+/// cohesion_weight = [0.5, 2.0]
+/// ```
+///
+/// naming the parameters by their key, those left out keeping their default bounds.
+#[derive(Debug, Clone, Copy)]
+pub struct Bounds([(f32, f32); Tunable::ALL.len()]);
+
+impl Default for Bounds {
+    fn default() -> Self {
+        Bounds(Tunable::ALL.map(|tunable| match tunable {
+            Tunable::SeparationWeight => (0.5, 3.0),
+            Tunable::AlignmentWeight => (0.2, 2.5),
+            Tunable::CohesionWeight => (0.2, 2.5),
+            Tunable::DetectionRadius => (30.0, 120.0),
+            Tunable::MinDistance => (10.0, 50.0),
+            Tunable::PanicRadius => (60.0, 250.0),
+            Tunable::MaxForce => (0.02, 0.15),
+            Tunable::MaxTurnRate => (0.03, 0.3),
+            Tunable::Speed => (0.8, 3.0),
+        }))
+    }
+}
+
+/// Bounds of a parameter, checked as they are read so errors point at their line.
+#[derive(Deserialize)]
+struct Interval(#[serde(deserialize_with = "config::interval")] (f32, f32));
+
+impl<'de> Deserialize<'de> for Bounds {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_map(BoundsVisitor)
+    }
+}
+
+struct BoundsVisitor;
+
+impl<'de> Visitor<'de> for BoundsVisitor {
+    type Value = Bounds;
+
+    fn expecting(&self, formatter: &mut Formatter) -> fmt::Result {
+        formatter.write_str("a table of parameter bounds")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Bounds, A::Error> {
+        let mut bounds = Bounds::default();
+        while let Some(tunable) = map.next_key::<Tunable>()? {
+            bounds.0[tunable as usize] = map.next_value::<Interval>()?.0;
+        }
+        Ok(bounds)
+    }
+}
+
+impl Serialize for Bounds {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(Tunable::ALL.len()))?;
+        for tunable in Tunable::ALL {
+            map.serialize_entry(tunable.key(), &self.get(tunable))?;
+        }
+        map.end()
+    }
+}
+
+impl Bounds {
+    /// File every random set of parameters is appended to, next to where the
+    /// sketch is run
+    pub const HISTORY: &'static str = "randomized.csv";
+
+    pub fn get(&self, tunable: Tunable) -> (f32, f32) {
+        self.0[tunable as usize]
+    }
+
+    /// Configuration with every parameter drawn uniformly within its bounds, and
+    /// within the range it can be tuned in.
+    pub fn draw(&self, config: &Config, rng: &mut impl Rng) -> Config {
+        let mut config = config.clone();
+        for tunable in Tunable::ALL {
+            let (min, max) = tunable.range();
+            let (from, to) = self.get(tunable);
+            let (from, to) = (from.clamp(min, max), to.clamp(min, max));
+            let value = if from < to {
+                rng.gen_range(from..=to)
+            } else {
+                from
+            };
+            tunable.set(&mut config, value);
+        }
+        config
+    }
+
+    /// Append the parameters of a configuration to the history file, with the
+    /// time they were drawn at.
+    pub fn record(config: &Config) -> Result<(), String> {
+        let error = |error| format!("Could not write history {}: {error}", Self::HISTORY);
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(Self::HISTORY)
+            .map_err(error)?;
+
+        let mut line = String::new();
+        if file.metadata().map_err(error)?.len() == 0 {
+            line.push_str("time");
+            for tunable in Tunable::ALL {
+                line.push(',');
+                line.push_str(&tunable.name().replace(' ', "_"));
+            }
+            line.push('\n');
+        }
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        line.push_str(&timestamp.to_string());
+        for tunable in Tunable::ALL {
+            line.push_str(&format!(",{}", tunable.value(config)));
+        }
+        line.push('\n');
+        file.write_all(line.as_bytes()).map_err(error)
+    }
+}
@@ -0,0 +1,88 @@
+use crate::wall::Wall;
+use nannou::lyon::path::Path as LyonPath;
+use nannou::lyon::tessellation::FillRule;
+use nannou::prelude::*;
+
+/// Filled shape agents can't enter, made of closed rings with holes following
+/// the even-odd rule.
+pub struct Region {
+    rings: Vec<Vec<Vec2>>,
+}
+
+impl Region {
+    pub const COLOR: (f32, f32, f32, f32) = (0.3, 0.3, 0.3, 1.0);
+
+    pub fn new(rings: Vec<Vec<Vec2>>) -> Self {
+        Region { rings }
+    }
+
+    /// Edges of the rings.
+    fn edges(&self) -> impl Iterator<Item = (Vec2, Vec2)> + '_ {
+        self.rings.iter().flat_map(|ring| {
+            ring.iter()
+                .zip(ring.iter().cycle().skip(1))
+                .map(|(start, end)| (*start, *end))
+        })
+    }
+
+    /// Walls along the outline of the region for agents to steer around.
+    pub fn walls(&self) -> impl Iterator<Item = Wall> + '_ {
+        self.edges().map(|(start, end)| Wall::new(start, end))
+    }
+
+    /// Check if a point is inside the region by counting the edges a ray cast
+    /// from it crosses.
+    pub fn contains(&self, point: Vec2) -> bool {
+        self.edges()
+            .filter(|(start, end)| {
+                (start.y > point.y) != (end.y > point.y)
+                    && point.x
+                        < start.x + (point.y - start.y) / (end.y - start.y) * (end.x - start.x)
+            })
+            .count()
+            % 2
+            == 1
+    }
+
+    /// Move a position that ended up inside the region back onto its outline,
+    /// bouncing the velocity off of it.
+    pub fn eject(&self, position: Vec2, velocity: Vec2) -> (Vec2, Vec2) {
+        if !self.contains(position) {
+            return (position, velocity);
+        }
+
+        let closest = self
+            .edges()
+            .map(|(start, end)| Wall::new(start, end).closest_point(position))
+            .min_by(|a, b| {
+                a.distance_squared(position)
+                    .total_cmp(&b.distance_squared(position))
+            });
+        let Some(edge) = closest else {
+            return (position, velocity);
+        };
+
+        // Only the part of the velocity heading deeper inside is reflected
+        let normal = (edge - position).normalize_or_zero();
+        let inwards = velocity.dot(normal).min(0.0);
+        (edge + normal, velocity - normal * inwards * 2.0)
+    }
+
+    pub fn display(&self, draw: &Draw, color: Rgba) {
+        let mut builder = LyonPath::builder();
+        for ring in self.rings.iter().filter(|ring| ring.len() >= 3) {
+            builder.begin(ring[0].to_array().into());
+            for point in &ring[1..] {
+                builder.line_to(point.to_array().into());
+            }
+            builder.end(true);
+        }
+        let path = builder.build();
+
+        draw.path()
+            .fill()
+            .rule(FillRule::EvenOdd)
+            .events(path.iter())
+            .color(color);
+    }
+}
@@ -0,0 +1,70 @@
+use crate::batch::Batch;
+use std::ops::{Deref, DerefMut};
+use std::sync::Mutex;
+
+/// Buffers an agent or predator fills and empties again while updating, kept from
+/// one update to the next so steady-state steps allocate nothing once they have grown.
+#[derive(Debug, Default)]
+pub struct Scratch {
+    /// Agents possibly close enough to be neighbors
+    pub candidates: Vec<usize>,
+    /// Neighbors perceived, with their distance
+    pub neighbors: Vec<(usize, f32)>,
+    /// Agents close to a candidate, while counting its neighbors
+    pub nearby: Vec<usize>,
+    pub batch: Batch,
+    // Weight of every neighbor in each rule
+    pub alignment: Vec<f32>,
+    pub cohesion: Vec<f32>,
+    pub separation: Vec<f32>,
+    pub avoidance: Vec<f32>,
+}
+
+/// Scratch buffers shared by the threads updating agents, each taking one for
+/// as long as it updates agents and handing it back after.
+#[derive(Debug, Default)]
+pub struct ScratchPool {
+    free: Mutex<Vec<Scratch>>,
+}
+
+impl ScratchPool {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A free scratch, or a new one if all are taken.
+    pub fn take(&self) -> Pooled<'_> {
+        let scratch = self.free.lock().unwrap().pop().unwrap_or_default();
+        Pooled {
+            pool: self,
+            scratch,
+        }
+    }
+}
+
+/// Scratch taken from a pool, handed back when dropped.
+pub struct Pooled<'a> {
+    pool: &'a ScratchPool,
+    scratch: Scratch,
+}
+
+impl Deref for Pooled<'_> {
+    type Target = Scratch;
+
+    fn deref(&self) -> &Scratch {
+        &self.scratch
+    }
+}
+
+impl DerefMut for Pooled<'_> {
+    fn deref_mut(&mut self) -> &mut Scratch {
+        &mut self.scratch
+    }
+}
+
+impl Drop for Pooled<'_> {
+    fn drop(&mut self) {
+        let scratch = std::mem::take(&mut self.scratch);
+        self.pool.free.lock().unwrap().push(scratch);
+    }
+}
@@ -0,0 +1,15 @@
+// Sample of a blurred frame, added to the average with the blend constant as weight.
+
+@group(0) @binding(0) var sample_texture: texture_2d<f32>;
+
+@vertex
+fn vs_main(@builtin(vertex_index) vertex: u32) -> @builtin(position) vec4<f32> {
+    // Single triangle covering the whole target
+    let corner = vec2<f32>(f32((vertex << 1u) & 2u), f32(vertex & 2u));
+    return vec4<f32>(corner * 2.0 - 1.0, 0.0, 1.0);
+}
+
+@fragment
+fn fs_main(@builtin(position) position: vec4<f32>) -> @location(0) vec4<f32> {
+    return textureLoad(sample_texture, vec2<i32>(position.xy), 0);
+}
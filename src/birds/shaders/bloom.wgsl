@@ -0,0 +1,57 @@
+// Glow around bright agents: the bright parts of the scene blurred across, then
+// along, and added back over it.
+
+struct Uniforms {
+    // Step between the taps of the blur in texture coordinates
+    direction: vec2<f32>,
+    // Luminance below which nothing glows, 0 letting everything through
+    threshold: f32,
+    intensity: f32,
+}
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+}
+
+@group(0) @binding(0) var<uniform> uniforms: Uniforms;
+@group(0) @binding(1) var source: texture_2d<f32>;
+@group(0) @binding(2) var source_sampler: sampler;
+// Blurred glow, only bound when compositing
+@group(0) @binding(3) var glow: texture_2d<f32>;
+
+@vertex
+fn vs_main(@builtin(vertex_index) vertex: u32) -> VertexOutput {
+    // Single triangle covering the whole target
+    let corner = vec2<f32>(f32((vertex << 1u) & 2u), f32(vertex & 2u));
+    var out: VertexOutput;
+    out.position = vec4<f32>(corner * 2.0 - 1.0, 0.0, 1.0);
+    out.uv = vec2<f32>(corner.x, 1.0 - corner.y);
+    return out;
+}
+
+// Share of the color brighter than the threshold.
+fn bright(uv: vec2<f32>) -> vec3<f32> {
+    let color = textureSample(source, source_sampler, uv).rgb;
+    let luminance = dot(color, vec3<f32>(0.2126, 0.7152, 0.0722));
+    return color * max(luminance - uniforms.threshold, 0.0) / max(luminance, 0.0001);
+}
+
+@fragment
+fn fs_blur(in: VertexOutput) -> @location(0) vec4<f32> {
+    // Gaussian weights of the taps from the center outwards
+    var weights = array<f32, 5>(0.227027, 0.1945946, 0.1216216, 0.054054, 0.016216);
+    var color = bright(in.uv) * weights[0];
+    for (var i = 1; i < 5; i++) {
+        let offset = uniforms.direction * f32(i);
+        color += (bright(in.uv + offset) + bright(in.uv - offset)) * weights[i];
+    }
+    return vec4<f32>(color, 1.0);
+}
+
+@fragment
+fn fs_composite(in: VertexOutput) -> @location(0) vec4<f32> {
+    let scene = textureSample(source, source_sampler, in.uv);
+    let halo = textureSample(glow, source_sampler, in.uv).rgb;
+    return vec4<f32>(scene.rgb + halo * uniforms.intensity, scene.a);
+}
@@ -0,0 +1,194 @@
+// Flocking rules run on the GPU. Every frame the agents are bucketed into a
+// uniform grid with a counting sort, then each agent scans the 3 by 3 cells
+// around it to steer and moves, reading one buffer and writing the other.
+
+struct Agent {
+    position: vec2<f32>,
+    velocity: vec2<f32>,
+}
+
+struct Params {
+    count: u32,
+    grid_width: u32,
+    grid_height: u32,
+    cell_size: f32,
+    half_size: vec2<f32>,
+    speed: f32,
+    radius: f32,
+    separation_distance: f32,
+    separation: f32,
+    alignment: f32,
+    cohesion: f32,
+    max_force: f32,
+    max_neighbors: u32,
+    agent_size: f32,
+}
+
+@group(0) @binding(0) var<uniform> params: Params;
+@group(0) @binding(1) var<storage, read> agents_in: array<Agent>;
+@group(0) @binding(2) var<storage, read_write> agents_out: array<Agent>;
+@group(0) @binding(3) var<storage, read_write> counts: array<atomic<u32>>;
+@group(0) @binding(4) var<storage, read_write> offsets: array<u32>;
+@group(0) @binding(5) var<storage, read_write> cursors: array<atomic<u32>>;
+@group(0) @binding(6) var<storage, read_write> sorted: array<u32>;
+
+fn cell_coordinates(position: vec2<f32>) -> vec2<i32> {
+    let cell = vec2<i32>(floor((position + params.half_size) / params.cell_size));
+    return clamp(cell, vec2<i32>(0), vec2<i32>(i32(params.grid_width) - 1, i32(params.grid_height) - 1));
+}
+
+fn cell_index(cell: vec2<i32>) -> u32 {
+    return u32(cell.y) * params.grid_width + u32(cell.x);
+}
+
+// Offset between two positions through the closest edges of the wrapping window
+fn wrapped_offset(start: vec2<f32>, end: vec2<f32>) -> vec2<f32> {
+    let size = params.half_size * 2.0;
+    let offset = end - start;
+    return offset - size * round(offset / size);
+}
+
+fn limit(v: vec2<f32>, max_length: f32) -> vec2<f32> {
+    let length_v = length(v);
+    if length_v > max_length {
+        return v / length_v * max_length;
+    }
+    return v;
+}
+
+// Steering force turning the velocity towards a direction at full speed
+fn steer(velocity: vec2<f32>, direction: vec2<f32>) -> vec2<f32> {
+    if dot(direction, direction) == 0.0 {
+        return vec2<f32>(0.0);
+    }
+    return limit(normalize(direction) * params.speed - velocity, params.max_force);
+}
+
+@compute @workgroup_size(64)
+fn clear(@builtin(global_invocation_id) id: vec3<u32>) {
+    if id.x < params.grid_width * params.grid_height {
+        atomicStore(&counts[id.x], 0u);
+    }
+}
+
+@compute @workgroup_size(64)
+fn count(@builtin(global_invocation_id) id: vec3<u32>) {
+    if id.x < params.count {
+        atomicAdd(&counts[cell_index(cell_coordinates(agents_in[id.x].position))], 1u);
+    }
+}
+
+// Exclusive prefix sum of the counts, small enough for a single invocation
+@compute @workgroup_size(1)
+fn scan() {
+    var total = 0u;
+    for (var cell = 0u; cell < params.grid_width * params.grid_height; cell++) {
+        offsets[cell] = total;
+        atomicStore(&cursors[cell], total);
+        total += atomicLoad(&counts[cell]);
+    }
+}
+
+@compute @workgroup_size(64)
+fn scatter(@builtin(global_invocation_id) id: vec3<u32>) {
+    if id.x < params.count {
+        let cell = cell_index(cell_coordinates(agents_in[id.x].position));
+        sorted[atomicAdd(&cursors[cell], 1u)] = id.x;
+    }
+}
+
+@compute @workgroup_size(64)
+fn flock(@builtin(global_invocation_id) id: vec3<u32>) {
+    if id.x >= params.count {
+        return;
+    }
+    let agent = agents_in[id.x];
+
+    var separation = vec2<f32>(0.0);
+    var velocity_sum = vec2<f32>(0.0);
+    var offset_sum = vec2<f32>(0.0);
+    var neighbors = 0u;
+
+    // Agent only sees the neighbors in the cells around it, wrapping around the edges
+    let center = cell_coordinates(agent.position);
+    let grid = vec2<i32>(i32(params.grid_width), i32(params.grid_height));
+    for (var dy = -1; dy <= 1; dy++) {
+        for (var dx = -1; dx <= 1; dx++) {
+            let cell = cell_index((center + vec2<i32>(dx, dy) + grid) % grid);
+            let start = offsets[cell];
+            let end = start + atomicLoad(&counts[cell]);
+            for (var slot = start; slot < end && neighbors < params.max_neighbors; slot++) {
+                let other_index = sorted[slot];
+                if other_index == id.x {
+                    continue;
+                }
+                let other = agents_in[other_index];
+                let offset = wrapped_offset(agent.position, other.position);
+                let distance = length(offset);
+                if distance >= params.radius || distance == 0.0 {
+                    continue;
+                }
+
+                if distance < params.separation_distance {
+                    separation -= offset / distance * (1.0 - distance / params.separation_distance);
+                }
+                velocity_sum += other.velocity;
+                offset_sum += offset;
+                neighbors++;
+            }
+        }
+    }
+
+    // Agent should keep its distance, fly along and move towards its neighbors
+    var force = steer(agent.velocity, separation) * params.separation;
+    if neighbors > 0u {
+        force += steer(agent.velocity, velocity_sum) * params.alignment;
+        force += steer(agent.velocity, offset_sum / f32(neighbors)) * params.cohesion;
+    }
+
+    var velocity = agent.velocity + limit(force, params.max_force);
+    velocity = normalize(velocity) * params.speed;
+
+    // Agent wraps around the edges of the window
+    let size = params.half_size * 2.0;
+    let position = agent.position + velocity;
+    let wrapped = position - size * floor((position + params.half_size) / size);
+
+    agents_out[id.x] = Agent(wrapped, velocity);
+}
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) color: vec4<f32>,
+}
+
+// Every instance is an agent drawn as a triangle pointing along its velocity,
+// reading the agents the last frame wrote as its input
+@vertex
+fn vs_main(@builtin(vertex_index) vertex: u32, @builtin(instance_index) instance: u32) -> VertexOutput {
+    let agent = agents_in[instance];
+    let heading = normalize(agent.velocity);
+    let side = vec2<f32>(-heading.y, heading.x);
+
+    var corners = array<vec2<f32>, 3>(
+        vec2<f32>(1.0, 0.0),
+        vec2<f32>(-0.6, 0.5),
+        vec2<f32>(-0.6, -0.5),
+    );
+    let corner = corners[vertex] * params.agent_size;
+    let position = agent.position + heading * corner.x + side * corner.y;
+
+    // Agents are tinted by their heading so currents in the flock stand out
+    let angle = atan2(heading.y, heading.x);
+    let tint = 0.5 + 0.5 * vec3<f32>(cos(angle), cos(angle + 2.094), cos(angle + 4.189));
+
+    var out: VertexOutput;
+    out.position = vec4<f32>(position / params.half_size, 0.0, 1.0);
+    out.color = vec4<f32>(mix(vec3<f32>(1.0), tint, 0.6), 1.0);
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    return in.color;
+}
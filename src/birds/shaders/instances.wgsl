@@ -0,0 +1,44 @@
+// Triangles of the agents drawn in a single call, one instance per agent.
+
+struct Uniforms {
+    // Half of the size of the window, in xy
+    half_size: vec4<f32>,
+}
+
+@group(0) @binding(0) var<uniform> uniforms: Uniforms;
+
+struct Instance {
+    @location(0) position: vec2<f32>,
+    @location(1) rotation: f32,
+    @location(2) size: vec2<f32>,
+    @location(3) color: vec4<f32>,
+}
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) color: vec4<f32>,
+}
+
+@vertex
+fn vs_main(@builtin(vertex_index) vertex: u32, instance: Instance) -> VertexOutput {
+    // Same triangle as the ones drawn one by one, pointing towards 0 radians
+    var corners = array<vec2<f32>, 3>(
+        vec2<f32>(-0.5, 0.5),
+        vec2<f32>(0.5, 0.0),
+        vec2<f32>(-0.5, -0.5),
+    );
+    let corner = corners[vertex] * instance.size;
+    let c = cos(instance.rotation);
+    let s = sin(instance.rotation);
+    let rotated = vec2<f32>(corner.x * c - corner.y * s, corner.x * s + corner.y * c);
+
+    var out: VertexOutput;
+    out.position = vec4<f32>((instance.position + rotated) / uniforms.half_size.xy, 0.0, 1.0);
+    out.color = instance.color;
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    return in.color;
+}
@@ -0,0 +1,54 @@
+// Sprites of the agents drawn in a single call, one tinted quad per agent.
+
+struct Uniforms {
+    // Half of the size of the window in xy, and the scale of the sprites in z
+    half_size: vec4<f32>,
+}
+
+@group(0) @binding(0) var<uniform> uniforms: Uniforms;
+@group(0) @binding(1) var sprite: texture_2d<f32>;
+@group(0) @binding(2) var sprite_sampler: sampler;
+
+struct Instance {
+    @location(0) position: vec2<f32>,
+    @location(1) rotation: f32,
+    @location(2) size: vec2<f32>,
+    @location(3) color: vec4<f32>,
+}
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) color: vec4<f32>,
+    @location(1) tex_coords: vec2<f32>,
+}
+
+@vertex
+fn vs_main(@builtin(vertex_index) vertex: u32, instance: Instance) -> VertexOutput {
+    // Two triangles of a quad centered on the agent, the sprite facing 0 radians
+    var corners = array<vec2<f32>, 6>(
+        vec2<f32>(-0.5, -0.5),
+        vec2<f32>(0.5, -0.5),
+        vec2<f32>(0.5, 0.5),
+        vec2<f32>(-0.5, -0.5),
+        vec2<f32>(0.5, 0.5),
+        vec2<f32>(-0.5, 0.5),
+    );
+    let corner = corners[vertex];
+    let scaled = corner * instance.size * uniforms.half_size.z;
+    let c = cos(instance.rotation);
+    let s = sin(instance.rotation);
+    let rotated = vec2<f32>(scaled.x * c - scaled.y * s, scaled.x * s + scaled.y * c);
+
+    var out: VertexOutput;
+    out.position = vec4<f32>((instance.position + rotated) / uniforms.half_size.xy, 0.0, 1.0);
+    out.color = instance.color;
+    // Rows of the image go down
+    out.tex_coords = vec2<f32>(corner.x + 0.5, 0.5 - corner.y);
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    // The sprite is tinted by the color of the agent
+    return textureSample(sprite, sprite_sampler, in.tex_coords) * in.color;
+}
@@ -0,0 +1,84 @@
+use nannou::prelude::*;
+
+/// Outline of a bird, a body with swept wings, heading along x in units of its
+/// length and span. The outline of every pose of the wingbeat is computed once,
+/// only scaled, turned and moved for each agent.
+#[derive(Debug, Clone)]
+pub struct Silhouette {
+    /// Whether wings beat between their two positions, or stay spread
+    pub animated: bool,
+    /// Outlines from the wings swept back to spread wide
+    poses: Vec<[Vec2; Self::POINTS]>,
+}
+
+impl Silhouette {
+    const POINTS: usize = 9;
+    /// Poses cached across the wingbeat
+    const POSES: usize = 16;
+
+    /// Wings swept back at the bottom of a wingbeat, from the beak around the
+    /// left wing, tail and right wing
+    const SWEPT: [(f32, f32); Self::POINTS] = [
+        (0.5, 0.0),
+        (0.15, 0.08),
+        (-0.35, 0.3),
+        (-0.1, 0.06),
+        (-0.5, 0.12),
+        (-0.5, -0.12),
+        (-0.1, -0.06),
+        (-0.35, -0.3),
+        (0.15, -0.08),
+    ];
+    /// Wings spread wide at the top of a wingbeat and while gliding
+    const SPREAD: [(f32, f32); Self::POINTS] = [
+        (0.5, 0.0),
+        (0.15, 0.08),
+        (-0.1, 0.5),
+        (-0.1, 0.06),
+        (-0.5, 0.12),
+        (-0.5, -0.12),
+        (-0.1, -0.06),
+        (-0.1, -0.5),
+        (0.15, -0.08),
+    ];
+
+    pub fn new() -> Self {
+        let poses = (0..Self::POSES)
+            .map(|pose| {
+                let spread = pose as f32 / (Self::POSES - 1) as f32;
+                std::array::from_fn(|i| {
+                    let swept = Vec2::from(Self::SWEPT[i]);
+                    swept.lerp(Vec2::from(Self::SPREAD[i]), spread)
+                })
+            })
+            .collect();
+        Silhouette {
+            animated: true,
+            poses,
+        }
+    }
+
+    /// Outline of a size at a position, heading at an angle, with the wings
+    /// spread by a share between swept back at 0 and spread wide at 1.
+    pub fn points(
+        &self,
+        position: Vec2,
+        angle: f32,
+        size: Vec2,
+        spread: f32,
+    ) -> impl Iterator<Item = Vec2> + '_ {
+        let spread = if self.animated { spread } else { 1.0 };
+        let pose = (spread.clamp(0.0, 1.0) * (Self::POSES - 1) as f32).round() as usize;
+        let heading = vec2(angle.cos(), angle.sin());
+        self.poses[pose].iter().map(move |point| {
+            let point = *point * size;
+            position + heading * point.x + heading.perp() * point.y
+        })
+    }
+}
+
+impl Default for Silhouette {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,129 @@
+use crate::agent::Agent;
+use crate::color_mode::ColorMode;
+use crate::config::Config;
+use crate::lod::Sketch;
+use crate::plane::{Plane, Surroundings};
+use crate::predator::Predator;
+use crate::timestep::Timestep;
+use crate::triple_buffer::{triple_buffer, Reader, Writer};
+use nannou::prelude::*;
+use nannou::rand::rngs::StdRng;
+use std::cell::RefCell;
+use std::sync::mpsc::{self, Receiver, Sender, TryRecvError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Plane updated on a thread of its own at the fixed simulation rate, rendering
+/// only ever reading the latest finished step so neither waits on the other.
+pub struct SimThread {
+    /// Latest step published by the thread
+    snapshot: Reader<Snapshot>,
+    /// Window bounds and cursor position sent to the thread when they change
+    surroundings: Sender<(Rect, Vec2)>,
+    win_rect: Rect,
+    mouse: Vec2,
+    sketch: RefCell<Sketch>,
+}
+
+/// What is drawn of a step of the plane.
+#[derive(Clone, Default)]
+struct Snapshot {
+    agents: Vec<Agent>,
+    predators: Vec<Predator>,
+}
+
+impl Snapshot {
+    /// Copy what is drawn of the plane, reusing the buffers of the snapshot.
+    fn fill(&mut self, plane: &Plane) {
+        self.agents.clear();
+        self.agents.extend(plane.agents.iter());
+        self.predators.clone_from(&plane.predators);
+    }
+}
+
+impl SimThread {
+    /// Start stepping the plane within bounds, its random draws following the
+    /// given generator.
+    pub fn spawn(plane: Plane, win_rect: Rect, rng: StdRng) -> Self {
+        let mut initial = Snapshot::default();
+        initial.fill(&plane);
+        let (writer, snapshot) = triple_buffer(initial);
+        let (surroundings, receiver) = mpsc::channel();
+        thread::spawn(move || simulate(plane, win_rect, rng, writer, receiver));
+        SimThread {
+            snapshot,
+            surroundings,
+            win_rect,
+            mouse: Vec2::ZERO,
+            sketch: RefCell::new(Sketch::new(0)),
+        }
+    }
+
+    /// Let the thread know of the window bounds and the cursor if they changed.
+    pub fn update(&mut self, win_rect: Rect, mouse: Vec2) {
+        if win_rect != self.win_rect || mouse != self.mouse {
+            self.win_rect = win_rect;
+            self.mouse = mouse;
+            // The thread runs until the sender is dropped, so it is still listening
+            self.surroundings.send((win_rect, mouse)).unwrap();
+        }
+    }
+
+    pub fn display(&self, draw: &Draw) {
+        let config = Config::get();
+        let snapshot = self.snapshot.latest();
+        let mut sketch = self.sketch.borrow_mut();
+        sketch.reset(snapshot.agents.len());
+        snapshot.agents.iter().for_each(|agent| {
+            let color = agent.color(ColorMode::Species, &config);
+            agent.display(draw, color, &mut sketch, &config);
+        });
+        sketch.display(draw);
+        snapshot.predators.iter().for_each(|predator| {
+            predator.display(draw, Predator::COLOR.into());
+        });
+    }
+}
+
+/// Step the plane at the fixed rate, publishing every step, until the sketch
+/// hangs up the surroundings channel.
+fn simulate(
+    mut plane: Plane,
+    bounds: Rect,
+    mut rng: StdRng,
+    mut writer: Writer<Snapshot>,
+    receiver: Receiver<(Rect, Vec2)>,
+) {
+    let period = Duration::from_secs_f32(Timestep::DT);
+    let mut timestep = Timestep::new();
+    let mut surroundings = Surroundings {
+        bounds,
+        mouse: Vec2::ZERO,
+        time: 0.0,
+        step: 0,
+        darkness: 0.0,
+    };
+    loop {
+        let start = Instant::now();
+        loop {
+            match receiver.try_recv() {
+                Ok((bounds, mouse)) => (surroundings.bounds, surroundings.mouse) = (bounds, mouse),
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => return,
+            }
+        }
+
+        surroundings.time = timestep.time();
+        surroundings.step = timestep.step;
+        plane.step(&surroundings, &mut rng);
+        timestep.tick();
+
+        // Reuse the buffers the reader last gave back instead of allocating
+        writer.value().fill(&plane);
+        writer.publish();
+
+        if let Some(rest) = period.checked_sub(start.elapsed()) {
+            thread::sleep(rest);
+        }
+    }
+}
@@ -0,0 +1,268 @@
+use crate::agents::Agents;
+use crate::index::NeighborIndex;
+use nannou::prelude::*;
+use std::collections::HashMap;
+
+type Cell = (i32, i32);
+
+/// No agent, ending the list of a cell
+const NONE: usize = usize::MAX;
+
+/// Uniform grid bucketing agents by position so neighbor queries only scan the
+/// cells around a position, moving the agents that changed cell every step.
+///
+/// The agents of a cell are linked through arrays of every agent, so moving
+/// agents between cells never allocates once the cells they enter were visited.
+pub struct SpatialHash {
+    positions: Vec<Vec2>,
+    /// First agent of every cell, kept for agents entering cells left empty again
+    heads: HashMap<Cell, usize>,
+    /// Cell of every agent
+    cells: Vec<Cell>,
+    /// Agents before and after every agent in its cell, so it can leave the cell
+    /// in constant time
+    links: Vec<(usize, usize)>,
+    /// Seeds of the indexed agents in order, telling when the flock itself changed
+    seeds: Vec<u64>,
+    /// Corners of the box enclosing the occupied cells
+    min: Cell,
+    max: Cell,
+}
+
+impl SpatialHash {
+    /// About the detection radius of an agent, so most queries scan 3 by 3 cells
+    pub const CELL_SIZE: f32 = 60.0;
+
+    pub fn new(agents: &Agents) -> Self {
+        let mut grid = SpatialHash {
+            positions: Vec::with_capacity(agents.len()),
+            heads: HashMap::new(),
+            cells: Vec::with_capacity(agents.len()),
+            links: Vec::with_capacity(agents.len()),
+            seeds: Vec::with_capacity(agents.len()),
+            min: (i32::MAX, i32::MAX),
+            max: (i32::MIN, i32::MIN),
+        };
+        grid.rebuild(agents);
+        grid
+    }
+
+    /// Bucket every agent again, reusing the memory of the grid.
+    fn rebuild(&mut self, agents: &Agents) {
+        self.positions.clone_from(&agents.positions);
+        self.seeds.clone_from(&agents.seeds);
+        self.heads.values_mut().for_each(|head| *head = NONE);
+        self.cells.clear();
+        self.links.clear();
+        for &position in &agents.positions {
+            let cell = Self::cell(position);
+            self.cells.push(cell);
+            self.links.push((NONE, NONE));
+            self.link(self.cells.len() - 1, cell);
+        }
+        self.enclose();
+    }
+
+    /// Put an agent first in the list of a cell.
+    fn link(&mut self, i: usize, cell: Cell) {
+        let head = self.heads.entry(cell).or_insert(NONE);
+        let next = std::mem::replace(head, i);
+        if next != NONE {
+            self.links[next].0 = i;
+        }
+        self.links[i] = (NONE, next);
+        self.cells[i] = cell;
+    }
+
+    /// Take an agent out of the list of its cell.
+    fn unlink(&mut self, i: usize) {
+        let (previous, next) = self.links[i];
+        if previous == NONE {
+            *self.heads.get_mut(&self.cells[i]).unwrap() = next;
+        } else {
+            self.links[previous].1 = next;
+        }
+        if next != NONE {
+            self.links[next].0 = previous;
+        }
+    }
+
+    /// Agents in a cell.
+    fn members(&self, cell: Cell) -> impl Iterator<Item = usize> + '_ {
+        let mut i = self.heads.get(&cell).copied().unwrap_or(NONE);
+        std::iter::from_fn(move || {
+            let current = i;
+            (current != NONE).then(|| {
+                i = self.links[current].1;
+                current
+            })
+        })
+    }
+
+    /// Cells holding at least an agent.
+    fn filled(&self) -> impl Iterator<Item = Cell> + '_ {
+        self.heads
+            .iter()
+            .filter(|(_, &head)| head != NONE)
+            .map(|(&cell, _)| cell)
+    }
+
+    /// Fit the box enclosing the occupied cells to them.
+    fn enclose(&mut self) {
+        let (mut min, mut max) = ((i32::MAX, i32::MAX), (i32::MIN, i32::MIN));
+        for (x, y) in self.filled() {
+            min = (min.0.min(x), min.1.min(y));
+            max = (max.0.max(x), max.1.max(y));
+        }
+        (self.min, self.max) = (min, max);
+    }
+
+    /// Centers of the cells holding at least an agent, in no particular order as
+    /// the cells are kept in a hash map.
+    pub fn occupied(&self) -> impl Iterator<Item = Vec2> + '_ {
+        self.filled()
+            .map(|(x, y)| (vec2(x as f32, y as f32) + 0.5) * Self::CELL_SIZE)
+    }
+
+    fn cell(position: Vec2) -> Cell {
+        let cell = (position / Self::CELL_SIZE).floor();
+        (cell.x as i32, cell.y as i32)
+    }
+}
+
+impl NeighborIndex for SpatialHash {
+    fn positions(&self) -> &[Vec2] {
+        &self.positions
+    }
+
+    /// Move the agents that changed cell, bucketing them all again when agents
+    /// were added, removed or reordered.
+    fn update(&mut self, agents: &Agents) {
+        if agents.seeds != self.seeds {
+            self.rebuild(agents);
+            return;
+        }
+
+        for (i, &position) in agents.positions.iter().enumerate() {
+            self.positions[i] = position;
+            let cell = Self::cell(position);
+            if cell != self.cells[i] {
+                self.unlink(i);
+                self.link(i, cell);
+            }
+        }
+        self.enclose();
+    }
+
+    /// Agents in the cells overlapping the circle, scanning every occupied cell
+    /// for an infinite radius.
+    fn within(&self, position: Vec2, radius: f32, found: &mut Vec<usize>) {
+        let (low, high) = (Self::cell(position - radius), Self::cell(position + radius));
+        let (min_x, max_x) = (low.0.max(self.min.0), high.0.min(self.max.0));
+        let (min_y, max_y) = (low.1.max(self.min.1), high.1.min(self.max.1));
+        let cells = (min_y..=max_y).flat_map(move |y| (min_x..=max_x).map(move |x| (x, y)));
+        for cell in cells {
+            found.extend(self.members(cell));
+        }
+    }
+
+    fn display(&self, draw: &Draw, color: Rgba) {
+        for (x, y) in self.filled() {
+            let corner = vec2(x as f32, y as f32) * Self::CELL_SIZE;
+            draw.rect()
+                .xy(corner + Self::CELL_SIZE * 0.5)
+                .w_h(Self::CELL_SIZE, Self::CELL_SIZE)
+                .no_fill()
+                .stroke_weight(1.0)
+                .stroke(color);
+        }
+    }
+}
+
+/// Reorders agents cell by cell, row after row, so the agents a query scans lie
+/// next to each other in memory, keeping its buffers from one sort to the next.
+#[derive(Default)]
+pub struct CellSort {
+    /// Cell of every agent
+    cells: Vec<Cell>,
+    /// Position of the cell of every agent in the rows of the box enclosing them
+    keys: Vec<usize>,
+    /// Start of every cell in the sorted agents while counting them
+    starts: Vec<usize>,
+    /// Agents in sorted order
+    order: Vec<usize>,
+    sorted: Agents,
+}
+
+impl CellSort {
+    /// Cells per agent up to which sorting counts the agents of every cell
+    const MAX_CELLS_PER_AGENT: usize = 16;
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sort the agents by cell. Counting sort, keeping the order within a cell.
+    pub fn sort(&mut self, agents: &mut Agents) {
+        let CellSort {
+            cells,
+            keys,
+            starts,
+            order,
+            sorted,
+        } = self;
+        cells.clear();
+        cells.extend(
+            agents
+                .positions
+                .iter()
+                .map(|&position| SpatialHash::cell(position)),
+        );
+        let Some(min) = cells
+            .iter()
+            .copied()
+            .reduce(|a, b| (a.0.min(b.0), a.1.min(b.1)))
+        else {
+            return;
+        };
+        let max = cells
+            .iter()
+            .copied()
+            .reduce(|a, b| (a.0.max(b.0), a.1.max(b.1)))
+            .unwrap();
+        let width = (max.0 as i64 - min.0 as i64 + 1) as usize;
+        let height = (max.1 as i64 - min.1 as i64 + 1) as usize;
+        keys.clear();
+        keys.extend(cells.iter().map(|cell| {
+            (cell.1 as i64 - min.1 as i64) as usize * width
+                + (cell.0 as i64 - min.0 as i64) as usize
+        }));
+
+        order.clear();
+        order.extend(0..agents.len());
+        // Agents straying far from the rest would leave most counts empty, compare them instead
+        if width.saturating_mul(height) > agents.len() * Self::MAX_CELLS_PER_AGENT {
+            order.sort_unstable_by_key(|&i| (keys[i], i));
+        } else {
+            // Start of every cell in the sorted agents, from the number of agents in the ones before
+            starts.clear();
+            starts.resize(width * height, 0);
+            for &key in keys.iter() {
+                starts[key] += 1;
+            }
+            let mut start = 0;
+            for count in starts.iter_mut() {
+                (*count, start) = (start, start + *count);
+            }
+            for (i, &key) in keys.iter().enumerate() {
+                order[starts[key]] = i;
+                starts[key] += 1;
+            }
+        }
+
+        // Gather the agents in order, the old order keeping the memory for the next sort
+        sorted.clear();
+        sorted.extend(order.iter().map(|&i| agents.get(i)));
+        std::mem::swap(agents, sorted);
+    }
+}
@@ -0,0 +1,232 @@
+use crate::config;
+use serde::{Deserialize, Serialize};
+use std::fmt::{self, Display, Formatter};
+use std::ops::Range;
+use toml::Spanned;
+
+/// Parameters shared by every agent of a species.
+#[derive(Debug, Clone, Copy, Deserialize, Serialize)]
+pub struct Species {
+    #[serde(deserialize_with = "config::color")]
+    pub color: (f32, f32, f32, f32),
+    pub count: usize,
+    #[serde(deserialize_with = "config::positive")]
+    pub speed: f32,
+    #[serde(deserialize_with = "config::size")]
+    pub size: (f32, f32),
+    // Multipliers of the average rule weights in the agent settings
+    #[serde(default = "Species::unit", deserialize_with = "config::non_negative")]
+    pub separation_weight: f32,
+    #[serde(default = "Species::unit", deserialize_with = "config::non_negative")]
+    pub alignment_weight: f32,
+    #[serde(default = "Species::unit", deserialize_with = "config::non_negative")]
+    pub cohesion_weight: f32,
+}
+
+impl Species {
+    /// Species of the flock unless configured otherwise, though any number of
+    /// species may be configured
+    pub const DEFAULTS: [Species; 3] = [
+        Species {
+            color: (1.0, 1.0, 1.0, 1.0),
+            count: 120,
+            speed: 1.5,
+            size: (15.0, 15.0),
+            separation_weight: 1.0,
+            alignment_weight: 1.0,
+            cohesion_weight: 1.0,
+        },
+        Species {
+            color: (0.3, 0.7, 1.0, 1.0),
+            count: 60,
+            speed: 1.7,
+            size: (12.0, 12.0),
+            separation_weight: 1.0,
+            alignment_weight: 1.0,
+            cohesion_weight: 1.0,
+        },
+        Species {
+            color: (1.0, 0.8, 0.2, 1.0),
+            count: 20,
+            speed: 1.2,
+            size: (18.0, 18.0),
+            separation_weight: 1.0,
+            alignment_weight: 1.0,
+            cohesion_weight: 1.0,
+        },
+    ];
+
+    fn unit() -> f32 {
+        1.0
+    }
+
+    /// Radius of the circle enclosing the body of the agents of the species.
+    pub fn radius(&self) -> f32 {
+        let (width, height) = self.size;
+        width.max(height) * 0.5
+    }
+}
+
+/// Weights of the flocking rules an agent applies to a neighbor of a given species.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Interaction {
+    pub alignment: f32,
+    pub cohesion: f32,
+    pub separation: f32,
+    pub avoidance: f32,
+}
+
+impl Interaction {
+    /// Agents of the same species flock together
+    const FLOCK: Interaction = Interaction {
+        alignment: 1.0,
+        cohesion: 1.0,
+        separation: 1.0,
+        avoidance: 0.0,
+    };
+    /// Agents of different species only keep their distance
+    const IGNORE: Interaction = Interaction {
+        alignment: 0.0,
+        cohesion: 0.0,
+        separation: 1.0,
+        avoidance: 0.0,
+    };
+}
+
+/// Interaction between every pair of species, agents flocking with their own
+/// species and only keeping their distance from the others unless configured
+/// otherwise.
+///
+/// Configured as a list of `[[interaction]]` tables naming a pair of species by
+/// their position in the `[[species]]` list, from 0, and the weights to change,
+/// as in
+///
+/// ```toml
+/// [[interaction]]
+/// species = 0
+/// other = 2
+/// avoidance = 0.5
+/// ```
+///
+/// Pairs and weights left out keep their default. The species named are only
+/// checked once the species are known, by [`Interactions::resolve`].
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(from = "Vec<Rule>", into = "Vec<Rule>")]
+pub struct Interactions {
+    /// Tables of the configuration, applied over the defaults
+    rules: Vec<Rule>,
+    /// Number of species the weights are laid out for
+    count: usize,
+    /// Weights of every pair, a row of `count` weights for every species
+    weights: Vec<Interaction>,
+}
+
+impl Interactions {
+    /// Default interactions between a number of species.
+    pub fn new(count: usize) -> Self {
+        let mut interactions = Interactions::from(Vec::new());
+        interactions.resolve(count).unwrap();
+        interactions
+    }
+
+    /// Lay out the weights between a number of species, applying the rules over
+    /// the defaults. Fails on the first rule naming a species beyond them.
+    pub fn resolve(&mut self, count: usize) -> Result<(), UnknownSpecies> {
+        let mut weights = Vec::with_capacity(count * count);
+        for species in 0..count {
+            weights.extend((0..count).map(|other| {
+                if species == other {
+                    Interaction::FLOCK
+                } else {
+                    Interaction::IGNORE
+                }
+            }));
+        }
+
+        for rule in &self.rules {
+            let unknown = [&rule.species, &rule.other]
+                .into_iter()
+                .find(|species| *species.get_ref() >= count);
+            if let Some(species) = unknown {
+                return Err(UnknownSpecies {
+                    species: *species.get_ref(),
+                    count,
+                    span: species.span(),
+                });
+            }
+
+            let pair = &mut weights[rule.species.get_ref() * count + rule.other.get_ref()];
+            for (weight, value) in [
+                (&mut pair.alignment, rule.alignment),
+                (&mut pair.cohesion, rule.cohesion),
+                (&mut pair.separation, rule.separation),
+                (&mut pair.avoidance, rule.avoidance),
+            ] {
+                if let Some(value) = value {
+                    *weight = value;
+                }
+            }
+        }
+
+        self.count = count;
+        self.weights = weights;
+        Ok(())
+    }
+
+    pub fn between(&self, species: usize, other_species: usize) -> Interaction {
+        self.weights[species * self.count + other_species]
+    }
+}
+
+/// Weights an `[[interaction]]` table sets for a pair of species.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct Rule {
+    pub species: Spanned<usize>,
+    pub other: Spanned<usize>,
+    #[serde(default, deserialize_with = "config::optional_fraction")]
+    pub alignment: Option<f32>,
+    #[serde(default, deserialize_with = "config::optional_fraction")]
+    pub cohesion: Option<f32>,
+    #[serde(default, deserialize_with = "config::optional_fraction")]
+    pub separation: Option<f32>,
+    #[serde(default, deserialize_with = "config::optional_fraction")]
+    pub avoidance: Option<f32>,
+}
+
+/// Rule naming a species that isn't configured.
+#[derive(Debug, Clone)]
+pub struct UnknownSpecies {
+    pub species: usize,
+    /// Number of species configured
+    pub count: usize,
+    /// Where the species is named in the configuration
+    pub span: Range<usize>,
+}
+
+impl Display for UnknownSpecies {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(
+            f,
+            "interaction with species {}, but only species 0 to {} are configured",
+            self.species,
+            self.count - 1
+        )
+    }
+}
+
+impl From<Vec<Rule>> for Interactions {
+    fn from(rules: Vec<Rule>) -> Self {
+        Interactions {
+            rules,
+            count: 0,
+            weights: Vec::new(),
+        }
+    }
+}
+
+impl From<Interactions> for Vec<Rule> {
+    fn from(interactions: Interactions) -> Self {
+        interactions.rules
+    }
+}
@@ -0,0 +1,109 @@
+use crate::instancing::{Instance, InstanceBuffer};
+use nannou::prelude::*;
+use nannou::wgpu::util::DeviceExt;
+
+/// Pipeline drawing every agent as a quad textured with an image, such as a bird
+/// silhouette, tinted by the color of the agent. The image should face right, as
+/// the triangles do.
+pub struct Sprites {
+    uniforms: wgpu::Buffer,
+    bind_group: wgpu::BindGroup,
+    pipeline: wgpu::RenderPipeline,
+    instances: InstanceBuffer,
+}
+
+impl Sprites {
+    const ATTRIBUTES: [wgpu::VertexAttribute; 4] =
+        wgpu::vertex_attr_array![0 => Float32x2, 1 => Float32, 2 => Float32x2, 3 => Float32x4];
+
+    /// Factor the size of the triangles is scaled by, as images have margins
+    const SCALE: f32 = 1.5;
+
+    /// Pipeline drawing the image at a path.
+    pub fn load(window: &Window, path: &str) -> Result<Self, String> {
+        let texture = wgpu::Texture::from_path(window, path).map_err(|error| error.to_string())?;
+        let device = window.device();
+        let module = device.create_shader_module(wgpu::include_wgsl!("shaders/sprites.wgsl"));
+
+        let uniforms = device.create_buffer_init(&wgpu::BufferInitDescriptor {
+            label: Some("uniforms"),
+            contents: bytemuck::bytes_of(&Self::uniforms(window.rect())),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+        let view = texture.view().build();
+        let sampler_descriptor = wgpu::SamplerBuilder::new().into_descriptor();
+        let sampler = device.create_sampler(&sampler_descriptor);
+        let layout = wgpu::BindGroupLayoutBuilder::new()
+            .uniform_buffer(wgpu::ShaderStages::VERTEX, false)
+            .texture(
+                wgpu::ShaderStages::FRAGMENT,
+                false,
+                wgpu::TextureViewDimension::D2,
+                view.sample_type(),
+            )
+            .sampler(
+                wgpu::ShaderStages::FRAGMENT,
+                wgpu::sampler_filtering(&sampler_descriptor),
+            )
+            .build(device);
+        let bind_group = wgpu::BindGroupBuilder::new()
+            .binding(uniforms.as_entire_binding())
+            .texture_view(&view)
+            .sampler(&sampler)
+            .build(device, &layout);
+
+        // The transparent margins of the image are blended over what is below
+        let pipeline_layout =
+            wgpu::create_pipeline_layout(device, Some("sprites"), &[&layout], &[]);
+        let pipeline = wgpu::RenderPipelineBuilder::from_layout(&pipeline_layout, &module)
+            .vertex_entry_point("vs_main")
+            .fragment_shader(&module)
+            .fragment_entry_point("fs_main")
+            .add_instance_buffer::<Instance>(&Self::ATTRIBUTES)
+            .color_format(Frame::TEXTURE_FORMAT)
+            .color_blend(wgpu::BlendComponent {
+                src_factor: wgpu::BlendFactor::SrcAlpha,
+                dst_factor: wgpu::BlendFactor::OneMinusSrcAlpha,
+                operation: wgpu::BlendOperation::Add,
+            })
+            .sample_count(window.msaa_samples())
+            .build(device);
+
+        Ok(Sprites {
+            uniforms,
+            bind_group,
+            pipeline,
+            instances: InstanceBuffer::new(device, "sprites"),
+        })
+    }
+
+    /// Half of the size of the window, mapping window coordinates to clip space,
+    /// and the scale of the sprites.
+    fn uniforms(win_rect: Rect) -> [f32; 4] {
+        [win_rect.w() * 0.5, win_rect.h() * 0.5, Self::SCALE, 0.0]
+    }
+
+    /// Draw the instances over what is already in the frame.
+    pub fn display(&mut self, window: &Window, frame: &Frame, instances: &[Instance]) {
+        if instances.is_empty() {
+            return;
+        }
+        window.queue().write_buffer(
+            &self.uniforms,
+            0,
+            bytemuck::bytes_of(&Self::uniforms(window.rect())),
+        );
+        let buffer = self.instances.write(window, instances);
+
+        let mut encoder = frame.command_encoder();
+        let mut pass = wgpu::RenderPassBuilder::new()
+            .color_attachment(frame.texture_view(), |color| {
+                color.load_op(wgpu::LoadOp::Load)
+            })
+            .begin(&mut encoder);
+        pass.set_pipeline(&self.pipeline);
+        pass.set_bind_group(0, &self.bind_group, &[]);
+        pass.set_vertex_buffer(0, buffer);
+        pass.draw(0..6, 0..instances.len() as u32);
+    }
+}
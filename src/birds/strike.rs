@@ -0,0 +1,65 @@
+use crate::agents::Agents;
+use nannou::prelude::*;
+
+/// Hawk strike scattering the agents around the point it hits.
+#[derive(Debug, Clone, Copy)]
+pub struct Strike {
+    pub position: Vec2,
+    /// Frames since the strike
+    frame: u32,
+}
+
+impl Strike {
+    pub const COLOR: (f32, f32, f32, f32) = (1.0, 1.0, 1.0, 0.8);
+
+    const RADIUS: f32 = 200.0;
+    const IMPULSE: f32 = 8.0;
+
+    /// Frames the shockwave stays visible for
+    const DURATION: u32 = 30;
+
+    /// Strike at a position, pushing the agents around it away at once.
+    /// The closer the agent, the stronger the push.
+    pub fn new(position: Vec2, agents: &mut Agents) -> Self {
+        for i in 0..agents.len() {
+            if agents.is_perched(i) {
+                continue;
+            }
+            let away = agents.positions[i] - position;
+            let distance = away.length();
+            if distance < Self::RADIUS {
+                let velocity = &mut agents.velocities[i];
+                let direction = if distance > 0.0 {
+                    away / distance
+                } else {
+                    velocity.normalize_or_zero()
+                };
+                *velocity += direction * (1.0 - distance / Self::RADIUS) * Self::IMPULSE;
+            }
+        }
+
+        Strike { position, frame: 0 }
+    }
+
+    /// Age the shockwave, returning whether it is still visible.
+    pub fn update(&mut self) -> bool {
+        self.frame += 1;
+        self.frame < Self::DURATION
+    }
+
+    pub fn display(&self, draw: &Draw, color: Rgba) {
+        // Expanding and fading ring
+        let progress = self.frame as f32 / Self::DURATION as f32;
+        draw.ellipse()
+            .xy(self.position)
+            .radius(Self::RADIUS * progress)
+            .no_fill()
+            .stroke_weight(2.0)
+            .stroke(rgba(
+                color.red,
+                color.green,
+                color.blue,
+                color.alpha * (1.0 - progress),
+            ));
+    }
+}
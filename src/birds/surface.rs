@@ -0,0 +1,164 @@
+use crate::camera::Camera;
+use nannou::prelude::*;
+use nannou::rand::Rng;
+
+/// Curved surface agents can live on, centered on the origin.
+#[derive(Debug, Clone, Copy)]
+pub enum Surface {
+    Sphere {
+        radius: f32,
+    },
+    /// Torus around the vertical axis
+    Torus {
+        /// Distance from the center to the middle of the tube
+        major: f32,
+        /// Radius of the tube
+        minor: f32,
+    },
+}
+
+impl Surface {
+    /// Lines of latitude and longitude drawn on the surface
+    const GRID_LINES: usize = 12;
+    const GRID_RESOLUTION: usize = 48;
+
+    /// Random point on the surface.
+    pub fn random_point(&self, rng: &mut impl Rng) -> Vec3 {
+        let direction = vec3(
+            rng.gen_range(-1.0..1.0),
+            rng.gen_range(-1.0..1.0),
+            rng.gen_range(-1.0..1.0),
+        );
+        self.project(direction * self.extent())
+    }
+
+    /// Distance from the center to the outermost points of the surface.
+    fn extent(&self) -> f32 {
+        match *self {
+            Surface::Sphere { radius } => radius,
+            Surface::Torus { major, minor } => major + minor,
+        }
+    }
+
+    /// Closest point of the torus' central circle to a point, and the
+    /// direction away from the center in the horizontal plane.
+    fn ring(major: f32, point: Vec3) -> (Vec3, Vec3) {
+        let u = point.z.atan2(point.x);
+        let radial = vec3(u.cos(), 0.0, u.sin());
+        (radial * major, radial)
+    }
+
+    /// Closest point on the surface.
+    pub fn project(&self, point: Vec3) -> Vec3 {
+        match *self {
+            Surface::Sphere { radius } => point.normalize_or_zero() * radius,
+            Surface::Torus { major, minor } => {
+                let (center, radial) = Self::ring(major, point);
+                let offset = point - center;
+                let offset = if offset == Vec3::ZERO {
+                    radial
+                } else {
+                    offset.normalize()
+                };
+                center + offset * minor
+            }
+        }
+    }
+
+    /// Unit normal pointing out of the surface at a point on it.
+    pub fn normal(&self, point: Vec3) -> Vec3 {
+        match *self {
+            Surface::Sphere { .. } => point.normalize_or_zero(),
+            Surface::Torus { major, .. } => {
+                (point - Self::ring(major, point).0).normalize_or_zero()
+            }
+        }
+    }
+
+    /// Part of a vector tangent to the surface at a point on it.
+    pub fn tangent(&self, point: Vec3, vector: Vec3) -> Vec3 {
+        let normal = self.normal(point);
+        vector - normal * normal.dot(vector)
+    }
+
+    /// Angles around the vertical axis and around the tube of a point on the torus.
+    fn torus_angles(major: f32, point: Vec3) -> (f32, f32) {
+        let (center, radial) = Self::ring(major, point);
+        let offset = point - center;
+        (point.z.atan2(point.x), offset.y.atan2(offset.dot(radial)))
+    }
+
+    /// Vector tangent to the surface at `from`, pointing towards `to` along the
+    /// surface with a length of the distance between them along the surface.
+    pub fn offset(&self, from: Vec3, to: Vec3) -> Vec3 {
+        match *self {
+            Surface::Sphere { radius } => {
+                // Length of the arc of the great circle through both points
+                let normal = from / radius;
+                let angle = normal.dot(to / radius).clamp(-1.0, 1.0).acos();
+                self.tangent(from, to).normalize_or_zero() * angle * radius
+            }
+            Surface::Torus { major, minor } => {
+                // Walk the shortest way around both circles of the torus
+                let (u0, v0) = Self::torus_angles(major, from);
+                let (u1, v1) = Self::torus_angles(major, to);
+                let du = wrap_angle(u1 - u0);
+                let dv = wrap_angle(v1 - v0);
+
+                let along_u = vec3(-u0.sin(), 0.0, u0.cos());
+                let along_v = along_u.cross(self.normal(from));
+                let ring_radius = major + minor * v0.cos();
+                along_u * ring_radius * du + along_v * minor * dv
+            }
+        }
+    }
+
+    /// Draw lines of latitude and longitude across the surface.
+    pub fn display(&self, draw: &Draw, camera: &Camera, color: Rgba) {
+        let steps =
+            (0..=Self::GRID_RESOLUTION).map(|i| i as f32 / Self::GRID_RESOLUTION as f32 * TAU);
+        for line in 0..Self::GRID_LINES {
+            let a = line as f32 / Self::GRID_LINES as f32 * TAU;
+            let (around, across): (Vec<Vec3>, Vec<Vec3>) = steps
+                .clone()
+                .map(|b| (self.point(a, b), self.point(b, a)))
+                .unzip();
+            for curve in [around, across] {
+                let points: Vec<Vec2> = curve
+                    .into_iter()
+                    .filter_map(|point| camera.project(point).map(|(position, _)| position))
+                    .collect();
+                draw.polyline().weight(1.0).points(points).color(color);
+            }
+        }
+    }
+
+    /// Point of the surface at the given angles, the first one around the
+    /// vertical axis.
+    fn point(&self, u: f32, v: f32) -> Vec3 {
+        match *self {
+            Surface::Sphere { radius } => {
+                // Keep the second angle within the poles
+                let latitude = (v - PI) * 0.5;
+                vec3(
+                    latitude.cos() * u.cos(),
+                    latitude.sin(),
+                    latitude.cos() * u.sin(),
+                ) * radius
+            }
+            Surface::Torus { major, minor } => {
+                let ring_radius = major + minor * v.cos();
+                vec3(
+                    ring_radius * u.cos(),
+                    minor * v.sin(),
+                    ring_radius * u.sin(),
+                )
+            }
+        }
+    }
+}
+
+/// Equivalent angle between -π and π.
+fn wrap_angle(angle: f32) -> f32 {
+    (angle + PI).rem_euclid(TAU) - PI
+}
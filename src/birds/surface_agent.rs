@@ -0,0 +1,139 @@
+use crate::camera::Camera;
+use crate::config::Config;
+use crate::lod::Sketch;
+use crate::surface::Surface;
+use nannou::prelude::*;
+use nannou::rand::Rng;
+
+/// Agent living on a curved surface, its velocity tangent to the surface.
+#[derive(Debug, Clone, Copy)]
+pub struct SurfaceAgent {
+    pub position: Vec3,
+    pub velocity: Vec3,
+    pub species: usize,
+}
+
+impl SurfaceAgent {
+    const DETECTION_RADIUS: f32 = 60.0;
+    const MIN_DISTANCE: f32 = 30.0;
+
+    const SEPARATION_FACTOR: f32 = 0.05;
+    const AVERAGE_VELOCITY_FACTOR: f32 = 0.01;
+    const AVERAGE_POSITION_FACTOR: f32 = 1e-3;
+
+    pub fn new(surface: &Surface, species: usize, config: &Config, rng: &mut impl Rng) -> Self {
+        // Random position on the surface and random direction along it
+        let position = surface.random_point(rng);
+        let direction = vec3(
+            rng.gen_range(-1.0..1.0),
+            rng.gen_range(-1.0..1.0),
+            rng.gen_range(-1.0..1.0),
+        );
+        let velocity = surface.tangent(position, direction).normalize_or_zero()
+            * config.species[species].speed;
+
+        // Return new agent
+        SurfaceAgent {
+            position,
+            velocity,
+            species,
+        }
+    }
+
+    /// Move the agent along the surface, turning its velocity with the surface.
+    fn step(&mut self, surface: &Surface) {
+        self.position = surface.project(self.position + self.velocity);
+        self.velocity = surface.tangent(self.position, self.velocity);
+    }
+
+    pub fn update(&mut self, surface: &Surface, agents: &[SurfaceAgent], config: &Config) {
+        // Move agent
+        self.step(surface);
+
+        // Average offset towards, velocity of and separation from neighbors,
+        // all measured along the surface
+        let mut average_offset = Vec3::ZERO;
+        let mut average_velocity = Vec3::ZERO;
+        let mut separation = Vec3::ZERO;
+        let mut cohesion_weight = 0.0;
+        let mut alignment_weight = 0.0;
+
+        // Iterate over all agents
+        let interactions = &config.interactions;
+        for other in agents {
+            // Skip agents too far away through space before measuring along the surface
+            if self.position.distance_squared(other.position)
+                >= Self::DETECTION_RADIUS * Self::DETECTION_RADIUS
+            {
+                continue;
+            }
+            let offset = surface.offset(self.position, other.position);
+            let distance = offset.length();
+
+            // Check if other agent is in the detection range and not the agent itself
+            if distance < Self::DETECTION_RADIUS && distance > 0.0 {
+                let weights = interactions.between(self.species, other.species);
+
+                average_velocity += other.velocity * weights.alignment;
+                average_offset += offset * weights.cohesion;
+                alignment_weight += weights.alignment;
+                cohesion_weight += weights.cohesion;
+
+                // Move away from agents that are too close
+                // The closer the agent, the stronger the force
+                if distance < Self::MIN_DISTANCE {
+                    separation -= offset / distance
+                        * weights.separation
+                        * (1.0 - distance / Self::MIN_DISTANCE);
+                }
+            }
+        }
+
+        // Agent should move towards the same direction as its neighbors
+        if alignment_weight > 0.0 {
+            average_velocity /= alignment_weight;
+            self.velocity = self
+                .velocity
+                .lerp(average_velocity, Self::AVERAGE_VELOCITY_FACTOR);
+        }
+
+        // Agent should move towards the average position of its neighbors to stay with them
+        if cohesion_weight > 0.0 {
+            self.velocity += average_offset / cohesion_weight * Self::AVERAGE_POSITION_FACTOR;
+        }
+
+        // Agent should keep a minimum distance to its neighbors
+        self.velocity += separation * Self::SEPARATION_FACTOR;
+
+        // Keep velocity along the surface, normalize it and set speed
+        self.velocity = surface
+            .tangent(self.position, self.velocity)
+            .normalize_or_zero()
+            * config.species[self.species].speed;
+    }
+
+    pub fn display(&self, draw: &Draw, camera: &Camera, sketch: &mut Sketch, config: &Config) {
+        let species = &config.species[self.species];
+
+        // Skip agents behind the camera
+        let (Some((position, scale)), Some((ahead, _))) = (
+            camera.project(self.position),
+            camera.project(self.position + self.velocity),
+        ) else {
+            return;
+        };
+
+        // Farther agents are smaller and dimmer
+        let (r, g, b, a) = species.color;
+        let brightness = scale.min(1.0);
+        let color = rgba(r * brightness, g * brightness, b * brightness, a);
+
+        sketch.tri(
+            draw,
+            position,
+            (ahead - position).angle(),
+            Vec2::from(species.size) * scale,
+            color,
+        );
+    }
+}
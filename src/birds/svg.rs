@@ -0,0 +1,391 @@
+use crate::region::Region;
+use crate::wall::Wall;
+use nannou::lyon::math::{point, vector, Angle, Point};
+use nannou::lyon::path::iterator::PathIterator;
+use nannou::lyon::path::traits::SvgPathBuilder;
+use nannou::lyon::path::{ArcFlags, Path as LyonPath, PathEvent};
+use nannou::prelude::*;
+use std::fs;
+
+/// Walls and filled regions read from the paths of an SVG file.
+pub struct Shapes {
+    pub walls: Vec<Wall>,
+    pub regions: Vec<Region>,
+}
+
+/// Maximum distance between a curve and the segments approximating it, in SVG units
+const TOLERANCE: f32 = 0.5;
+
+/// Fraction of the window the shapes are scaled to fill
+const FILL: f32 = 0.8;
+
+/// Load the `path`, `polygon`, `polyline`, `line`, `rect` and `circle` elements
+/// of an SVG file, fitted to the middle of the window. Filled elements become
+/// regions agents can't enter and the others become walls. Transforms are ignored.
+pub fn load(file: &str, win_rect: Rect) -> Result<Shapes, String> {
+    let text = fs::read_to_string(file).map_err(|error| error.to_string())?;
+
+    // Flatten the curves of every element into polylines
+    let mut elements = Vec::new();
+    for (name, attributes) in tags(&text) {
+        let attribute = |key: &str| {
+            attributes
+                .iter()
+                .find(|(k, _)| *k == key)
+                .map(|(_, value)| value.as_str())
+        };
+        let number = |key: &str| attribute(key).and_then(|v| v.parse::<f32>().ok());
+
+        let mut builder = LyonPath::builder().with_svg();
+        let filled = match name {
+            "path" => {
+                path_data(&mut builder, attribute("d").unwrap_or_default());
+                true
+            }
+            "polygon" | "polyline" => {
+                let points = numbers(attribute("points").unwrap_or_default());
+                for (i, pair) in points.chunks_exact(2).enumerate() {
+                    let to = point(pair[0], pair[1]);
+                    if i == 0 {
+                        builder.move_to(to);
+                    } else {
+                        builder.line_to(to);
+                    }
+                }
+                if name == "polygon" {
+                    builder.close();
+                }
+                name == "polygon"
+            }
+            "line" => {
+                let (Some(x1), Some(y1), Some(x2), Some(y2)) =
+                    (number("x1"), number("y1"), number("x2"), number("y2"))
+                else {
+                    continue;
+                };
+                builder.move_to(point(x1, y1));
+                builder.line_to(point(x2, y2));
+                false
+            }
+            "rect" => {
+                let (x, y) = (number("x").unwrap_or(0.0), number("y").unwrap_or(0.0));
+                let (Some(w), Some(h)) = (number("width"), number("height")) else {
+                    continue;
+                };
+                builder.move_to(point(x, y));
+                builder.horizontal_line_to(x + w);
+                builder.vertical_line_to(y + h);
+                builder.horizontal_line_to(x);
+                builder.close();
+                true
+            }
+            "circle" => {
+                let (cx, cy) = (number("cx").unwrap_or(0.0), number("cy").unwrap_or(0.0));
+                let Some(r) = number("r") else {
+                    continue;
+                };
+                let radii = vector(r, r);
+                let flags = ArcFlags::default();
+                builder.move_to(point(cx + r, cy));
+                builder.arc_to(radii, Angle::zero(), flags, point(cx - r, cy));
+                builder.arc_to(radii, Angle::zero(), flags, point(cx + r, cy));
+                builder.close();
+                true
+            }
+            _ => continue,
+        };
+
+        // Shapes explicitly left unfilled are outlines
+        let unfilled = attribute("fill") == Some("none")
+            || attribute("style").is_some_and(|style| style.replace(' ', "").contains("fill:none"));
+
+        elements.push((polylines(&builder.build()), filled && !unfilled));
+    }
+
+    let points = elements
+        .iter()
+        .flat_map(|(polylines, _)| polylines.iter().flatten());
+    let Some(fit) = Fit::new(points, win_rect) else {
+        return Err(String::from("no shapes found"));
+    };
+
+    let mut shapes = Shapes {
+        walls: Vec::new(),
+        regions: Vec::new(),
+    };
+    for (polylines, filled) in elements {
+        let polylines: Vec<Vec<Vec2>> = polylines
+            .into_iter()
+            .map(|polyline| polyline.into_iter().map(|p| fit.apply(p)).collect())
+            .collect();
+
+        if filled {
+            let region = Region::new(polylines);
+            shapes.walls.extend(region.walls());
+            shapes.regions.push(region);
+        } else {
+            for polyline in polylines {
+                let segments = polyline.windows(2).map(|pair| Wall::new(pair[0], pair[1]));
+                shapes.walls.extend(segments);
+            }
+        }
+    }
+    Ok(shapes)
+}
+
+/// Scaling and offset mapping SVG coordinates, pointing down, into the window.
+struct Fit {
+    center: Vec2,
+    scale: f32,
+    target: Vec2,
+}
+
+impl Fit {
+    fn new<'a>(mut points: impl Iterator<Item = &'a Vec2>, win_rect: Rect) -> Option<Self> {
+        let first = *points.next()?;
+        let (min, max) = points.fold((first, first), |(min, max), p| (min.min(*p), max.max(*p)));
+        let size = (max - min).max(Vec2::splat(1.0));
+        Some(Fit {
+            center: (min + max) * 0.5,
+            scale: (win_rect.w() / size.x).min(win_rect.h() / size.y) * FILL,
+            target: win_rect.xy(),
+        })
+    }
+
+    fn apply(&self, point: Vec2) -> Vec2 {
+        let offset = (point - self.center) * self.scale;
+        self.target + vec2(offset.x, -offset.y)
+    }
+}
+
+/// Flattened sub-paths of a path, closed ones ending on their first point.
+fn polylines(path: &LyonPath) -> Vec<Vec<Vec2>> {
+    let vec = |p: Point| vec2(p.x, p.y);
+    let mut polylines = Vec::new();
+    let mut current = Vec::new();
+    for event in path.iter().flattened(TOLERANCE) {
+        match event {
+            PathEvent::Begin { at } => current = vec![vec(at)],
+            PathEvent::Line { to, .. } => current.push(vec(to)),
+            PathEvent::End { first, close, .. } => {
+                if close {
+                    current.push(vec(first));
+                }
+                if current.len() >= 2 {
+                    polylines.push(std::mem::take(&mut current));
+                }
+            }
+            // Curves are flattened into lines
+            PathEvent::Quadratic { .. } | PathEvent::Cubic { .. } => {}
+        }
+    }
+    polylines
+}
+
+/// Feed the commands of SVG path data to a builder.
+fn path_data(builder: &mut dyn SvgPathBuilder, data: &str) {
+    let mut command = None;
+    let mut rest = data.trim_start();
+    while !rest.is_empty() {
+        // A command letter, or more arguments repeating the previous command
+        let mut chars = rest.chars();
+        if let Some(letter) = chars.next().filter(char::is_ascii_alphabetic) {
+            command = Some(letter);
+            rest = chars.as_str();
+        }
+        let Some(letter) = command else {
+            return;
+        };
+
+        let count = match letter.to_ascii_uppercase() {
+            'Z' => 0,
+            'H' | 'V' => 1,
+            'M' | 'L' | 'T' => 2,
+            'S' | 'Q' => 4,
+            'C' => 6,
+            'A' => 7,
+            _ => return,
+        };
+        let mut args = [0.0; 7];
+        for arg in args.iter_mut().take(count) {
+            let Some((value, remaining)) = next_number(rest) else {
+                return;
+            };
+            *arg = value;
+            rest = remaining;
+        }
+
+        let [a, b, c, d, e, f, g] = args;
+        match letter {
+            'M' => builder.move_to(point(a, b)),
+            'm' => builder.relative_move_to(vector(a, b)),
+            'L' => builder.line_to(point(a, b)),
+            'l' => builder.relative_line_to(vector(a, b)),
+            'H' => builder.horizontal_line_to(a),
+            'h' => builder.relative_horizontal_line_to(a),
+            'V' => builder.vertical_line_to(a),
+            'v' => builder.relative_vertical_line_to(a),
+            'C' => builder.cubic_bezier_to(point(a, b), point(c, d), point(e, f)),
+            'c' => builder.relative_cubic_bezier_to(vector(a, b), vector(c, d), vector(e, f)),
+            'S' => builder.smooth_cubic_bezier_to(point(a, b), point(c, d)),
+            's' => builder.smooth_relative_cubic_bezier_to(vector(a, b), vector(c, d)),
+            'Q' => builder.quadratic_bezier_to(point(a, b), point(c, d)),
+            'q' => builder.relative_quadratic_bezier_to(vector(a, b), vector(c, d)),
+            'T' => builder.smooth_quadratic_bezier_to(point(a, b)),
+            't' => builder.smooth_relative_quadratic_bezier_to(vector(a, b)),
+            'A' | 'a' => {
+                let radii = vector(a, b);
+                let rotation = Angle::degrees(c);
+                let flags = ArcFlags {
+                    large_arc: d != 0.0,
+                    sweep: e != 0.0,
+                };
+                if letter == 'A' {
+                    builder.arc_to(radii, rotation, flags, point(f, g));
+                } else {
+                    builder.relative_arc_to(radii, rotation, flags, vector(f, g));
+                }
+            }
+            _ => builder.close(),
+        }
+
+        // Pairs following a move are implicit lines
+        command = match letter {
+            'M' => Some('L'),
+            'm' => Some('l'),
+            'Z' | 'z' => None,
+            _ => command,
+        };
+        rest = rest.trim_start_matches(|c: char| c.is_whitespace() || c == ',');
+    }
+}
+
+/// Parse the number at the start of a string, returning it and the rest of the string.
+fn next_number(text: &str) -> Option<(f32, &str)> {
+    let text = text.trim_start_matches(|c: char| c.is_whitespace() || c == ',');
+    let bytes = text.as_bytes();
+
+    // Numbers can follow each other without separators, as in `1-2` or `.5.5`
+    let mut end = 0;
+    let mut seen_dot = false;
+    let mut seen_exponent = false;
+    while end < bytes.len() {
+        match bytes[end] {
+            b'+' | b'-' if end == 0 || matches!(bytes[end - 1], b'e' | b'E') => {}
+            b'0'..=b'9' => {}
+            b'.' if !seen_dot && !seen_exponent => seen_dot = true,
+            b'e' | b'E' if !seen_exponent && end > 0 => seen_exponent = true,
+            _ => break,
+        }
+        end += 1;
+    }
+    let value = text[..end].parse().ok()?;
+    Some((value, &text[end..]))
+}
+
+/// All the numbers of a list separated by whitespace or commas.
+fn numbers(text: &str) -> Vec<f32> {
+    let mut numbers = Vec::new();
+    let mut rest = text;
+    while let Some((value, remaining)) = next_number(rest) {
+        numbers.push(value);
+        rest = remaining;
+    }
+    numbers
+}
+
+/// Names and attributes of the opening tags of an XML document.
+fn tags(text: &str) -> Vec<(&str, Vec<(&str, String)>)> {
+    text.split('<')
+        .skip(1)
+        .filter(|tag| !tag.starts_with(['/', '!', '?']))
+        .filter_map(|tag| {
+            let tag = &tag[..tag.find('>')?];
+            let tag = tag.trim_end_matches('/');
+            let name_end = tag.find(|c: char| c.is_whitespace()).unwrap_or(tag.len());
+            Some((&tag[..name_end], attributes(&tag[name_end..])))
+        })
+        .collect()
+}
+
+/// Attributes written as `key="value"` or `key='value'`.
+fn attributes(text: &str) -> Vec<(&str, String)> {
+    let mut attributes = Vec::new();
+    let mut rest = text;
+    while let Some(equals) = rest.find('=') {
+        let key = rest[..equals].trim();
+        let after = rest[equals + 1..].trim_start();
+        let Some(quote) = after.chars().next().filter(|c| *c == '"' || *c == '\'') else {
+            break;
+        };
+        let Some(length) = after[1..].find(quote) else {
+            break;
+        };
+        attributes.push((key, after[1..=length].to_string()));
+        rest = &after[length + 2..];
+    }
+    attributes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Polylines of SVG path data.
+    fn parse(data: &str) -> Vec<Vec<Vec2>> {
+        let mut builder = LyonPath::builder().with_svg();
+        path_data(&mut builder, data);
+        polylines(&builder.build())
+    }
+
+    #[test]
+    fn absolute_commands_are_parsed() {
+        assert_eq!(
+            parse("M 0 0 L 10 0 L 10 5 Z"),
+            [vec![
+                vec2(0.0, 0.0),
+                vec2(10.0, 0.0),
+                vec2(10.0, 5.0),
+                vec2(0.0, 0.0)
+            ]]
+        );
+    }
+
+    #[test]
+    fn relative_commands_follow_the_current_point() {
+        assert_eq!(
+            parse("m 1 1 l 10 0 v 5 h -10 z"),
+            [vec![
+                vec2(1.0, 1.0),
+                vec2(11.0, 1.0),
+                vec2(11.0, 6.0),
+                vec2(1.0, 6.0),
+                vec2(1.0, 1.0)
+            ]]
+        );
+    }
+
+    #[test]
+    fn pairs_after_a_move_are_lines() {
+        assert_eq!(
+            parse("M0,0 10,0 10-5m5 5 5 0"),
+            [
+                vec![vec2(0.0, 0.0), vec2(10.0, 0.0), vec2(10.0, -5.0)],
+                vec![vec2(15.0, 0.0), vec2(20.0, 0.0)]
+            ]
+        );
+    }
+
+    #[test]
+    fn malformed_data_keeps_what_came_before() {
+        assert_eq!(
+            parse("M 0 0 L 10 0 L 10"),
+            [vec![vec2(0.0, 0.0), vec2(10.0, 0.0)]]
+        );
+    }
+
+    #[test]
+    fn numbers_follow_each_other_without_separators() {
+        assert_eq!(numbers("1-2.5.5,3e1 -4"), [1.0, -2.5, 0.5, 30.0, -4.0]);
+    }
+}
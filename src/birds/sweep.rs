@@ -0,0 +1,263 @@
+use crate::agent::Agent;
+use crate::agents::Agents;
+use crate::args::Args;
+use crate::config::{AgentConfig, Config};
+use crate::flocks::Flocks;
+use crate::flow_field::FlowField;
+use crate::index::NeighborIndex;
+use crate::kd_tree::KdTree;
+use crate::plain_flock::PlainFlock;
+use crate::species::Species;
+use crate::vicsek::Vicsek;
+use nannou::prelude::*;
+use nannou::rand::rngs::StdRng;
+use nannou::rand::{Rng, SeedableRng};
+use noise::Perlin;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::str::FromStr;
+
+/// Evenly spaced values swept over, parsed from `from:to:count`, or from a
+/// single value kept throughout the sweep.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Span {
+    pub from: f32,
+    pub to: f32,
+    pub count: usize,
+}
+
+impl Span {
+    pub fn values(self) -> impl Iterator<Item = f32> {
+        let step = if self.count > 1 {
+            (self.to - self.from) / (self.count - 1) as f32
+        } else {
+            0.0
+        };
+        (0..self.count).map(move |i| self.from + step * i as f32)
+    }
+}
+
+impl FromStr for Span {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let parts: Vec<&str> = text.split(':').collect();
+        let number = |part: &str| {
+            part.trim()
+                .parse::<f32>()
+                .map_err(|error| format!("invalid bound {part}: {error}"))
+        };
+        let [from, to, count] = parts[..] else {
+            return match parts[..] {
+                [value] => number(value).map(|value| Span {
+                    from: value,
+                    to: value,
+                    count: 1,
+                }),
+                _ => Err(format!("expected from:to:count, got {text}")),
+            };
+        };
+        Ok(Span {
+            from: number(from)?,
+            to: number(to)?,
+            count: count
+                .trim()
+                .parse()
+                .map_err(|error| format!("invalid count {count}: {error}"))?,
+        })
+    }
+}
+
+/// Statistics of a run, averaged over its last steps once the flock settled.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Summary {
+    /// Alignment of the headings, from 0 to 1
+    pub polarization: f32,
+    /// Groups of agents connected through their detection radii
+    pub flocks: f32,
+    /// Distance from every agent to its nearest neighbor
+    pub nearest_distance: f32,
+}
+
+impl Summary {
+    /// Statistics of the flock at a step, finding the nearest neighbors through a
+    /// tree of the agents.
+    fn of(
+        agents: &mut Agents,
+        flocks: &mut Flocks,
+        tree: &mut KdTree,
+        nearest: &mut Vec<(usize, f32)>,
+    ) -> Self {
+        flocks.update(agents);
+        tree.update(agents);
+        let mut total = 0.0;
+        for (i, &position) in agents.positions.iter().enumerate() {
+            tree.nearest(position, 1, &|j, _| j != i, nearest);
+            total += nearest.first().map_or(0.0, |&(_, distance)| distance);
+        }
+        let nearest_distance = total / agents.len().max(1) as f32;
+        Summary {
+            polarization: Vicsek::polarization(agents),
+            flocks: flocks.count as f32,
+            nearest_distance,
+        }
+    }
+}
+
+/// Sweep of the flocking rules over a grid of heading noise and cohesion weights,
+/// run without a window.
+pub struct Sweep;
+
+impl Sweep {
+    /// Plane the flock flies over, about the size of a window
+    const BOUNDS: (f32, f32) = (1600.0, 1000.0);
+
+    /// Fraction of the steps at the end of a run its statistics are averaged over
+    const MEASURED: f32 = 0.25;
+    /// Steps between two measures
+    const MEASURE_INTERVAL: u32 = 10;
+
+    /// Run every combination of the swept values given on the command line and
+    /// write their statistics to a CSV file, a row per run.
+    pub fn run(path: &str) -> Result<(), String> {
+        let args = Args::get();
+        let bounds = Rect::from_w_h(Self::BOUNDS.0, Self::BOUNDS.1);
+        let seed = args.seed.or(Config::get().seed).unwrap_or_default();
+
+        let file =
+            File::create(path).map_err(|error| format!("Could not create {path}: {error}"))?;
+        let mut csv = BufWriter::new(file);
+        let write_error = |error: std::io::Error| format!("Could not write {path}: {error}");
+        writeln!(
+            csv,
+            "noise,cohesion,run,seed,polarization,flocks,nearest_distance"
+        )
+        .map_err(write_error)?;
+
+        let agent = Config::get().agent;
+        for noise in args.sweep_noise.values() {
+            for cohesion_weight in args.sweep_cohesion.values() {
+                // Agents draw their weights around the configured ones
+                Config::set(Config {
+                    agent: AgentConfig {
+                        cohesion_weight,
+                        ..agent
+                    },
+                    ..(*Config::get()).clone()
+                });
+
+                for run in 0..args.sweep_runs {
+                    let seed = seed.wrapping_add(run as u64);
+                    let summary = Self::simulate(bounds, noise, args.sweep_steps, seed);
+                    let Summary {
+                        polarization,
+                        flocks,
+                        nearest_distance,
+                    } = summary;
+                    writeln!(
+                        csv,
+                        "{noise},{cohesion_weight},{run},{seed},{polarization},{flocks},{nearest_distance}"
+                    )
+                    .map_err(write_error)?;
+                    println!(
+                        "noise {noise:.2}  cohesion {cohesion_weight:.2}  run {run}: polarization {polarization:.2}, {flocks:.1} flocks"
+                    );
+                }
+            }
+        }
+        csv.flush().map_err(write_error)
+    }
+
+    /// Statistics of a flock flying for a number of steps with some heading noise.
+    fn simulate(bounds: Rect, noise: f32, steps: u32, seed: u64) -> Summary {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let config = Config::get();
+        let mut agents = Agents::new();
+        for (species, Species { count, .. }) in config.species.iter().enumerate() {
+            agents.extend((0..*count).map(|_| Agent::new(bounds, species, &config, &mut rng)));
+        }
+        let flow_field = FlowField::new(&mut rng);
+        let mut flock = PlainFlock::new(agents, flow_field, Perlin::new(rng.gen()));
+        flock.vicsek = Vicsek { noise };
+
+        let mut flocks = Flocks::new();
+        let mut tree = KdTree::new(&flock.agents);
+        let mut nearest = Vec::new();
+        let mut total = Summary::default();
+        let mut measures = 0;
+        let first_measured = (steps as f32 * (1.0 - Self::MEASURED)) as u32;
+        for step in 0..steps {
+            flock.step(bounds);
+            if step >= first_measured && (steps - 1 - step).is_multiple_of(Self::MEASURE_INTERVAL) {
+                let summary = Summary::of(&mut flock.agents, &mut flocks, &mut tree, &mut nearest);
+                total.polarization += summary.polarization;
+                total.flocks += summary.flocks;
+                total.nearest_distance += summary.nearest_distance;
+                measures += 1;
+            }
+        }
+
+        let measures = measures.max(1) as f32;
+        Summary {
+            polarization: total.polarization / measures,
+            flocks: total.flocks / measures,
+            nearest_distance: total.nearest_distance / measures,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn spans_are_parsed() {
+        let span: Span = "0:3:7".parse().unwrap();
+        assert_eq!(
+            span,
+            Span {
+                from: 0.0,
+                to: 3.0,
+                count: 7
+            }
+        );
+        assert_eq!(
+            span.values().collect::<Vec<_>>(),
+            [0.0, 0.5, 1.0, 1.5, 2.0, 2.5, 3.0]
+        );
+    }
+
+    #[test]
+    fn single_values_are_spans_of_one() {
+        let span: Span = "1.5".parse().unwrap();
+        assert_eq!(
+            span,
+            Span {
+                from: 1.5,
+                to: 1.5,
+                count: 1
+            }
+        );
+        assert_eq!(span.values().collect::<Vec<_>>(), [1.5]);
+    }
+
+    #[test]
+    fn malformed_spans_are_rejected() {
+        assert_eq!(
+            "0:1".parse::<Span>(),
+            Err("expected from:to:count, got 0:1".to_string())
+        );
+        assert_eq!(
+            "0:1:2:3".parse::<Span>(),
+            Err("expected from:to:count, got 0:1:2:3".to_string())
+        );
+        assert!("a:1:2"
+            .parse::<Span>()
+            .unwrap_err()
+            .starts_with("invalid bound a"));
+        assert!("0:1:-2"
+            .parse::<Span>()
+            .unwrap_err()
+            .starts_with("invalid count -2"));
+    }
+}
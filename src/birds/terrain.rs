@@ -0,0 +1,110 @@
+use nannou::image;
+use nannou::prelude::*;
+use std::path::Path;
+
+/// How the terrain steers agents.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Slope {
+    Off,
+    /// Agents glide down towards the valleys
+    Downhill,
+    /// Agents climb up towards the ridges
+    Ridges,
+}
+
+impl Slope {
+    pub fn next(self) -> Self {
+        match self {
+            Slope::Off => Slope::Downhill,
+            Slope::Downhill => Slope::Ridges,
+            Slope::Ridges => Slope::Off,
+        }
+    }
+}
+
+/// Heightmap stretched over the window, sampled on a grid of cells.
+pub struct Terrain {
+    pub slope: Slope,
+    /// Heights between 0 and 1
+    cells: Vec<f32>,
+    columns: usize,
+    rows: usize,
+    origin: Vec2,
+}
+
+impl Terrain {
+    pub const COLOR: (f32, f32, f32) = (0.5, 0.45, 0.35);
+    const CELL_SIZE: f32 = 16.0;
+
+    /// Height difference between neighboring cells at which the force is strongest
+    const STEEPNESS: f32 = 0.05;
+
+    /// Load a grayscale heightmap, the brightest pixels being the highest.
+    pub fn load(path: impl AsRef<Path>, win_rect: Rect) -> image::ImageResult<Self> {
+        let heightmap = image::open(path)?.to_luma8();
+        let columns = (win_rect.w() / Self::CELL_SIZE).ceil() as usize;
+        let rows = (win_rect.h() / Self::CELL_SIZE).ceil() as usize;
+
+        // Sample the pixel under the center of every cell, the image's rows going downwards
+        let (width, height) = heightmap.dimensions();
+        let cells = (0..rows)
+            .flat_map(|row| (0..columns).map(move |column| (column, row)))
+            .map(|(column, row)| {
+                let x = (column as f32 + 0.5) / columns as f32 * width as f32;
+                let y = (1.0 - (row as f32 + 0.5) / rows as f32) * height as f32;
+                let pixel =
+                    heightmap.get_pixel((x as u32).min(width - 1), (y as u32).min(height - 1));
+                pixel.0[0] as f32 / 255.0
+            })
+            .collect();
+
+        Ok(Terrain {
+            slope: Slope::Off,
+            cells,
+            columns,
+            rows,
+            origin: win_rect.bottom_left(),
+        })
+    }
+
+    /// Height at a position, clamped to the edges of the grid.
+    fn sample(&self, position: Vec2) -> f32 {
+        let cell = ((position - self.origin) / Self::CELL_SIZE).floor();
+        let column = (cell.x.max(0.0) as usize).min(self.columns - 1);
+        let row = (cell.y.max(0.0) as usize).min(self.rows - 1);
+        self.cells[row * self.columns + column]
+    }
+
+    /// Direction in which the height increases the most, per cell.
+    fn gradient(&self, position: Vec2) -> Vec2 {
+        let dx = vec2(Self::CELL_SIZE, 0.0);
+        let dy = vec2(0.0, Self::CELL_SIZE);
+        vec2(
+            self.sample(position + dx) - self.sample(position - dx),
+            self.sample(position + dy) - self.sample(position - dy),
+        ) * 0.5
+    }
+
+    /// Direction of the force at a position, its length growing with the
+    /// steepness of the terrain up to 1.
+    pub fn force(&self, position: Vec2) -> Vec2 {
+        let gradient = self.gradient(position);
+        let strength = (gradient.length() / Self::STEEPNESS).min(1.0);
+        match self.slope {
+            Slope::Off => Vec2::ZERO,
+            Slope::Downhill => -gradient.normalize_or_zero() * strength,
+            Slope::Ridges => gradient.normalize_or_zero() * strength,
+        }
+    }
+
+    pub fn display(&self, draw: &Draw, color: (f32, f32, f32)) {
+        let (r, g, b) = color;
+        for (i, height) in self.cells.iter().enumerate() {
+            let cell = vec2((i % self.columns) as f32, (i / self.columns) as f32);
+            draw.rect()
+                .xy(self.origin + (cell + 0.5) * Self::CELL_SIZE)
+                .w_h(Self::CELL_SIZE, Self::CELL_SIZE)
+                .color(rgba(r, g, b, height * 0.3));
+        }
+    }
+}
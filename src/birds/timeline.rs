@@ -0,0 +1,243 @@
+use crate::config::{self, Config};
+use crate::tuning::Tunable;
+use serde::de::{Error, MapAccess, Visitor};
+use serde::ser::SerializeMap;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt::{self, Formatter};
+
+/// Values some of the parameters reach at a time of the simulation, each set
+/// by the key of the parameter next to the time.
+#[derive(Debug, Clone, Copy)]
+pub struct Keyframe {
+    /// Seconds simulated since the start
+    pub time: f32,
+    /// Value of every parameter, in the order of [`Tunable::ALL`], unless left out
+    pub values: [Option<f32>; Tunable::ALL.len()],
+}
+
+impl Keyframe {
+    fn value(&self, tunable: Tunable) -> Option<f32> {
+        self.values[tunable as usize]
+    }
+}
+
+/// Setting of a `[[keyframe]]` table.
+enum Key {
+    Time,
+    Value(Tunable),
+}
+
+impl<'de> Deserialize<'de> for Key {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let key = String::deserialize(deserializer)?;
+        if key == "time" {
+            return Ok(Key::Time);
+        }
+        Tunable::from_key(&key).map(Key::Value).ok_or_else(|| {
+            D::Error::custom(format!(
+                "unknown field `{key}`, expected `time` or one of `{}`",
+                Tunable::KEYS.join("`, `")
+            ))
+        })
+    }
+}
+
+/// Time of a keyframe, checked as it is read so errors point at its line.
+#[derive(Deserialize)]
+struct Time(#[serde(deserialize_with = "config::non_negative")] f32);
+
+impl<'de> Deserialize<'de> for Keyframe {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_map(KeyframeVisitor)
+    }
+}
+
+struct KeyframeVisitor;
+
+impl<'de> Visitor<'de> for KeyframeVisitor {
+    type Value = Keyframe;
+
+    fn expecting(&self, formatter: &mut Formatter) -> fmt::Result {
+        formatter.write_str("a keyframe table")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Keyframe, A::Error> {
+        let mut time = None;
+        let mut values = [None; Tunable::ALL.len()];
+        while let Some(key) = map.next_key()? {
+            match key {
+                Key::Time => time = Some(map.next_value::<Time>()?.0),
+                Key::Value(tunable) => values[tunable as usize] = Some(map.next_value()?),
+            }
+        }
+        let time = time.ok_or_else(|| A::Error::missing_field("time"))?;
+        Ok(Keyframe { time, values })
+    }
+}
+
+impl Serialize for Keyframe {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(None)?;
+        map.serialize_entry("time", &self.time)?;
+        for tunable in Tunable::ALL {
+            if let Some(value) = self.value(tunable) {
+                map.serialize_entry(tunable.key(), &value)?;
+            }
+        }
+        map.end()
+    }
+}
+
+/// Parameters changing on their own as the simulation runs, so a recording can
+/// show the flock evolving. Configured as a list of `[[keyframe]]` tables, as in
+///
+/// ```toml
+/// [[keyframe]]
+/// time = 0.0
+/// cohesion_weight = 0.0
+///
+/// [[keyframe]]
+/// time = 60.0
+/// cohesion_weight = 3.0
+/// ```
+///
+/// Each parameter is interpolated linearly between the keyframes setting it,
+/// holding its first value before them and its last value after them.
+/// Parameters no keyframe sets keep their configured value.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(try_from = "Vec<Keyframe>", into = "Vec<Keyframe>")]
+pub struct Timeline(Vec<Keyframe>);
+
+impl Timeline {
+    /// Steps between updates of the parameters, as every update replaces the
+    /// configuration
+    pub const STEPS: u64 = 12;
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Configuration with the keyframed parameters at their value at a time.
+    pub fn at(&self, config: &Config, time: f32) -> Config {
+        let mut config = config.clone();
+        for tunable in Tunable::ALL {
+            if let Some(value) = self.value(tunable, time) {
+                tunable.set(&mut config, value);
+            }
+        }
+        config
+    }
+
+    /// Value of a parameter at a time, if any keyframe sets it.
+    fn value(&self, tunable: Tunable, time: f32) -> Option<f32> {
+        let mut before = None;
+        for keyframe in &self.0 {
+            let Some(value) = keyframe.value(tunable) else {
+                continue;
+            };
+            if keyframe.time > time {
+                return Some(match before {
+                    Some((from_time, from)) => {
+                        let progress = (time - from_time) / (keyframe.time - from_time);
+                        from + (value - from) * progress
+                    }
+                    None => value,
+                });
+            }
+            before = Some((keyframe.time, value));
+        }
+        before.map(|(_, value)| value)
+    }
+}
+
+impl TryFrom<Vec<Keyframe>> for Timeline {
+    type Error = String;
+
+    fn try_from(mut keyframes: Vec<Keyframe>) -> Result<Self, Self::Error> {
+        for keyframe in &keyframes {
+            for tunable in Tunable::ALL {
+                let (min, max) = tunable.range();
+                match keyframe.value(tunable) {
+                    Some(value) if !(min..=max).contains(&value) => {
+                        return Err(format!(
+                            "expected a {} from {min} to {max} at {} s, got {value}",
+                            tunable.name(),
+                            keyframe.time
+                        ))
+                    }
+                    _ => {}
+                }
+            }
+        }
+        keyframes.sort_by(|a, b| a.time.total_cmp(&b.time));
+        Ok(Timeline(keyframes))
+    }
+}
+
+impl From<Timeline> for Vec<Keyframe> {
+    fn from(timeline: Timeline) -> Self {
+        timeline.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keyframe(time: f32, cohesion_weight: Option<f32>, speed: Option<f32>) -> Keyframe {
+        let mut values = [None; Tunable::ALL.len()];
+        values[Tunable::CohesionWeight as usize] = cohesion_weight;
+        values[Tunable::Speed as usize] = speed;
+        Keyframe { time, values }
+    }
+
+    fn timeline() -> Timeline {
+        Timeline::try_from(vec![
+            keyframe(20.0, Some(3.0), None),
+            keyframe(10.0, Some(1.0), Some(1.0)),
+            keyframe(30.0, None, Some(3.0)),
+        ])
+        .unwrap()
+    }
+
+    #[test]
+    fn interpolates_between_keyframes() {
+        let timeline = timeline();
+        assert_eq!(timeline.value(Tunable::CohesionWeight, 15.0), Some(2.0));
+        assert_eq!(timeline.value(Tunable::CohesionWeight, 12.5), Some(1.5));
+        // Keyframes not setting a parameter are skipped over
+        assert_eq!(timeline.value(Tunable::Speed, 20.0), Some(2.0));
+    }
+
+    #[test]
+    fn holds_the_first_value_before_the_keyframes() {
+        let timeline = timeline();
+        assert_eq!(timeline.value(Tunable::CohesionWeight, 0.0), Some(1.0));
+        assert_eq!(timeline.value(Tunable::Speed, 10.0), Some(1.0));
+    }
+
+    #[test]
+    fn holds_the_last_value_after_the_keyframes() {
+        let timeline = timeline();
+        assert_eq!(timeline.value(Tunable::CohesionWeight, 25.0), Some(3.0));
+        assert_eq!(timeline.value(Tunable::Speed, 100.0), Some(3.0));
+    }
+
+    #[test]
+    fn keeps_parameters_no_keyframe_sets() {
+        let timeline = timeline();
+        assert_eq!(timeline.value(Tunable::DetectionRadius, 15.0), None);
+
+        let config = Config::default();
+        let at = timeline.at(&config, 15.0);
+        assert_eq!(at.agent.cohesion_weight, 2.0);
+        assert_eq!(at.agent.detection_radius, config.agent.detection_radius);
+        assert!((Tunable::Speed.value(&at) - 1.5).abs() < 1e-6);
+    }
+
+    #[test]
+    fn rejects_values_out_of_range() {
+        let error = Timeline::try_from(vec![keyframe(5.0, Some(-1.0), None)]).unwrap_err();
+        assert!(error.contains("at 5 s, got -1"), "{error}");
+    }
+}
@@ -0,0 +1,49 @@
+/// Clock stepping the simulation at a fixed rate whatever the frame rate of the
+/// window, so the flock behaves the same on every monitor.
+#[derive(Debug, Default)]
+pub struct Timestep {
+    /// Seconds elapsed and not simulated yet
+    accumulator: f32,
+    /// Steps simulated since the start
+    pub step: u64,
+}
+
+impl Timestep {
+    /// Seconds simulated by a step
+    pub const DT: f32 = 1.0 / 60.0;
+
+    /// Steps taken in a frame at most, so a slow frame doesn't leave the
+    /// simulation ever further behind
+    const MAX_STEPS: u32 = 4;
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add the seconds elapsed since the last frame.
+    /// Returns the number of steps to simulate to catch up.
+    pub fn advance(&mut self, seconds: f32) -> u32 {
+        self.accumulator += seconds;
+        let steps = (self.accumulator / Self::DT) as u32;
+        self.accumulator -= steps as f32 * Self::DT;
+        if steps > Self::MAX_STEPS {
+            self.accumulator = 0.0;
+        }
+        steps.min(Self::MAX_STEPS)
+    }
+
+    /// Count a simulated step.
+    pub fn tick(&mut self) {
+        self.step += 1;
+    }
+
+    /// Seconds simulated since the start, the time the flock lives in.
+    pub fn time(&self) -> f32 {
+        self.step as f32 * Self::DT
+    }
+
+    /// How far the current time lies between the last step and the next one, from 0 to 1.
+    pub fn alpha(&self) -> f32 {
+        (self.accumulator / Self::DT).clamp(0.0, 1.0)
+    }
+}
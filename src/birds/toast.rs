@@ -0,0 +1,67 @@
+use nannou::prelude::*;
+
+/// Short message shown at the bottom of the window, fading after a while.
+#[derive(Debug, Clone)]
+pub struct Toast {
+    message: String,
+    color: (f32, f32, f32),
+    duration: u32,
+    frame: u32,
+}
+
+impl Toast {
+    const INFO_COLOR: (f32, f32, f32) = (0.6, 1.0, 0.6);
+    const ERROR_COLOR: (f32, f32, f32) = (1.0, 0.4, 0.3);
+    // Frames messages are shown for, fading over the last third
+    const DURATION: u32 = 180;
+    const BRIEF_DURATION: u32 = 90;
+
+    pub fn info(message: impl Into<String>) -> Self {
+        Toast {
+            message: message.into(),
+            color: Self::INFO_COLOR,
+            duration: Self::DURATION,
+            frame: 0,
+        }
+    }
+
+    /// Message shown for about a second, as feedback to a key press.
+    pub fn brief(message: impl Into<String>) -> Self {
+        Toast {
+            message: message.into(),
+            color: Self::INFO_COLOR,
+            duration: Self::BRIEF_DURATION,
+            frame: 0,
+        }
+    }
+
+    /// Message reporting a failure, shown longer so it can be read.
+    pub fn error(message: impl Into<String>) -> Self {
+        Toast {
+            message: message.into(),
+            color: Self::ERROR_COLOR,
+            duration: 2 * Self::DURATION,
+            frame: 0,
+        }
+    }
+
+    /// Age the message, returning whether it is still visible.
+    pub fn update(&mut self) -> bool {
+        self.frame += 1;
+        self.frame < self.duration
+    }
+
+    pub fn display(&self, draw: &Draw, win_rect: Rect) {
+        let (r, g, b) = self.color;
+        let fade_frames = self.duration / 3;
+        let fade = (self.duration - self.frame).min(fade_frames) as f32 / fade_frames as f32;
+
+        let area = win_rect.pad(20.0);
+        draw.text(&self.message)
+            .xy(area.xy())
+            .wh(area.wh())
+            .align_text_bottom()
+            .font_size(14)
+            .color(rgba(r, g, b, fade));
+    }
+}
@@ -0,0 +1,60 @@
+use nannou::prelude::*;
+
+/// Streaks fading behind the agents, left by veiling the last frame with the
+/// background instead of clearing it.
+#[derive(Debug, Clone, Copy)]
+pub struct Trails {
+    pub enabled: bool,
+    /// Opacity of the veil, the share of a streak fading every frame
+    fade: f32,
+}
+
+impl Trails {
+    const FADE: f32 = 0.1;
+    // Fainter veils leave ghosts, as colors are stored with 8 bits
+    const MIN_FADE: f32 = 0.02;
+    const MAX_FADE: f32 = 0.5;
+    /// Factor the fade changes by for every step of the trail length
+    const FADE_STEP: f32 = 1.25;
+
+    pub fn new() -> Self {
+        Trails {
+            enabled: false,
+            fade: Self::FADE,
+        }
+    }
+
+    pub fn lengthen(&mut self) {
+        self.fade = (self.fade / Self::FADE_STEP).max(Self::MIN_FADE);
+    }
+
+    pub fn shorten(&mut self) {
+        self.fade = (self.fade * Self::FADE_STEP).min(Self::MAX_FADE);
+    }
+
+    /// Frames a streak takes to fade to a twentieth of its brightness.
+    pub fn length(&self) -> f32 {
+        0.05_f32.ln() / (1.0 - self.fade).ln()
+    }
+
+    /// Opacity of the veil, the share of a streak fading every frame.
+    pub fn fade(&self) -> f32 {
+        self.fade
+    }
+
+    /// Veil the last frame with the background color.
+    pub fn display(&self, draw: &Draw, win_rect: Rect, background: Rgba) {
+        draw.rect().xy(win_rect.xy()).wh(win_rect.wh()).color(rgba(
+            background.red,
+            background.green,
+            background.blue,
+            self.fade,
+        ));
+    }
+}
+
+impl Default for Trails {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,73 @@
+use std::cell::Cell;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+/// Flag set on the middle slot when it holds a value the reader hasn't taken yet
+const FRESH: usize = 4;
+
+/// Three slots handing values from a writer thread to a reader thread. The writer
+/// fills its own slot and swaps it with the middle one, and the reader swaps the
+/// middle one with its own when it is fresh, so neither ever waits on the other.
+struct Slots<T> {
+    /// Every slot only ever belongs to one side at a time, so its lock is never contended
+    slots: [Mutex<T>; 3],
+    /// Slot between the writer and the reader, with the fresh flag
+    middle: AtomicUsize,
+}
+
+/// Side of a triple buffer publishing values.
+pub struct Writer<T> {
+    shared: Arc<Slots<T>>,
+    slot: usize,
+}
+
+/// Side of a triple buffer reading the latest published value.
+pub struct Reader<T> {
+    shared: Arc<Slots<T>>,
+    slot: Cell<usize>,
+}
+
+/// Triple buffer starting with the same value in every slot.
+pub fn triple_buffer<T: Clone>(initial: T) -> (Writer<T>, Reader<T>) {
+    let shared = Arc::new(Slots {
+        slots: [
+            Mutex::new(initial.clone()),
+            Mutex::new(initial.clone()),
+            Mutex::new(initial),
+        ],
+        middle: AtomicUsize::new(1),
+    });
+    let writer = Writer {
+        shared: shared.clone(),
+        slot: 0,
+    };
+    let reader = Reader {
+        shared,
+        slot: Cell::new(2),
+    };
+    (writer, reader)
+}
+
+impl<T> Writer<T> {
+    /// Value to fill in before publishing it.
+    pub fn value(&mut self) -> MutexGuard<'_, T> {
+        self.shared.slots[self.slot].lock().unwrap()
+    }
+
+    /// Hand the filled value over to the reader, replacing any it hasn't taken yet.
+    pub fn publish(&mut self) {
+        let previous = self.shared.middle.swap(self.slot | FRESH, Ordering::AcqRel);
+        self.slot = previous & !FRESH;
+    }
+}
+
+impl<T> Reader<T> {
+    /// Latest published value, or the one read last if nothing new was published.
+    pub fn latest(&self) -> MutexGuard<'_, T> {
+        if self.shared.middle.load(Ordering::Acquire) & FRESH != 0 {
+            let previous = self.shared.middle.swap(self.slot.get(), Ordering::AcqRel);
+            self.slot.set(previous & !FRESH);
+        }
+        self.shared.slots[self.slot.get()].lock().unwrap()
+    }
+}
@@ -0,0 +1,157 @@
+use crate::config::Config;
+use serde::de::Error;
+use serde::{Deserialize, Deserializer};
+
+/// Flocking parameter adjusted with the keyboard, changed by keyframes and drawn
+/// at random, named in the configuration by its key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tunable {
+    SeparationWeight,
+    AlignmentWeight,
+    CohesionWeight,
+    DetectionRadius,
+    MinDistance,
+    PanicRadius,
+    MaxForce,
+    MaxTurnRate,
+    /// Speed of the first species, the others keeping their relative speeds
+    Speed,
+}
+
+impl Tunable {
+    /// Steps across the range of a parameter
+    const STEPS: f32 = 50.0;
+
+    pub const ALL: [Tunable; 9] = [
+        Tunable::SeparationWeight,
+        Tunable::AlignmentWeight,
+        Tunable::CohesionWeight,
+        Tunable::DetectionRadius,
+        Tunable::MinDistance,
+        Tunable::PanicRadius,
+        Tunable::MaxForce,
+        Tunable::MaxTurnRate,
+        Tunable::Speed,
+    ];
+
+    /// Keys of the parameters in the configuration, in the order of [`Tunable::ALL`]
+    pub const KEYS: [&'static str; Tunable::ALL.len()] = [
+        "separation_weight",
+        "alignment_weight",
+        "cohesion_weight",
+        "detection_radius",
+        "min_distance",
+        "panic_radius",
+        "max_force",
+        "max_turn_rate",
+        "speed",
+    ];
+
+    pub fn next(self) -> Self {
+        match self {
+            Tunable::SeparationWeight => Tunable::AlignmentWeight,
+            Tunable::AlignmentWeight => Tunable::CohesionWeight,
+            Tunable::CohesionWeight => Tunable::DetectionRadius,
+            Tunable::DetectionRadius => Tunable::MinDistance,
+            Tunable::MinDistance => Tunable::PanicRadius,
+            Tunable::PanicRadius => Tunable::MaxForce,
+            Tunable::MaxForce => Tunable::MaxTurnRate,
+            Tunable::MaxTurnRate => Tunable::Speed,
+            Tunable::Speed => Tunable::SeparationWeight,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Tunable::SeparationWeight => "separation weight",
+            Tunable::AlignmentWeight => "alignment weight",
+            Tunable::CohesionWeight => "cohesion weight",
+            Tunable::DetectionRadius => "detection radius",
+            Tunable::MinDistance => "separation distance",
+            Tunable::PanicRadius => "panic radius",
+            Tunable::MaxForce => "max force",
+            Tunable::MaxTurnRate => "max turn rate",
+            Tunable::Speed => "speed",
+        }
+    }
+
+    pub fn key(self) -> &'static str {
+        Self::KEYS[self as usize]
+    }
+
+    /// Parameter named by a key of the configuration.
+    pub fn from_key(key: &str) -> Option<Self> {
+        Tunable::ALL
+            .into_iter()
+            .find(|tunable| tunable.key() == key)
+    }
+
+    /// Values the parameter can be tuned between.
+    pub fn range(self) -> (f32, f32) {
+        match self {
+            Tunable::SeparationWeight | Tunable::AlignmentWeight | Tunable::CohesionWeight => {
+                (0.0, 5.0)
+            }
+            Tunable::DetectionRadius => (10.0, 200.0),
+            Tunable::MinDistance => (5.0, 100.0),
+            Tunable::PanicRadius => (20.0, 400.0),
+            Tunable::MaxForce => (0.005, 0.5),
+            Tunable::MaxTurnRate => (0.01, 1.0),
+            Tunable::Speed => (0.1, 5.0),
+        }
+    }
+
+    pub fn value(self, config: &Config) -> f32 {
+        let agent = &config.agent;
+        match self {
+            Tunable::SeparationWeight => agent.separation_weight,
+            Tunable::AlignmentWeight => agent.alignment_weight,
+            Tunable::CohesionWeight => agent.cohesion_weight,
+            Tunable::DetectionRadius => agent.detection_radius,
+            Tunable::MinDistance => agent.min_distance,
+            Tunable::PanicRadius => agent.panic_radius,
+            Tunable::MaxForce => agent.max_force,
+            Tunable::MaxTurnRate => agent.max_turn_rate,
+            Tunable::Speed => config.species[0].speed,
+        }
+    }
+
+    /// Configuration with the parameter raised by a number of steps, or lowered
+    /// when negative, staying within its range.
+    pub fn adjust(self, config: &Config, steps: f32) -> Config {
+        let (min, max) = self.range();
+        let value = (self.value(config) + steps * (max - min) / Self::STEPS).clamp(min, max);
+
+        let mut config = config.clone();
+        self.set(&mut config, value);
+        config
+    }
+
+    pub fn set(self, config: &mut Config, value: f32) {
+        let agent = &mut config.agent;
+        match self {
+            Tunable::SeparationWeight => agent.separation_weight = value,
+            Tunable::AlignmentWeight => agent.alignment_weight = value,
+            Tunable::CohesionWeight => agent.cohesion_weight = value,
+            Tunable::DetectionRadius => agent.detection_radius = value,
+            Tunable::MinDistance => agent.min_distance = value,
+            Tunable::PanicRadius => agent.panic_radius = value,
+            Tunable::MaxForce => agent.max_force = value,
+            Tunable::MaxTurnRate => agent.max_turn_rate = value,
+            Tunable::Speed => {
+                let factor = value / config.species[0].speed.max(f32::EPSILON);
+                for species in config.species.iter_mut() {
+                    species.speed *= factor;
+                }
+            }
+        }
+    }
+}
+
+/// Read a parameter by its key in the configuration.
+impl<'de> Deserialize<'de> for Tunable {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let key = String::deserialize(deserializer)?;
+        Tunable::from_key(&key).ok_or_else(|| D::Error::unknown_field(&key, &Tunable::KEYS))
+    }
+}
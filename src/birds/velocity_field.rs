@@ -0,0 +1,87 @@
+use crate::agents::Agents;
+use crate::index::NeighborIndex;
+use crate::spatial_hash::SpatialHash;
+use nannou::prelude::*;
+
+/// Average velocity of the flock around every occupied cell of the spatial grid,
+/// drawn as arrows to show the murmuration flowing like a fluid. Holds on to its
+/// grid, only moving the agents that changed cell from one frame to the next.
+pub struct VelocityField {
+    pub enabled: bool,
+    grid: SpatialHash,
+    /// Center of every occupied cell and the average velocity around it
+    arrows: Vec<(Vec2, Vec2)>,
+    found: Vec<usize>,
+}
+
+impl VelocityField {
+    pub const COLOR: (f32, f32, f32, f32) = (0.6, 0.9, 1.0, 0.7);
+
+    /// Length of the arrows per unit of speed
+    const ARROW_SCALE: f32 = 15.0;
+    /// Agents averaged within this distance of a cell center, reaching into
+    /// the neighboring cells to smooth the field
+    const RADIUS: f32 = SpatialHash::CELL_SIZE;
+
+    pub fn new() -> Self {
+        VelocityField {
+            enabled: false,
+            grid: SpatialHash::new(&Agents::new()),
+            arrows: Vec::new(),
+            found: Vec::new(),
+        }
+    }
+
+    /// Average the velocities of the agents around every cell holding one, from
+    /// the bottom row of cells up so arrows are drawn in the same order every run.
+    pub fn update(&mut self, agents: &Agents) {
+        let VelocityField {
+            grid,
+            arrows,
+            found,
+            ..
+        } = self;
+        grid.update(agents);
+        arrows.clear();
+        for center in grid.occupied() {
+            found.clear();
+            grid.within(center, Self::RADIUS, found);
+            let (sum, count) = found
+                .iter()
+                .filter(|&&i| {
+                    agents.positions[i].distance_squared(center) < Self::RADIUS * Self::RADIUS
+                })
+                .fold((Vec2::ZERO, 0), |(sum, count), &i| {
+                    (sum + agents.velocities[i], count + 1)
+                });
+            if count > 0 {
+                arrows.push((center, sum / count as f32));
+            }
+        }
+        arrows.sort_unstable_by(|(a, _), (b, _)| a.y.total_cmp(&b.y).then(a.x.total_cmp(&b.x)));
+    }
+
+    /// Draw an arrow along the average velocity around every cell, longer where
+    /// the flock flies faster and more aligned.
+    pub fn display(&self, draw: &Draw, color: Rgba) {
+        for &(center, velocity) in &self.arrows {
+            let half = velocity * Self::ARROW_SCALE * 0.5;
+            if half.length_squared() < 1.0 {
+                continue;
+            }
+            draw.arrow()
+                .start(center - half)
+                .end(center + half)
+                .weight(1.5)
+                .head_length(5.0)
+                .head_width(3.0)
+                .color(color);
+        }
+    }
+}
+
+impl Default for VelocityField {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,61 @@
+use crate::agents::Agents;
+use nannou::prelude::*;
+use nannou::rand::Rng;
+
+/// Angular noise perturbing the headings of agents, as in the Vicsek model.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Vicsek {
+    /// Width of the range of random turns, from 0 to a full turn
+    pub noise: f32,
+}
+
+impl Vicsek {
+    pub const COLOR: (f32, f32, f32, f32) = (0.8, 0.8, 0.8, 1.0);
+    const NOISE_STEP: f32 = TAU / 20.0;
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn increase(&mut self) {
+        self.noise = (self.noise + Self::NOISE_STEP).min(TAU);
+    }
+
+    pub fn decrease(&mut self) {
+        self.noise = (self.noise - Self::NOISE_STEP).max(0.0);
+    }
+
+    /// Random turn within the noise range.
+    pub fn turn(&self, rng: &mut impl Rng) -> f32 {
+        (rng.gen::<f32>() - 0.5) * self.noise
+    }
+
+    /// Order of the flock, from 0 when agents head every which way to 1 when
+    /// they all head the same way.
+    pub fn polarization(agents: &Agents) -> f32 {
+        if agents.is_empty() {
+            return 0.0;
+        }
+        let headings = agents.velocities.iter().fold(Vec2::ZERO, |sum, velocity| {
+            sum + velocity.normalize_or_zero()
+        });
+        headings.length() / agents.len() as f32
+    }
+
+    pub fn display(&self, draw: &Draw, win_rect: Rect, agents: &Agents, color: Rgba) {
+        let text = format!(
+            "noise {:.2}\npolarization {:.2}",
+            self.noise,
+            Self::polarization(agents)
+        );
+
+        let area = win_rect.pad(20.0);
+        draw.text(&text)
+            .xy(area.xy())
+            .wh(area.wh())
+            .center_justify()
+            .align_text_top()
+            .font_size(14)
+            .color(color);
+    }
+}
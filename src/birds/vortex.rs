@@ -0,0 +1,71 @@
+use nannou::prelude::*;
+
+/// Rotational field placed by the user that swirls agents around its center.
+#[derive(Debug, Clone, Copy)]
+pub struct Vortex {
+    pub position: Vec2,
+    /// Positive for counter-clockwise rotation, negative for clockwise
+    pub strength: f32,
+}
+
+impl Vortex {
+    pub const COLOR: (f32, f32, f32, f32) = (0.6, 0.4, 1.0, 0.6);
+
+    const STRENGTH: f32 = 0.08;
+
+    /// Distance beyond which the vortex has no effect
+    const RADIUS: f32 = 200.0;
+
+    pub fn counter_clockwise(position: Vec2) -> Self {
+        Vortex {
+            position,
+            strength: Self::STRENGTH,
+        }
+    }
+
+    pub fn clockwise(position: Vec2) -> Self {
+        Vortex {
+            position,
+            strength: -Self::STRENGTH,
+        }
+    }
+
+    /// Force tangent to the circle around the center, decaying linearly to
+    /// nothing at the edge of the vortex.
+    pub fn force(&self, position: Vec2) -> Vec2 {
+        let offset = position - self.position;
+        let distance = offset.length();
+        if distance >= Self::RADIUS {
+            return Vec2::ZERO;
+        }
+        let decay = 1.0 - distance / Self::RADIUS;
+        offset.normalize_or_zero().perp() * self.strength * decay
+    }
+
+    pub fn display(&self, draw: &Draw, color: Rgba) {
+        // Spiral arms winding in the direction of rotation
+        let arms = 3;
+        for arm in 0..arms {
+            let offset = arm as f32 * TAU / arms as f32;
+            let points = (0..=32).map(|i| {
+                let t = i as f32 / 32.0;
+                let angle = offset + t * PI * self.strength.signum();
+                self.position + vec2(angle.cos(), angle.sin()) * t * Self::RADIUS
+            });
+            draw.polyline().weight(1.0).points(points).color(Rgba::new(
+                color.red,
+                color.green,
+                color.blue,
+                color.alpha * 0.5,
+            ));
+        }
+
+        // Reach of the vortex
+        draw.ellipse()
+            .xy(self.position)
+            .radius(Self::RADIUS)
+            .no_fill()
+            .stroke_weight(1.0)
+            .stroke(color);
+    }
+}
@@ -0,0 +1,60 @@
+use nannou::prelude::*;
+
+/// Line segment drawn by the user that agents steer away from.
+#[derive(Debug, Clone, Copy)]
+pub struct Wall {
+    pub start: Vec2,
+    pub end: Vec2,
+}
+
+impl Wall {
+    pub const COLOR: (f32, f32, f32, f32) = (0.6, 0.6, 0.6, 1.0);
+    const THICKNESS: f32 = 4.0;
+
+    pub fn new(start: Vec2, end: Vec2) -> Self {
+        Wall { start, end }
+    }
+
+    /// Point of the wall closest to a position.
+    pub fn closest_point(&self, position: Vec2) -> Vec2 {
+        let segment = self.end - self.start;
+        let length_squared = segment.length_squared();
+        if length_squared == 0.0 {
+            return self.start;
+        }
+        let t = ((position - self.start).dot(segment) / length_squared).clamp(0.0, 1.0);
+        self.start + segment * t
+    }
+
+    /// Check if the wall crosses the segment between two points.
+    pub fn blocks(&self, start: Vec2, end: Vec2) -> bool {
+        let offset = end - start;
+        let distance = offset.length();
+        distance > 0.0
+            && self
+                .raycast(start, offset / distance)
+                .is_some_and(|hit| hit <= distance)
+    }
+
+    /// Distance along a ray of unit direction at which it crosses the wall.
+    pub fn raycast(&self, origin: Vec2, direction: Vec2) -> Option<f32> {
+        let segment = self.end - self.start;
+        let denominator = direction.perp_dot(segment);
+        if denominator == 0.0 {
+            return None;
+        }
+        let offset = self.start - origin;
+        let distance = offset.perp_dot(segment) / denominator;
+        let along = offset.perp_dot(direction) / denominator;
+        (distance >= 0.0 && (0.0..=1.0).contains(&along)).then_some(distance)
+    }
+
+    pub fn display(&self, draw: &Draw, color: Rgba) {
+        draw.line()
+            .start(self.start)
+            .end(self.end)
+            .weight(Self::THICKNESS)
+            .caps_round()
+            .color(color);
+    }
+}
@@ -0,0 +1,47 @@
+use notify::{recommended_watcher, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{channel, Receiver};
+
+/// Filesystem notifier telling when a file changes.
+pub struct FileWatcher {
+    path: PathBuf,
+    events: Receiver<notify::Result<notify::Event>>,
+    // Stops watching when dropped
+    _watcher: RecommendedWatcher,
+}
+
+impl FileWatcher {
+    /// Watch a file, even one that doesn't exist yet. Its directory is watched
+    /// rather than the file itself, as editors often save by replacing the file.
+    pub fn new(path: impl AsRef<Path>) -> notify::Result<Self> {
+        let path = std::path::absolute(path)?;
+        let directory = path.parent().unwrap_or(Path::new("/"));
+
+        let (sender, events) = channel();
+        let mut watcher = recommended_watcher(sender)?;
+        watcher.watch(directory, RecursiveMode::NonRecursive)?;
+
+        Ok(FileWatcher {
+            path,
+            events,
+            _watcher: watcher,
+        })
+    }
+
+    /// Whether the file was written, created or replaced since last asked.
+    pub fn changed(&self) -> bool {
+        self.events
+            .try_iter()
+            .filter_map(Result::ok)
+            .filter(|event| {
+                matches!(
+                    event.kind,
+                    EventKind::Create(_) | EventKind::Modify(_) | EventKind::Any
+                )
+            })
+            // Drain every event rather than stopping at the first change
+            .fold(false, |changed, event| {
+                changed | event.paths.contains(&self.path)
+            })
+    }
+}
@@ -0,0 +1,56 @@
+use nannou::prelude::*;
+
+/// Sequence of points the flock migrates through.
+pub struct Waypoints {
+    points: Vec<Vec2>,
+    current: usize,
+}
+
+impl Waypoints {
+    pub const COLOR: (f32, f32, f32, f32) = (0.4, 0.4, 0.8, 1.0);
+    const NUM_WAYPOINTS: usize = 6;
+    const SIZE: f32 = 8.0;
+
+    /// Distance to the flock centroid at which the next waypoint becomes the target
+    const ARRIVAL_RADIUS: f32 = 150.0;
+
+    pub fn new(win_rect: Rect) -> Self {
+        // Lay the waypoints out on a loop inside the window
+        let radius = win_rect.wh() * 0.35;
+        let points = (0..Self::NUM_WAYPOINTS)
+            .map(|i| {
+                let angle = i as f32 / Self::NUM_WAYPOINTS as f32 * TAU;
+                win_rect.xy() + vec2(angle.cos(), angle.sin()) * radius
+            })
+            .collect();
+
+        Waypoints { points, current: 0 }
+    }
+
+    pub fn target(&self) -> Vec2 {
+        self.points[self.current]
+    }
+
+    /// Move on to the next waypoint once the flock has reached the current one.
+    pub fn update(&mut self, centroid: Vec2) {
+        if centroid.distance(self.target()) < Self::ARRIVAL_RADIUS {
+            self.current = (self.current + 1) % self.points.len();
+        }
+    }
+
+    pub fn display(&self, draw: &Draw, color: Rgba) {
+        // Draw the path between waypoints
+        let path = self.points.iter().chain(self.points.first()).copied();
+        draw.polyline().weight(1.0).points(path).color(color);
+
+        // Draw the waypoints, highlighting the current target
+        for (i, point) in self.points.iter().enumerate() {
+            let size = if i == self.current {
+                Self::SIZE * 2.0
+            } else {
+                Self::SIZE
+            };
+            draw.ellipse().xy(*point).radius(size).color(color);
+        }
+    }
+}
@@ -0,0 +1,178 @@
+use crate::agent::Agent;
+use crate::agents::Agents;
+use crate::avoidance::Avoidance;
+use crate::boundary::Boundary;
+use crate::brain::Brain;
+use crate::config::Config;
+use crate::flow_field::FlowField;
+use crate::force_point::ForcePoint;
+use crate::index::NeighborIndex;
+use crate::integrator::Integrator;
+use crate::kernel::Kernel;
+use crate::long_range::LongRange;
+use crate::mouse::MouseForce;
+use crate::movement::Movement;
+use crate::neighbor_cache::NeighborCache;
+use crate::obstacle::Obstacle;
+use crate::path::Path;
+use crate::perception::Perception;
+use crate::pheromones::Pheromones;
+use crate::predator::Predator;
+use crate::quadtree::Quadtree;
+use crate::terrain::Terrain;
+use crate::vicsek::Vicsek;
+use crate::vortex::Vortex;
+use crate::wall::Wall;
+use crate::zones::{Phase, Zones};
+use nannou::prelude::*;
+use noise::Perlin;
+
+/// Everything an agent perceives of its surroundings during an update.
+pub struct World<'a> {
+    /// Configuration read once for the whole step
+    pub config: &'a Config,
+    pub bounds: Rect,
+    pub boundary: Boundary,
+    /// Distance from the edges at which agents start steering back when steering
+    pub margin: f32,
+    pub brain: Brain,
+    pub zones: Zones,
+    pub kernel: Kernel,
+    pub perception: Perception,
+    /// Whether the personality traits of agents affect their behavior
+    pub personalities: bool,
+    pub vicsek: Vicsek,
+    /// Whether agents fly in V formations
+    pub formation: bool,
+    pub movement: Movement,
+    /// How agents moved by forces advance over a step
+    pub integrator: Integrator,
+    /// Whether agents bank into turns and ease into speed changes
+    pub banking: bool,
+    /// Whether flapping costs agents energy they recover while gliding
+    pub flapping: bool,
+    pub time: f32,
+    /// Number of the simulation step being updated
+    pub step: u64,
+    /// How dark it is, from 0 in daylight to 1 at midnight
+    pub darkness: f32,
+    pub agents: &'a Agents,
+    /// Structure for finding the agents close to a position
+    pub index: &'a dyn NeighborIndex,
+    /// Neighbor lists of the agents reused between refreshes, if enabled
+    pub neighbor_cache: Option<&'a NeighborCache>,
+    /// Quadtree of the agents pulling them towards the distant parts of the flock, if enabled
+    pub cohesion_tree: Option<&'a Quadtree>,
+    pub long_range: LongRange,
+    pub leaders: &'a [Agent],
+    pub predators: &'a [Predator],
+    pub obstacles: &'a [Obstacle],
+    pub walls: &'a [Wall],
+    pub avoidance: Avoidance,
+    pub flow_field: &'a FlowField,
+    pub force_points: &'a [ForcePoint],
+    pub vortices: &'a [Vortex],
+    /// Shared noise agents sample to wander
+    pub noise: &'a Perlin,
+    pub waypoint: Option<Vec2>,
+    pub migration: Option<Vec2>,
+    pub attractor: Option<Vec2>,
+    pub path: Option<&'a Path>,
+    pub mouse: Vec2,
+    pub mouse_force: MouseForce,
+    pub pheromones: Option<&'a Pheromones>,
+    pub terrain: Option<&'a Terrain>,
+    pub food: Option<&'a [Vec2]>,
+}
+
+impl<'a> World<'a> {
+    /// World on the plane with every feature off, agents only following the rules
+    /// with their neighbors and wrapping around the edges.
+    pub fn plain(
+        config: &'a Config,
+        bounds: Rect,
+        agents: &'a Agents,
+        index: &'a dyn NeighborIndex,
+        flow_field: &'a FlowField,
+        noise: &'a Perlin,
+    ) -> Self {
+        World {
+            config,
+            bounds,
+            boundary: Boundary::Wrap,
+            margin: 100.0,
+            brain: Brain::Rules,
+            zones: Zones::new(Phase::Torus),
+            kernel: Kernel::Uniform,
+            perception: Perception::Metric,
+            personalities: false,
+            vicsek: Vicsek::new(),
+            formation: false,
+            movement: Movement::Classic,
+            integrator: Integrator::Euler,
+            banking: false,
+            flapping: false,
+            time: 0.0,
+            step: 0,
+            darkness: 0.0,
+            agents,
+            index,
+            neighbor_cache: None,
+            cohesion_tree: None,
+            long_range: LongRange::new(),
+            leaders: &[],
+            predators: &[],
+            obstacles: &[],
+            walls: &[],
+            avoidance: Avoidance::Radial,
+            flow_field,
+            force_points: &[],
+            vortices: &[],
+            noise,
+            waypoint: None,
+            migration: None,
+            attractor: None,
+            path: None,
+            mouse: Vec2::ZERO,
+            mouse_force: MouseForce::Off,
+            pheromones: None,
+            terrain: None,
+            food: None,
+        }
+    }
+
+    /// Fill `found` with the indices of the agents close enough to an agent to
+    /// possibly be within a radius of it, from its cached neighbor list when it
+    /// covers the radius.
+    pub fn around(&self, agent: &Agent, radius: f32, found: &mut Vec<usize>) {
+        found.clear();
+        match self
+            .neighbor_cache
+            .and_then(|cache| cache.within(agent, radius))
+        {
+            Some(list) => found.extend_from_slice(list),
+            None => self.index.within(agent.position, radius, found),
+        }
+    }
+
+    /// Fill `nearest` with the indices of the agents nearest to a position
+    /// accepted by a filter, with their distance, at most `count` of them.
+    pub fn nearest(
+        &self,
+        position: Vec2,
+        count: usize,
+        accept: impl Fn(usize, f32) -> bool,
+        nearest: &mut Vec<(usize, f32)>,
+    ) {
+        self.index.nearest(position, count, &accept, nearest)
+    }
+
+    /// Check if nothing stands between two points.
+    pub fn line_of_sight(&self, start: Vec2, end: Vec2) -> bool {
+        !self
+            .obstacles
+            .iter()
+            .any(|obstacle| obstacle.blocks(start, end))
+            && !self.walls.iter().any(|wall| wall.blocks(start, end))
+    }
+}
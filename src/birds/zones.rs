@@ -0,0 +1,75 @@
+use nannou::prelude::*;
+
+/// Radii of the three zones of Couzin's model. Neighbors inside the zone of
+/// repulsion are avoided, those in the zone of orientation are aligned with and
+/// those in the zone of attraction are approached.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Zones {
+    pub phase: Phase,
+    pub repulsion: f32,
+    pub orientation: f32,
+    pub attraction: f32,
+}
+
+/// Collective behaviors the model transitions between as the zone of orientation widens.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Phase {
+    Swarm,
+    Torus,
+    DynamicParallel,
+    HighlyParallel,
+}
+
+impl Zones {
+    pub const COLOR: (f32, f32, f32, f32) = (0.8, 0.8, 0.8, 1.0);
+
+    /// Radius of the zone of repulsion, about the length of a body
+    const REPULSION: f32 = 15.0;
+
+    /// Width of the zone of attraction
+    const ATTRACTION_WIDTH: f32 = 14.0 * Self::REPULSION;
+
+    pub fn new(phase: Phase) -> Self {
+        // Width of the zone of orientation, in body lengths
+        let orientation_width = match phase {
+            Phase::Swarm => 0.0,
+            Phase::Torus => 2.0,
+            Phase::DynamicParallel => 6.0,
+            Phase::HighlyParallel => 13.0,
+        } * Self::REPULSION;
+
+        let orientation = Self::REPULSION + orientation_width;
+        Zones {
+            phase,
+            repulsion: Self::REPULSION,
+            orientation,
+            attraction: orientation + Self::ATTRACTION_WIDTH,
+        }
+    }
+
+    /// Zones producing the next collective behavior.
+    pub fn next(self) -> Self {
+        Zones::new(match self.phase {
+            Phase::Swarm => Phase::Torus,
+            Phase::Torus => Phase::DynamicParallel,
+            Phase::DynamicParallel => Phase::HighlyParallel,
+            Phase::HighlyParallel => Phase::Swarm,
+        })
+    }
+
+    pub fn display(&self, draw: &Draw, win_rect: Rect, color: Rgba) {
+        let text = format!(
+            "zones: {:?}\nrepulsion {:.0}  orientation {:.0}  attraction {:.0}",
+            self.phase, self.repulsion, self.orientation, self.attraction
+        );
+
+        let area = win_rect.pad(20.0);
+        draw.text(&text)
+            .xy(area.xy())
+            .wh(area.wh())
+            .right_justify()
+            .align_text_bottom()
+            .font_size(14)
+            .color(color);
+    }
+}
@@ -0,0 +1,252 @@
+//! Steady-state steps of the flock allocate nothing once their buffers have
+//! grown, counted by a global allocator wrapping the system one.
+
+use birds::agent::Agent;
+use birds::agents::Agents;
+use birds::boundary::Boundary;
+use birds::call::Call;
+use birds::camera::Camera;
+use birds::collision::Collider;
+use birds::config::Config;
+use birds::flow_field::FlowField;
+use birds::index::{Indexing, NeighborIndex};
+use birds::neighbor_cache::NeighborCache;
+use birds::perception::Perception;
+use birds::predator::{Hunt, Predator};
+use birds::pursuit::Pursuit;
+use birds::quadtree::Quadtree;
+use birds::scratch::{Scratch, ScratchPool};
+use birds::spatial_hash::CellSort;
+use birds::world::World;
+use nannou::prelude::*;
+use nannou::rand::rngs::SmallRng;
+use nannou::rand::{Rng, SeedableRng};
+use noise::Perlin;
+use rayon::{ThreadPool, ThreadPoolBuilder};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, OnceLock};
+
+/// System allocator counting the allocations of the threads a test runs on.
+struct Counting;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+/// Held by the test counting allocations, as tests run in parallel
+static COUNTING: Mutex<()> = Mutex::new(());
+
+thread_local! {
+    /// Whether the allocations of the thread are counted
+    static COUNTED: Cell<bool> = const { Cell::new(false) };
+}
+
+fn count() {
+    if COUNTED.with(Cell::get) {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        count();
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        count();
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: Counting = Counting;
+
+/// Run a test counting the allocations of its thread and of the threads of a
+/// pool it may run parallel work on, shared by the tests so its threads never exit.
+fn counted(test: impl FnOnce(&ThreadPool) + Send) {
+    static POOL: OnceLock<ThreadPool> = OnceLock::new();
+    let pool = POOL.get_or_init(|| {
+        ThreadPoolBuilder::new()
+            .num_threads(4)
+            .start_handler(|_| COUNTED.with(|counted| counted.set(true)))
+            .build()
+            .unwrap()
+    });
+
+    let _counting = COUNTING.lock().unwrap_or_else(|error| error.into_inner());
+    COUNTED.with(|counted| counted.set(true));
+    test(pool);
+    COUNTED.with(|counted| counted.set(false));
+}
+
+/// Allocations the counted threads made while running a closure.
+fn allocations(f: impl FnOnce()) -> usize {
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    f();
+    ALLOCATIONS.load(Ordering::Relaxed) - before
+}
+
+/// Flock spread over bounds, with the world it is updated in.
+fn flock(bounds: Rect, config: &Config, rng: &mut SmallRng) -> (Agents, FlowField, Perlin) {
+    let agents: Agents = (0..2_000)
+        .map(|i| Agent::new(bounds, i % config.species.len(), config, rng))
+        .collect();
+    (agents, FlowField::new(rng), Perlin::new(0))
+}
+
+#[test]
+fn agent_updates_do_not_allocate() {
+    counted(|_| agent_updates());
+}
+
+fn agent_updates() {
+    let bounds = Rect::from_w_h(2000.0, 2000.0);
+    let config = Config::get();
+    let mut rng = SmallRng::seed_from_u64(0);
+    let (agents, flow_field, noise) = flock(bounds, &config, &mut rng);
+
+    let indexings = [
+        Indexing::BruteForce,
+        Indexing::Grid,
+        Indexing::Quadtree,
+        Indexing::KdTree,
+    ];
+    let perceptions = [Perception::Metric, Perception::Topological];
+    for (indexing, perception) in indexings
+        .into_iter()
+        .flat_map(|indexing| perceptions.map(|perception| (indexing, perception)))
+    {
+        let index = indexing.build(&agents);
+        let mut world = World::plain(
+            &config,
+            bounds,
+            &agents,
+            index.as_ref(),
+            &flow_field,
+            &noise,
+        );
+        world.perception = perception;
+        let mut scratch = Scratch::default();
+        let mut next = agents.clone();
+        let mut update = || {
+            next.clone_from(&agents);
+            next.for_each_mut(|agent| agent.update(&world, &mut scratch));
+        };
+
+        // The first pass grows the buffers to the largest neighborhood
+        assert!(allocations(&mut update) > 0);
+        assert_eq!(allocations(update), 0, "{indexing:?} {perception:?}");
+    }
+}
+
+/// Whole steps of the flock as the sketch takes them on the plane, sorting the
+/// agents by cell, with predators, collisions, calls, the neighbor cache and the
+/// quadtree of long-range cohesion.
+#[test]
+fn plane_steps_do_not_allocate() {
+    counted(|pool| {
+        for indexing in [
+            Indexing::BruteForce,
+            Indexing::Grid,
+            Indexing::Quadtree,
+            Indexing::KdTree,
+        ] {
+            pool.install(|| plane_steps(indexing));
+        }
+    });
+}
+
+fn plane_steps(indexing: Indexing) {
+    let bounds = Rect::from_w_h(2000.0, 2000.0);
+    let config = Config::get();
+    let mut rng = SmallRng::seed_from_u64(0);
+    let (mut agents, flow_field, noise) = flock(bounds, &config, &mut rng);
+    let mut previous_agents = Agents::new();
+    let mut predators: Vec<Predator> = (0..Predator::NUM_PREDATORS)
+        .map(|_| Predator::new(bounds, &mut rng))
+        .collect();
+    let mut index = indexing.build(&agents);
+    // Gathered again every step
+    let mut neighbor_cache = NeighborCache::default();
+    let mut collider = Collider::new();
+    let mut cell_sort = CellSort::new();
+    let mut cohesion_tree = Quadtree::new(&Agents::new());
+    let mut calls = Vec::new();
+    let scratch = ScratchPool::new();
+    let mut predator_scratch = Scratch::default();
+
+    let mut step = || {
+        cell_sort.sort(&mut agents);
+        index.update(&agents);
+        neighbor_cache.update(&agents, index.as_ref());
+        cohesion_tree.update(&agents);
+        let world = World {
+            neighbor_cache: Some(&neighbor_cache),
+            cohesion_tree: Some(&cohesion_tree),
+            predators: &predators,
+            ..World::plain(
+                &config,
+                bounds,
+                &agents,
+                index.as_ref(),
+                &flow_field,
+                &noise,
+            )
+        };
+
+        previous_agents.clone_from(&agents);
+        previous_agents.par_for_each_mut(
+            || scratch.take(),
+            |scratch, agent| agent.update(&world, scratch),
+        );
+        std::mem::swap(&mut agents, &mut previous_agents);
+        collider.resolve(&mut agents, &config);
+
+        for (predator, pursuit) in predators
+            .iter_mut()
+            .zip([Pursuit::Intercept, Pursuit::Stalk])
+        {
+            let hunt = Hunt {
+                pursuit,
+                agents: &previous_agents,
+                index: index.as_ref(),
+            };
+            predator.update(bounds, Boundary::Wrap, 100.0, &hunt, &mut predator_scratch);
+        }
+
+        Call::emit(&agents, &mut rng, &mut calls);
+        calls.retain_mut(|call| call.update(&mut agents));
+    };
+
+    // Buffers grow over the first steps until they fit the densest neighborhoods
+    for _ in 0..30 {
+        step();
+    }
+    assert_eq!(allocations(step), 0, "{indexing:?}");
+}
+
+/// Agents in space ordered from back to front to be drawn, every frame.
+#[test]
+fn depth_order_does_not_allocate() {
+    counted(|_| {
+        let mut rng = SmallRng::seed_from_u64(0);
+        let points: Vec<Vec3> = (0..2_000)
+            .map(|_| vec3(rng.gen(), rng.gen(), rng.gen()) * 500.0)
+            .collect();
+        let mut camera = Camera::new(1000.0);
+        let mut order = Vec::new();
+        let mut draw = || {
+            camera.yaw += 0.1;
+            camera.back_to_front(points.iter().copied(), &mut order);
+        };
+
+        assert!(allocations(&mut draw) > 0);
+        assert_eq!(allocations(draw), 0);
+        assert!(order.windows(2).all(|pair| pair[0].1 >= pair[1].1));
+    });
+}